@@ -1,6 +1,25 @@
+use crate::backend::RenderBackend;
+use crate::fractals::{
+    FractalKind, ToneMap, TrapShape, DEFAULT_BAILOUT, DEFAULT_SINGLE_HUE, DEFAULT_STRIPE_FREQUENCY,
+};
+use crate::keymap::KeyMap;
+use crate::palette::Palette;
+use crate::stats::ViewState;
+use crate::viewport::buffer_len;
+use clap::{Parser, Subcommand};
 use num::Complex;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Clone, Copy)]
+/// Serialized in kebab-case, like `black-and-white`, which
+/// [`ColorScheme::from_str`] also accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum ColorScheme {
     Smooth,
     Zebra,
@@ -11,12 +30,103 @@ pub enum ColorScheme {
     Psychedelic,
     GreenGradient,
     Electric,
+    EscapeVelocity,
+    OrbitTrap,
+    /// Lights up points by their estimated distance to the set
+    DistanceEstimate,
+    Histogram,
+    /// Interpolates between the stops of a [`Palette`]
+    Gradient,
+    /// Glows by the continuous potential, optionally with equipotential lines
+    Potential,
+    /// Hue by the angle each orbit escaped at
+    Angle,
+    /// Black up to any base color, see [`Args::with_single_hue`]
+    SingleHue,
+    /// Stripes along the orbits, see [`Args::with_stripe_frequency`]
+    StripeAverage,
+}
+
+impl ColorScheme {
+    /// Every scheme, in declaration order. Location tokens store schemes by
+    /// their index here, so new ones go at the end.
+    pub const ALL: [ColorScheme; 18] = [
+        ColorScheme::Smooth,
+        ColorScheme::Zebra,
+        ColorScheme::Red,
+        ColorScheme::Blue,
+        ColorScheme::BlackAndWhite,
+        ColorScheme::Rainbow,
+        ColorScheme::Psychedelic,
+        ColorScheme::GreenGradient,
+        ColorScheme::Electric,
+        ColorScheme::EscapeVelocity,
+        ColorScheme::OrbitTrap,
+        ColorScheme::DistanceEstimate,
+        ColorScheme::Histogram,
+        ColorScheme::Gradient,
+        ColorScheme::Potential,
+        ColorScheme::Angle,
+        ColorScheme::SingleHue,
+        ColorScheme::StripeAverage,
+    ];
+}
+
+impl FromStr for ColorScheme {
+    type Err = String;
+
+    /// Parses a scheme name, ignoring case, dashes and underscores, so
+    /// `black-and-white`, `BLACK_AND_WHITE` and `BlackAndWhite` all work.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+
+        match normalized.as_str() {
+            "smooth" => Ok(Self::Smooth),
+            "zebra" => Ok(Self::Zebra),
+            "red" => Ok(Self::Red),
+            "blue" => Ok(Self::Blue),
+            "blackandwhite" => Ok(Self::BlackAndWhite),
+            "rainbow" => Ok(Self::Rainbow),
+            "psychedelic" => Ok(Self::Psychedelic),
+            "greengradient" => Ok(Self::GreenGradient),
+            "electric" => Ok(Self::Electric),
+            "escapevelocity" => Ok(Self::EscapeVelocity),
+            "orbittrap" => Ok(Self::OrbitTrap),
+            "distanceestimate" => Ok(Self::DistanceEstimate),
+            "histogram" => Ok(Self::Histogram),
+            "gradient" => Ok(Self::Gradient),
+            "potential" => Ok(Self::Potential),
+            "angle" => Ok(Self::Angle),
+            "singlehue" => Ok(Self::SingleHue),
+            "stripeaverage" => Ok(Self::StripeAverage),
+            _ => Err(format!("unknown color scheme \"{name}\"")),
+        }
+    }
 }
 
+/// Frames per second a scan refines at in a window, unless set with
+/// [`Args::try_with_max_fps`].
+pub const DEFAULT_MAX_FPS: u32 = 60;
+
 #[derive(Clone, Copy)]
 pub struct ScanConfig {
     pub enabled: bool,
+    /// Stride of the first pass, at least 1
     pub initial_stride: u32,
+    /// Whether the window is only shown whole passes, drawn into a back
+    /// buffer first, see
+    /// [`Renderer::render_to`](crate::renderer::Renderer::render_to)
+    pub double_buffered: bool,
+    /// How long a double buffered window goes without showing an unfinished
+    /// pass, for passes that take longer than that
+    pub present_interval: Duration,
+    /// The shortest time between two slices of a scan in a window, which
+    /// caps the frame rate it refines at, see [`Args::try_with_max_fps`]
+    pub frame_interval: Duration,
 }
 
 impl Default for ScanConfig {
@@ -24,23 +134,129 @@ impl Default for ScanConfig {
         Self {
             enabled: true,
             initial_stride: 8,
+            double_buffered: true,
+            present_interval: Duration::from_millis(250),
+            frame_interval: Duration::from_secs(1) / DEFAULT_MAX_FPS,
         }
     }
 }
 
+/// An environment variable that is set but can't be used.
+#[derive(Debug)]
+pub struct EnvError {
+    pub name: &'static str,
+    pub value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}={:?}: {}", self.name, self.value, self.reason)
+    }
+}
+
+impl Error for EnvError {}
+
+/// A size, region, iteration limit or scan setting [`Args`] can't be built
+/// with, from the `try_*` constructors.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    ZeroDimension,
+    /// The frame buffer wouldn't fit in memory
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+    ZeroIterations,
+    /// The corners of the region don't span any area, or aren't finite
+    EmptyBounds,
+    ZeroStride,
+    ZeroFps,
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDimension => write!(f, "Width and height must be greater than 0"),
+            Self::TooLarge { width, height } => {
+                write!(f, "{width}x{height} is too large for a frame buffer")
+            }
+            Self::ZeroIterations => write!(f, "Max iterations must be greater than 0"),
+            Self::EmptyBounds => write!(f, "The corners must be finite and span an area"),
+            Self::ZeroStride => write!(f, "Initial stride must be greater than 0"),
+            Self::ZeroFps => write!(f, "Max FPS must be greater than 0"),
+        }
+    }
+}
+
+impl Error for ArgsError {}
+
+/// Checks a frame size for [`Args::try_with_size`] and [`Args::try_new`].
+fn check_size(width: u32, height: u32) -> Result<(), ArgsError> {
+    if width == 0 || height == 0 {
+        return Err(ArgsError::ZeroDimension);
+    }
+    if buffer_len(width, height, 4).is_none() {
+        return Err(ArgsError::TooLarge { width, height });
+    }
+    Ok(())
+}
+
+/// Checks the corners of a region for [`Args::try_with_bounds`] and
+/// [`Args::try_new`].
+fn check_bounds(upper_left: Complex<f64>, lower_right: Complex<f64>) -> Result<(), ArgsError> {
+    let span = lower_right - upper_left;
+    if !(span.re.is_finite() && span.im.is_finite()) || span.re == 0.0 || span.im == 0.0 {
+        return Err(ArgsError::EmptyBounds);
+    }
+    Ok(())
+}
+
 pub struct Args {
     width: u32,
     height: u32,
     upper_left: Complex<f64>,
     lower_right: Complex<f64>,
+    /// Whether the corners were given rather than left at their defaults,
+    /// see [`Args::get_bounds`]
+    bounds_given: bool,
     max_iterations: u32,
+    bailout: f64,
+    fractal_kind: FractalKind,
     color_scheme: ColorScheme,
-    _fullscreen: bool,
+    fullscreen: bool,
+    center: Option<Complex<f64>>,
+    scale: Option<f64>,
     scan_config: ScanConfig,
+    render_backend: Option<RenderBackend>,
+    orbit_trap: TrapShape,
+    color_jitter: f64,
+    tonemap: ToneMap,
+    invert_palette: bool,
+    palette_depth_coupling: f64,
+    aa_samples: u32,
+    temporal_aa: bool,
+    palette: Option<Palette>,
+    gpu: bool,
+    compare: bool,
+    interior_color: [u8; 3],
+    interior_shading: bool,
+    single_hue: [u8; 3],
+    stripe_frequency: f64,
+    perceptual: bool,
+    keymap: Option<KeyMap>,
+    pan_speed: Option<f64>,
+    pan_inertia: bool,
+    zoom_step: Option<f64>,
+    max_scale: Option<f64>,
+    gamma: Option<f64>,
+    glow: Option<f64>,
+    glow_radius: Option<usize>,
 }
 
 #[allow(dead_code)]
 impl Args {
+    /// Like [`Args::try_new`], but panics on an invalid size or limit.
     pub fn new(
         width: u32,
         height: u32,
@@ -50,50 +266,534 @@ impl Args {
         lower_right: Complex<f64>,
         color_scheme: ColorScheme,
     ) -> Self {
-        if width <= 0 || height <= 0 {
-            panic!("Width and height must be greater than 0");
-        }
+        Self::try_new(
+            width,
+            height,
+            max_iterations,
+            fullscreen,
+            upper_left,
+            lower_right,
+            color_scheme,
+        )
+        .unwrap_or_else(|err| panic!("{err}"))
+    }
 
-        if max_iterations <= 0 {
-            panic!("Max iterations must be greater than 0");
+    /// Builds arguments for a `width` by `height` frame of the region
+    /// between the corners, or says why it can't.
+    pub fn try_new(
+        width: u32,
+        height: u32,
+        max_iterations: u32,
+        fullscreen: bool,
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+        color_scheme: ColorScheme,
+    ) -> Result<Self, ArgsError> {
+        check_size(width, height)?;
+        check_bounds(upper_left, lower_right)?;
+        if max_iterations == 0 {
+            return Err(ArgsError::ZeroIterations);
         }
 
-        Self {
+        Ok(Self {
             width,
             height,
             upper_left,
             lower_right,
+            bounds_given: true,
             max_iterations,
+            bailout: DEFAULT_BAILOUT,
+            fractal_kind: FractalKind::Mandelbrot,
             color_scheme,
-            _fullscreen: fullscreen,
+            fullscreen,
+            center: None,
+            scale: None,
             scan_config: ScanConfig::default(),
+            render_backend: None,
+            orbit_trap: TrapShape::DEFAULTS[0],
+            color_jitter: 0.0,
+            tonemap: ToneMap::None,
+            invert_palette: false,
+            palette_depth_coupling: 0.0,
+            aa_samples: 1,
+            temporal_aa: false,
+            palette: None,
+            gpu: false,
+            compare: false,
+            interior_color: [0, 0, 0],
+            interior_shading: false,
+            single_hue: DEFAULT_SINGLE_HUE,
+            stripe_frequency: DEFAULT_STRIPE_FREQUENCY,
+            perceptual: false,
+            keymap: None,
+            pan_speed: None,
+            pan_inertia: true,
+            zoom_step: None,
+            max_scale: None,
+            gamma: None,
+            glow: None,
+            glow_radius: None,
+        })
+    }
+
+    /// Builds the default arguments with any `FRUSTAL_*` environment
+    /// variables applied on top, see [`Args::with_env`].
+    pub fn from_env() -> Result<Self, EnvError> {
+        Self::default().with_env()
+    }
+
+    /// Overrides settings from the `FRUSTAL_WIDTH`, `FRUSTAL_HEIGHT`,
+    /// `FRUSTAL_MAX_ITER`, `FRUSTAL_CENTER_RE`, `FRUSTAL_CENTER_IM`,
+    /// `FRUSTAL_SCALE` and `FRUSTAL_COLOR_SCHEME` environment variables.
+    /// Unset variables leave the current value alone; command line flags
+    /// should be applied afterwards so they take precedence.
+    pub fn with_env(self) -> Result<Self, EnvError> {
+        self.with_vars(|name| std::env::var_os(name))
+    }
+
+    fn with_vars<F>(mut self, lookup: F) -> Result<Self, EnvError>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        // Reads and parses one variable, rejecting values `valid` doesn't accept
+        let var = |name: &'static str, valid: fn(f64) -> bool, requirement: &str| {
+            let Some(raw) = lookup(name) else {
+                return Ok(None);
+            };
+            let error = |value: String, reason: String| EnvError {
+                name,
+                value,
+                reason,
+            };
+
+            let value = raw.into_string().map_err(|raw| {
+                error(raw.to_string_lossy().into_owned(), "not valid UTF-8".into())
+            })?;
+            match value.trim().parse::<f64>() {
+                Ok(parsed) if valid(parsed) => Ok(Some(parsed)),
+                Ok(_) => Err(error(value, requirement.into())),
+                Err(err) => Err(error(value, err.to_string())),
+            }
+        };
+        let count = |value: f64| value >= 1.0 && value <= u32::MAX as f64 && value.fract() == 0.0;
+        let count_requirement = "must be a whole number greater than 0";
+
+        if let Some(width) = var("FRUSTAL_WIDTH", count, count_requirement)? {
+            self.width = width as u32;
+        }
+        if let Some(height) = var("FRUSTAL_HEIGHT", count, count_requirement)? {
+            self.height = height as u32;
+        }
+        if let Some(max_iterations) = var("FRUSTAL_MAX_ITER", count, count_requirement)? {
+            self.max_iterations = max_iterations as u32;
+        }
+
+        let finite = "must be a finite number";
+        let center_re = var("FRUSTAL_CENTER_RE", f64::is_finite, finite)?;
+        let center_im = var("FRUSTAL_CENTER_IM", f64::is_finite, finite)?;
+        match (center_re, center_im) {
+            (Some(re), Some(im)) => self.center = Some(Complex::new(re, im)),
+            (None, None) => {}
+            // Half a center would silently pick up the other half from elsewhere
+            (Some(re), None) => {
+                return Err(EnvError {
+                    name: "FRUSTAL_CENTER_RE",
+                    value: re.to_string(),
+                    reason: "FRUSTAL_CENTER_IM must be set as well".into(),
+                })
+            }
+            (None, Some(im)) => {
+                return Err(EnvError {
+                    name: "FRUSTAL_CENTER_IM",
+                    value: im.to_string(),
+                    reason: "FRUSTAL_CENTER_RE must be set as well".into(),
+                })
+            }
+        }
+
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        if let Some(scale) = var("FRUSTAL_SCALE", positive, "must be greater than 0")? {
+            self.scale = Some(scale);
+        }
+
+        if let Some(raw) = lookup("FRUSTAL_COLOR_SCHEME") {
+            let value = raw.to_string_lossy();
+            self.color_scheme = value.trim().parse().map_err(|reason| EnvError {
+                name: "FRUSTAL_COLOR_SCHEME",
+                value: value.clone().into_owned(),
+                reason,
+            })?;
+        }
+
+        Ok(self)
+    }
+
+    /// Picks the fractal to render. The view starts out showing all of it
+    /// unless a center or scale is given as well.
+    pub fn with_fractal_kind(mut self, fractal_kind: FractalKind) -> Self {
+        self.fractal_kind = fractal_kind;
+        self
+    }
+
+    /// Renders the multibrot set `z = z^power + c`, see
+    /// [`multibrot`](crate::fractals::multibrot). Power 2 is the Mandelbrot
+    /// set.
+    ///
+    /// # Panics
+    ///
+    /// If `power` isn't finite and greater than 1.
+    pub fn with_power(mut self, power: f64) -> Self {
+        if !(power.is_finite() && power > 1.0) {
+            panic!("Power must be greater than 1");
+        }
+
+        self.fractal_kind = FractalKind::multibrot(power);
+        self
+    }
+
+    pub fn with_color_scheme(mut self, color_scheme: ColorScheme) -> Self {
+        self.color_scheme = color_scheme;
+        self
+    }
+
+    /// Like [`Args::try_with_bounds`], but panics on corners that don't span
+    /// an area.
+    pub fn with_bounds(self, upper_left: Complex<f64>, lower_right: Complex<f64>) -> Self {
+        self.try_with_bounds(upper_left, lower_right)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Shows the region between the corners, fitted to the frame, see
+    /// [`Args::get_bounds`]. A center or scale that is also given wins over
+    /// the corners.
+    pub fn try_with_bounds(
+        mut self,
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+    ) -> Result<Self, ArgsError> {
+        check_bounds(upper_left, lower_right)?;
+
+        self.upper_left = upper_left;
+        self.lower_right = lower_right;
+        self.bounds_given = true;
+        Ok(self)
+    }
+
+    /// Centers the view on `center`.
+    pub fn with_center(mut self, center: Complex<f64>) -> Self {
+        self.center = Some(center);
+        self
+    }
+
+    /// Sets how many units the view spans across its smaller side.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        if !(scale.is_finite() && scale > 0.0) {
+            panic!("Scale must be greater than 0");
         }
+
+        self.scale = Some(scale);
+        self
     }
 
-    pub fn with_scan_config(mut self, enabled: bool, initial_stride: u32) -> Self {
+    /// Like [`Args::try_with_scan_config`], but panics on a stride of 0.
+    pub fn with_scan_config(self, enabled: bool, initial_stride: u32) -> Self {
+        self.try_with_scan_config(enabled, initial_stride)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_with_scan_config(
+        mut self,
+        enabled: bool,
+        initial_stride: u32,
+    ) -> Result<Self, ArgsError> {
+        if initial_stride == 0 {
+            return Err(ArgsError::ZeroStride);
+        }
+
         self.scan_config = ScanConfig {
             enabled,
             initial_stride,
+            ..self.scan_config
         };
+        Ok(self)
+    }
+
+    /// Like [`Args::try_with_max_fps`], but panics on 0 frames per second.
+    pub fn with_max_fps(self, fps: u32) -> Self {
+        self.try_with_max_fps(fps)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Caps how many slices of a scan a window draws per second, leaving the
+    /// CPU idle in between rather than spinning.
+    pub fn try_with_max_fps(mut self, fps: u32) -> Result<Self, ArgsError> {
+        if fps == 0 {
+            return Err(ArgsError::ZeroFps);
+        }
+
+        self.scan_config.frame_interval = Duration::from_secs(1) / fps;
+        Ok(self)
+    }
+
+    /// Overrides the automatically detected render kernel.
+    pub fn with_render_backend(mut self, backend: RenderBackend) -> Self {
+        self.render_backend = Some(backend);
+        self
+    }
+
+    pub fn with_orbit_trap(mut self, trap: TrapShape) -> Self {
+        self.orbit_trap = trap;
         self
     }
 
-    pub fn with_size(mut self, width: u32, height: u32) -> Self {
-        if width <= 0 || height <= 0 {
-            panic!("Width and height must be greater than 0");
+    pub fn with_color_jitter(mut self, amount: f64) -> Self {
+        self.color_jitter = amount.max(0.0);
+        self
+    }
+
+    pub fn with_tonemap(mut self, tonemap: ToneMap) -> Self {
+        self.tonemap = tonemap;
+        self
+    }
+
+    pub fn with_invert_palette(mut self, invert: bool) -> Self {
+        self.invert_palette = invert;
+        self
+    }
+
+    /// Cycles the palette with the zoom depth, see
+    /// [`Renderer::set_palette_depth_coupling`](crate::renderer::Renderer::set_palette_depth_coupling).
+    pub fn with_palette_depth_coupling(mut self, coupling: f64) -> Self {
+        self.palette_depth_coupling = coupling;
+        self
+    }
+
+    /// Supersamples every pixel with a `samples` by `samples` grid, see
+    /// [`Renderer::set_aa_samples`](crate::renderer::Renderer::set_aa_samples).
+    pub fn with_aa_samples(mut self, samples: u32) -> Self {
+        if samples == 0 {
+            panic!("AA samples must be greater than 0");
+        }
+
+        self.aa_samples = samples;
+        self
+    }
+
+    /// Whether the arrow keys glide, see
+    /// [`Renderer::set_pan_inertia`](crate::renderer::Renderer::set_pan_inertia).
+    pub fn with_pan_inertia(mut self, inertia: bool) -> Self {
+        self.pan_inertia = inertia;
+        self
+    }
+
+    /// Sets how far the arrow keys pan, see
+    /// [`Renderer::set_pan_speed`](crate::renderer::Renderer::set_pan_speed).
+    ///
+    /// # Panics
+    ///
+    /// If `speed` isn't finite and greater than 0.
+    pub fn with_pan_speed(mut self, speed: f64) -> Self {
+        if !(speed.is_finite() && speed > 0.0) {
+            panic!("Pan speed must be greater than 0");
         }
-        
+
+        self.pan_speed = Some(speed);
+        self
+    }
+
+    /// Sets the gamma supersampled colors are blended with, see
+    /// [`Renderer::set_gamma`](crate::renderer::Renderer::set_gamma).
+    ///
+    /// # Panics
+    ///
+    /// If `gamma` isn't finite and greater than 0.
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        if !(gamma.is_finite() && gamma > 0.0) {
+            panic!("Gamma must be greater than 0");
+        }
+
+        self.gamma = Some(gamma);
+        self
+    }
+
+    /// Adds a glow of `strength` around the bright parts of the view, see
+    /// [`Renderer::set_glow`](crate::renderer::Renderer::set_glow).
+    ///
+    /// # Panics
+    ///
+    /// If `strength` isn't finite and greater than 0.
+    pub fn with_glow(mut self, strength: f64) -> Self {
+        if !(strength.is_finite() && strength > 0.0) {
+            panic!("Glow strength must be greater than 0");
+        }
+
+        self.glow = Some(strength);
+        self
+    }
+
+    /// Sets how far the glow of [`Args::with_glow`] reaches, in pixels.
+    ///
+    /// # Panics
+    ///
+    /// If `radius` is 0.
+    pub fn with_glow_radius(mut self, radius: usize) -> Self {
+        if radius == 0 {
+            panic!("Glow radius must be greater than 0");
+        }
+
+        self.glow_radius = Some(radius);
+        self
+    }
+
+    /// Sets how far one notch of the mouse wheel zooms, see
+    /// [`Renderer::set_zoom_step`](crate::renderer::Renderer::set_zoom_step).
+    ///
+    /// # Panics
+    ///
+    /// If `step` isn't between 0 and 1, exclusive.
+    pub fn with_zoom_step(mut self, step: f64) -> Self {
+        if !(step > 0.0 && step < 1.0) {
+            panic!("Zoom step must be between 0 and 1");
+        }
+
+        self.zoom_step = Some(step);
+        self
+    }
+
+    /// Sets the widest view zooming out goes to, see
+    /// [`Renderer::set_max_scale`](crate::renderer::Renderer::set_max_scale).
+    ///
+    /// # Panics
+    ///
+    /// If `scale` isn't finite and greater than 0.
+    pub fn with_max_scale(mut self, scale: f64) -> Self {
+        if !(scale.is_finite() && scale > 0.0) {
+            panic!("Max scale must be greater than 0");
+        }
+
+        self.max_scale = Some(scale);
+        self
+    }
+
+    /// Sets the stops of the gradient color scheme.
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Renders the views the GPU can draw in a compute shader, see
+    /// [`GpuRenderer`](crate::gpu::GpuRenderer). Falls back to the CPU on
+    /// GPUs without compute shaders.
+    pub fn with_gpu(mut self, gpu: bool) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
+    /// Opens a second window on the same view, colored with another scheme,
+    /// for comparing the two.
+    pub fn with_compare(mut self, compare: bool) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    /// Colors the points that never escape, black by default.
+    pub fn with_interior_color(mut self, color: [u8; 3]) -> Self {
+        self.interior_color = color;
+        self
+    }
+
+    /// Keeps adding samples while the view stays still, see
+    /// [`Renderer::set_temporal_aa`](crate::renderer::Renderer::set_temporal_aa).
+    pub fn with_temporal_aa(mut self, temporal_aa: bool) -> Self {
+        self.temporal_aa = temporal_aa;
+        self
+    }
+
+    /// Lightens the interior color by how far out the cycle each point's
+    /// orbit settles on stays, bringing out the structure inside the set.
+    pub fn with_interior_shading(mut self, shading: bool) -> Self {
+        self.interior_shading = shading;
+        self
+    }
+
+    /// Sets the base color of [`ColorScheme::SingleHue`].
+    pub fn with_single_hue(mut self, color: [u8; 3]) -> Self {
+        self.single_hue = color;
+        self
+    }
+
+    /// Sets how many stripes [`ColorScheme::StripeAverage`] wraps around
+    /// each turn of the orbits, see
+    /// [`Renderer::set_stripe_frequency`](crate::renderer::Renderer::set_stripe_frequency).
+    ///
+    /// # Panics
+    ///
+    /// If `frequency` isn't finite and greater than 0.
+    pub fn with_stripe_frequency(mut self, frequency: f64) -> Self {
+        if !(frequency.is_finite() && frequency > 0.0) {
+            panic!("Stripe frequency must be greater than 0");
+        }
+
+        self.stripe_frequency = frequency;
+        self
+    }
+
+    /// Blends the smooth and gradient schemes in Oklab, see
+    /// [`Renderer::set_perceptual`](crate::renderer::Renderer::set_perceptual).
+    pub fn with_perceptual(mut self, perceptual: bool) -> Self {
+        self.perceptual = perceptual;
+        self
+    }
+
+    /// Replaces the default key bindings of the window.
+    pub fn with_keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Like [`Args::try_with_size`], but panics on an invalid size.
+    pub fn with_size(self, width: u32, height: u32) -> Self {
+        self.try_with_size(width, height)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_with_size(mut self, width: u32, height: u32) -> Result<Self, ArgsError> {
+        check_size(width, height)?;
+
         self.width = width;
         self.height = height;
-        self
+        Ok(self)
     }
 
-    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
-        if max_iterations <= 0 {
-            panic!("Max iterations must be greater than 0");
+    /// Like [`Args::try_with_max_iterations`], but panics on a limit of 0.
+    pub fn with_max_iterations(self, max_iterations: u32) -> Self {
+        self.try_with_max_iterations(max_iterations)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_with_max_iterations(mut self, max_iterations: u32) -> Result<Self, ArgsError> {
+        if max_iterations == 0 {
+            return Err(ArgsError::ZeroIterations);
         }
 
         self.max_iterations = max_iterations;
+        Ok(self)
+    }
+
+    /// Sets the escape radius, see [`mandelbrot`](crate::fractals::mandelbrot).
+    /// A larger radius gives smoother gradients for a little more work per
+    /// pixel.
+    pub fn with_bailout(mut self, bailout: f64) -> Self {
+        if !(bailout.is_finite() && bailout >= DEFAULT_BAILOUT) {
+            panic!("Bailout must be at least {DEFAULT_BAILOUT}");
+        }
+
+        self.bailout = bailout;
         self
     }
 
@@ -105,6 +805,10 @@ impl Args {
         self.height
     }
 
+    pub fn get_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
     pub fn get_upper_left(&self) -> Complex<f64> {
         self.upper_left
     }
@@ -113,17 +817,137 @@ impl Args {
         self.lower_right
     }
 
+    /// The corners of the region to show, if they were given to
+    /// [`Args::new`] or [`Args::with_bounds`]. The default corners only
+    /// stand in for the default view of the fractal, so they give `None`.
+    pub fn get_bounds(&self) -> Option<(Complex<f64>, Complex<f64>)> {
+        self.bounds_given
+            .then_some((self.upper_left, self.lower_right))
+    }
+
     pub fn get_max_iterations(&self) -> u32 {
         self.max_iterations
     }
 
+    pub fn get_bailout(&self) -> f64 {
+        self.bailout
+    }
+
+    pub fn get_fractal_kind(&self) -> FractalKind {
+        self.fractal_kind
+    }
+
     pub fn get_color_scheme(&self) -> ColorScheme {
         self.color_scheme
     }
 
+    pub fn get_center(&self) -> Option<Complex<f64>> {
+        self.center
+    }
+
+    pub fn get_scale(&self) -> Option<f64> {
+        self.scale
+    }
+
     pub fn get_scan_config(&self) -> ScanConfig {
         self.scan_config
     }
+
+    pub fn get_render_backend(&self) -> Option<RenderBackend> {
+        self.render_backend
+    }
+
+    pub fn get_orbit_trap(&self) -> TrapShape {
+        self.orbit_trap
+    }
+
+    pub fn get_color_jitter(&self) -> f64 {
+        self.color_jitter
+    }
+
+    pub fn get_tonemap(&self) -> ToneMap {
+        self.tonemap
+    }
+
+    pub fn get_invert_palette(&self) -> bool {
+        self.invert_palette
+    }
+
+    pub fn get_palette_depth_coupling(&self) -> f64 {
+        self.palette_depth_coupling
+    }
+
+    pub fn get_aa_samples(&self) -> u32 {
+        self.aa_samples
+    }
+
+    pub fn get_temporal_aa(&self) -> bool {
+        self.temporal_aa
+    }
+
+    pub fn get_palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    pub fn get_gpu(&self) -> bool {
+        self.gpu
+    }
+
+    pub fn get_compare(&self) -> bool {
+        self.compare
+    }
+
+    pub fn get_interior_color(&self) -> [u8; 3] {
+        self.interior_color
+    }
+
+    pub fn get_interior_shading(&self) -> bool {
+        self.interior_shading
+    }
+
+    pub fn get_single_hue(&self) -> [u8; 3] {
+        self.single_hue
+    }
+
+    pub fn get_stripe_frequency(&self) -> f64 {
+        self.stripe_frequency
+    }
+
+    pub fn get_perceptual(&self) -> bool {
+        self.perceptual
+    }
+
+    pub fn get_keymap(&self) -> Option<&KeyMap> {
+        self.keymap.as_ref()
+    }
+
+    pub fn get_gamma(&self) -> Option<f64> {
+        self.gamma
+    }
+
+    pub fn get_glow(&self) -> Option<f64> {
+        self.glow
+    }
+
+    pub fn get_glow_radius(&self) -> Option<usize> {
+        self.glow_radius
+    }
+
+    pub fn get_pan_speed(&self) -> Option<f64> {
+        self.pan_speed
+    }
+
+    pub fn get_pan_inertia(&self) -> bool {
+        self.pan_inertia
+    }
+
+    pub fn get_zoom_step(&self) -> Option<f64> {
+        self.zoom_step
+    }
+
+    pub fn get_max_scale(&self) -> Option<f64> {
+        self.max_scale
+    }
 }
 
 impl Default for Args {
@@ -133,10 +957,652 @@ impl Default for Args {
             height: 800,
             upper_left: Complex::new(-2.5, 1.5),
             lower_right: Complex::new(1.5, -2.5),
+            bounds_given: false,
             max_iterations: 200,
+            bailout: DEFAULT_BAILOUT,
+            fractal_kind: FractalKind::Mandelbrot,
             color_scheme: ColorScheme::Red,
-            _fullscreen: false,
+            fullscreen: false,
+            center: None,
+            scale: None,
             scan_config: ScanConfig::default(),
+            render_backend: None,
+            orbit_trap: TrapShape::DEFAULTS[0],
+            color_jitter: 0.0,
+            tonemap: ToneMap::None,
+            invert_palette: false,
+            palette_depth_coupling: 0.0,
+            aa_samples: 1,
+            temporal_aa: false,
+            palette: None,
+            gpu: false,
+            compare: false,
+            interior_color: [0, 0, 0],
+            interior_shading: false,
+            single_hue: DEFAULT_SINGLE_HUE,
+            stripe_frequency: DEFAULT_STRIPE_FREQUENCY,
+            perceptual: false,
+            keymap: None,
+            pan_speed: None,
+            pan_inertia: true,
+            zoom_step: None,
+            max_scale: None,
+            gamma: None,
+            glow: None,
+            glow_radius: None,
+        }
+    }
+}
+
+/// Command line flags. Every flag is optional and only overrides the
+/// matching setting, see [`Cli::apply`].
+#[derive(Debug, Parser)]
+#[command(version, about = "A fractal viewer")]
+pub struct Cli {
+    /// Window width in pixels
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    width: Option<u32>,
+    /// Window height in pixels
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    height: Option<u32>,
+    /// Iteration limit per point
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_iter: Option<u32>,
+    /// Real part of the view's center
+    #[arg(
+        long,
+        requires = "center_y",
+        allow_negative_numbers = true,
+        value_parser = parse_finite
+    )]
+    center_x: Option<f64>,
+    /// Imaginary part of the view's center
+    #[arg(
+        long,
+        requires = "center_x",
+        allow_negative_numbers = true,
+        value_parser = parse_finite
+    )]
+    center_y: Option<f64>,
+    /// How many units the view spans across its smaller side
+    #[arg(long, value_parser = parse_positive)]
+    scale: Option<f64>,
+    /// A token printed with Shift+W to start at, setting the center, scale,
+    /// iteration limit and color scheme unless they are given as well
+    #[arg(long, value_parser = parse_location)]
+    location: Option<ViewState>,
+    /// Power `d` of the multibrot set `z = z^d + c` to render
+    #[arg(long, value_parser = parse_power)]
+    power: Option<f64>,
+    /// Color scheme, e.g. `smooth`, `black-and-white` or `orbit-trap`
+    #[arg(long)]
+    color: Option<ColorScheme>,
+    /// Palette file for the gradient color scheme, which it also selects
+    /// unless `--color` is given
+    #[arg(long, value_parser = parse_palette)]
+    palette: Option<Palette>,
+    /// Gamma supersampled colors are blended with, 1 to blend them as they
+    /// are
+    #[arg(long, value_parser = parse_positive)]
+    gamma: Option<f64>,
+    /// Strength of a glow around the bright parts of the view
+    #[arg(long, value_name = "STRENGTH", value_parser = parse_positive)]
+    glow: Option<f64>,
+    /// How far the glow reaches, in pixels
+    #[arg(long, value_name = "PIXELS", requires = "glow", value_parser = clap::value_parser!(u32).range(1..))]
+    glow_radius: Option<u32>,
+    /// Fraction of the view the arrow keys pan by per step
+    #[arg(long, value_parser = parse_positive)]
+    pan_speed: Option<f64>,
+    /// Stop panning as soon as the arrow keys are released rather than
+    /// gliding to a stop
+    #[arg(long)]
+    no_pan_inertia: bool,
+    /// Factor the scale changes by per notch of the mouse wheel, between 0
+    /// and 1
+    #[arg(long, value_parser = parse_zoom_step)]
+    zoom_step: Option<f64>,
+    /// Widest view zooming out goes to, in units across the smaller side
+    #[arg(long, value_parser = parse_positive)]
+    max_scale: Option<f64>,
+    /// Stride of the first progressive rendering pass, in pixels
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    scan_stride: Option<u32>,
+    /// Show each progressive pass as it is drawn rather than once it is
+    /// whole
+    #[arg(long)]
+    no_double_buffer: bool,
+    /// Most frames per second a progressive render refines at
+    #[arg(long, value_name = "FPS", value_parser = clap::value_parser!(u32).range(1..))]
+    max_fps: Option<u32>,
+    /// Start in borderless fullscreen
+    #[arg(long)]
+    fullscreen: bool,
+    /// Render the Mandelbrot set in a GPU compute shader where `f32` is
+    /// precise enough
+    #[arg(long)]
+    gpu: bool,
+    /// Open a second window on the same view to compare color schemes or
+    /// iteration counts in
+    #[arg(long)]
+    compare: bool,
+    /// Color of the points that never escape, as `r,g,b`
+    #[arg(long, value_parser = parse_rgb)]
+    interior_color: Option<[u8; 3]>,
+    /// Lighten the interior by how far out the cycle each orbit settles on stays
+    #[arg(long)]
+    interior_shading: bool,
+    /// Keep adding a jittered sample per pixel each frame while the view
+    /// stays still
+    #[arg(long)]
+    temporal_aa: bool,
+    /// Base color of the single hue scheme as `RRGGBB`, which it also
+    /// selects unless `--color` is given
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_hex)]
+    color_hex: Option<[u8; 3]>,
+    /// Stripes per turn of the orbits in the stripe average scheme, which
+    /// it also selects unless `--color` is given
+    #[arg(long, value_name = "FREQUENCY", value_parser = parse_positive)]
+    stripe_frequency: Option<f64>,
+    /// Blend the smooth and gradient schemes in Oklab for even midtones
+    #[arg(long)]
+    perceptual: bool,
+    /// Key binding file with `action = Key` lines, like `zoom-in = Equals`,
+    /// for the keys to change from the defaults
+    #[arg(long, value_name = "PATH", value_parser = parse_keymap)]
+    keymap: Option<KeyMap>,
+    /// Write the iteration count of every pixel to this 16 bit PGM file
+    /// instead of opening the viewer. Counts above 65535 are clamped.
+    #[arg(long, value_name = "PATH")]
+    dump_iterations: Option<PathBuf>,
+    /// Render the view once to this PNG file instead of opening the viewer
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// What to do instead of opening the viewer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Time renders of a fixed view at several resolutions without a window
+    Bench {
+        /// Frames rendered per resolution
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        frames: u32,
+    },
+}
+
+impl Cli {
+    pub fn command(&self) -> Option<Command> {
+        self.command
+    }
+
+    /// Where to write the iteration counts to instead of opening the viewer.
+    pub fn dump_iterations(&self) -> Option<PathBuf> {
+        self.dump_iterations.clone()
+    }
+
+    /// Where to save a render of the view to instead of opening the viewer.
+    pub fn output(&self) -> Option<PathBuf> {
+        self.output.clone()
+    }
+
+    /// Overrides the settings of `args` that were given on the command line.
+    pub fn apply(self, mut args: Args) -> Args {
+        if let Some(location) = self.location {
+            args.center = Some(Complex::new(location.center_x, location.center_y));
+            args.scale = Some(location.scale);
+            args.max_iterations = location.max_iterations;
+            args.color_scheme = location.color_scheme;
+        }
+        if let Some(width) = self.width {
+            args.width = width;
+        }
+        if let Some(height) = self.height {
+            args.height = height;
+        }
+        if let Some(max_iterations) = self.max_iter {
+            args.max_iterations = max_iterations;
+        }
+        if let (Some(re), Some(im)) = (self.center_x, self.center_y) {
+            args.center = Some(Complex::new(re, im));
+        }
+        if let Some(scale) = self.scale {
+            args.scale = Some(scale);
+        }
+        if let Some(power) = self.power {
+            args = args.with_power(power);
+        }
+        if let Some(palette) = self.palette {
+            args.palette = Some(palette);
+            args.color_scheme = ColorScheme::Gradient;
+        }
+        if let Some(color) = self.color_hex {
+            args.single_hue = color;
+            args.color_scheme = ColorScheme::SingleHue;
+        }
+        if let Some(frequency) = self.stripe_frequency {
+            args = args.with_stripe_frequency(frequency);
+            args.color_scheme = ColorScheme::StripeAverage;
+        }
+        if let Some(color_scheme) = self.color {
+            args.color_scheme = color_scheme;
+        }
+        if let Some(gamma) = self.gamma {
+            args = args.with_gamma(gamma);
+        }
+        if let Some(strength) = self.glow {
+            args = args.with_glow(strength);
+        }
+        if let Some(radius) = self.glow_radius {
+            args = args.with_glow_radius(radius as usize);
+        }
+        if let Some(speed) = self.pan_speed {
+            args = args.with_pan_speed(speed);
+        }
+        if self.no_pan_inertia {
+            args.pan_inertia = false;
+        }
+        if let Some(step) = self.zoom_step {
+            args = args.with_zoom_step(step);
+        }
+        if let Some(scale) = self.max_scale {
+            args = args.with_max_scale(scale);
+        }
+        if let Some(initial_stride) = self.scan_stride {
+            args.scan_config.initial_stride = initial_stride;
+        }
+        if self.no_double_buffer {
+            args.scan_config.double_buffered = false;
+        }
+        if let Some(fps) = self.max_fps {
+            args = args.with_max_fps(fps);
+        }
+        if self.fullscreen {
+            args.fullscreen = true;
+        }
+        if self.gpu {
+            args.gpu = true;
+        }
+        if self.compare {
+            args.compare = true;
+        }
+        if let Some(color) = self.interior_color {
+            args.interior_color = color;
+        }
+        if self.interior_shading {
+            args.interior_shading = true;
+        }
+        if self.temporal_aa {
+            args.temporal_aa = true;
+        }
+        if self.perceptual {
+            args.perceptual = true;
+        }
+        if let Some(keymap) = self.keymap {
+            args.keymap = Some(keymap);
+        }
+        args
+    }
+}
+
+fn parse_finite(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(parsed) if parsed.is_finite() => Ok(parsed),
+        Ok(_) => Err("must be a finite number".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_location(token: &str) -> Result<ViewState, String> {
+    ViewState::from_location_string(token).map_err(|err| err.to_string())
+}
+
+fn parse_palette(path: &str) -> Result<Palette, String> {
+    Palette::load(path).map_err(|err| err.to_string())
+}
+
+fn parse_keymap(path: &str) -> Result<KeyMap, String> {
+    KeyMap::load(path).map_err(|err| err.to_string())
+}
+
+fn parse_power(value: &str) -> Result<f64, String> {
+    match parse_finite(value)? {
+        parsed if parsed > 1.0 => Ok(parsed),
+        _ => Err("must be greater than 1".into()),
+    }
+}
+
+fn parse_zoom_step(value: &str) -> Result<f64, String> {
+    match parse_finite(value)? {
+        parsed if parsed > 0.0 && parsed < 1.0 => Ok(parsed),
+        _ => Err("must be between 0 and 1".into()),
+    }
+}
+
+fn parse_rgb(value: &str) -> Result<[u8; 3], String> {
+    let channels: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [r, g, b] = channels.as_slice() else {
+        return Err("expected three channels like `20,0,40`".into());
+    };
+    let channel = |text: &str| {
+        text.parse::<u8>()
+            .map_err(|_| format!("channel \"{text}\" isn't a whole number from 0 to 255"))
+    };
+    Ok([channel(r)?, channel(g)?, channel(b)?])
+}
+
+fn parse_hex(value: &str) -> Result<[u8; 3], String> {
+    let digits = value.trim().trim_start_matches('#');
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("expected six hex digits like `ff8800`".into());
+    }
+    let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16);
+    Ok([0, 2, 4].map(|index| channel(index).expect("checked to be hex digits")))
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    match parse_finite(value)? {
+        parsed if parsed > 0.0 => Ok(parsed),
+        _ => Err("must be greater than 0".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars<'a>(pairs: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<OsString> + 'a {
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.into())
+        }
+    }
+
+    #[test]
+    fn test_env_overrides_defaults() {
+        let args = Args::default()
+            .with_vars(vars(&[
+                ("FRUSTAL_WIDTH", "320"),
+                ("FRUSTAL_MAX_ITER", " 1000 "),
+                ("FRUSTAL_CENTER_RE", "-0.75"),
+                ("FRUSTAL_CENTER_IM", "0.1"),
+                ("FRUSTAL_SCALE", "1e-3"),
+                ("FRUSTAL_COLOR_SCHEME", "black-and-white"),
+            ]))
+            .unwrap();
+
+        assert_eq!(args.get_width(), 320);
+        assert_eq!(args.get_height(), Args::default().get_height());
+        assert_eq!(args.get_max_iterations(), 1000);
+        assert_eq!(args.get_center(), Some(Complex::new(-0.75, 0.1)));
+        assert_eq!(args.get_scale(), Some(1e-3));
+        assert!(matches!(
+            args.get_color_scheme(),
+            ColorScheme::BlackAndWhite
+        ));
+    }
+
+    #[test]
+    fn test_try_builders_report_invalid_values() {
+        let corners = (Complex::new(-2.0, 1.0), Complex::new(1.0, -1.0));
+        let new = |width, height, max_iterations| {
+            Args::try_new(
+                width,
+                height,
+                max_iterations,
+                false,
+                corners.0,
+                corners.1,
+                ColorScheme::Smooth,
+            )
+            .err()
+        };
+        assert_eq!(new(0, 600, 200), Some(ArgsError::ZeroDimension));
+        assert_eq!(new(800, 600, 0), Some(ArgsError::ZeroIterations));
+        assert_eq!(new(800, 600, 200), None);
+
+        let flat = (Complex::new(-2.0, 1.0), Complex::new(1.0, 1.0));
+        let err = Args::try_new(800, 600, 200, false, flat.0, flat.1, ColorScheme::Smooth).err();
+        assert_eq!(err, Some(ArgsError::EmptyBounds));
+        let far = Complex::new(f64::INFINITY, -1.0);
+        assert_eq!(
+            Args::default().try_with_bounds(corners.0, far).err(),
+            Some(ArgsError::EmptyBounds)
+        );
+        assert_eq!(Args::default().get_bounds(), None);
+        let args = Args::default().with_bounds(corners.0, corners.1);
+        assert_eq!(args.get_bounds(), Some(corners));
+
+        let too_large = ArgsError::TooLarge {
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        assert_eq!(
+            Args::default().try_with_size(u32::MAX, u32::MAX).err(),
+            Some(too_large)
+        );
+        assert_eq!(
+            Args::default().try_with_size(320, 0).err(),
+            Some(ArgsError::ZeroDimension)
+        );
+        assert_eq!(
+            Args::default().try_with_max_iterations(0).err(),
+            Some(ArgsError::ZeroIterations)
+        );
+        assert_eq!(
+            Args::default().try_with_scan_config(true, 0).err(),
+            Some(ArgsError::ZeroStride)
+        );
+        assert_eq!(
+            Args::default().try_with_max_fps(0).err(),
+            Some(ArgsError::ZeroFps)
+        );
+
+        let args = Args::default()
+            .try_with_size(320, 240)
+            .and_then(|args| args.try_with_max_iterations(50))
+            .unwrap();
+        assert_eq!((args.get_width(), args.get_height()), (320, 240));
+        assert_eq!(args.get_max_iterations(), 50);
+    }
+
+    #[test]
+    fn test_invalid_env_values_error() {
+        for pairs in [
+            &[("FRUSTAL_WIDTH", "0")][..],
+            &[("FRUSTAL_HEIGHT", "12.5")],
+            &[("FRUSTAL_MAX_ITER", "lots")],
+            &[("FRUSTAL_SCALE", "-1")],
+            &[("FRUSTAL_CENTER_RE", "0.5")],
+            &[("FRUSTAL_CENTER_RE", "NaN"), ("FRUSTAL_CENTER_IM", "0")],
+            &[("FRUSTAL_COLOR_SCHEME", "plaid")],
+        ] {
+            let err = Args::default().with_vars(vars(pairs)).err();
+            assert_eq!(err.map(|err| err.name), Some(pairs[0].0), "{pairs:?}");
+        }
+    }
+
+    #[test]
+    fn test_cli_overrides_args() {
+        let cli = Cli::try_parse_from([
+            "frustal",
+            "--width",
+            "320",
+            "--max-iter",
+            "1000",
+            "--center-x",
+            "-0.75",
+            "--center-y",
+            "0.1",
+            "--scale",
+            "1e-3",
+            "--color",
+            "black-and-white",
+            "--scan-stride",
+            "4",
+            "--no-double-buffer",
+            "--max-fps",
+            "25",
+            "--power",
+            "3",
+            "--pan-speed",
+            "0.1",
+            "--no-pan-inertia",
+            "--gamma",
+            "1.8",
+            "--glow",
+            "0.4",
+            "--glow-radius",
+            "12",
+            "--zoom-step",
+            "0.95",
+            "--max-scale",
+            "50",
+            "--fullscreen",
+            "--gpu",
+            "--compare",
+            "--interior-color",
+            "20, 0,40",
+            "--interior-shading",
+            "--temporal-aa",
+            "--perceptual",
+            "--color-hex",
+            "#1E90ff",
+            "--stripe-frequency",
+            "7.5",
+        ])
+        .unwrap();
+        let args = cli.apply(Args::default());
+
+        assert_eq!(args.get_width(), 320);
+        assert_eq!(args.get_height(), Args::default().get_height());
+        assert_eq!(args.get_max_iterations(), 1000);
+        assert_eq!(args.get_center(), Some(Complex::new(-0.75, 0.1)));
+        assert_eq!(args.get_scale(), Some(1e-3));
+        assert!(matches!(
+            args.get_color_scheme(),
+            ColorScheme::BlackAndWhite
+        ));
+        assert_eq!(args.get_scan_config().initial_stride, 4);
+        assert!(!args.get_scan_config().double_buffered);
+        assert_eq!(
+            args.get_scan_config().frame_interval,
+            Duration::from_millis(40)
+        );
+        assert_eq!(
+            args.get_fractal_kind(),
+            FractalKind::Multibrot { power: 3.0 }
+        );
+        assert!(args.get_fullscreen());
+        assert!(args.get_gpu());
+        assert!(args.get_compare());
+        assert_eq!(args.get_interior_color(), [20, 0, 40]);
+        assert!(args.get_interior_shading());
+        assert!(args.get_temporal_aa());
+        assert!(args.get_perceptual());
+        assert_eq!(args.get_single_hue(), [0x1e, 0x90, 0xff]);
+        assert_eq!(args.get_stripe_frequency(), 7.5);
+        assert_eq!(args.get_pan_speed(), Some(0.1));
+        assert!(!args.get_pan_inertia());
+        assert_eq!(args.get_gamma(), Some(1.8));
+        assert_eq!(args.get_glow(), Some(0.4));
+        assert_eq!(args.get_glow_radius(), Some(12));
+        assert_eq!(args.get_zoom_step(), Some(0.95));
+        assert_eq!(args.get_max_scale(), Some(50.0));
+
+        // Nothing given leaves everything alone
+        let cli = Cli::try_parse_from(["frustal"]).unwrap();
+        assert_eq!(cli.dump_iterations(), None);
+        assert_eq!(cli.output(), None);
+        let args = cli.apply(Args::default());
+        assert_eq!(args.get_center(), None);
+        assert!(!args.get_fullscreen());
+        assert!(args.get_scan_config().double_buffered);
+        assert_eq!(
+            args.get_scan_config().frame_interval,
+            Duration::from_secs(1) / DEFAULT_MAX_FPS
+        );
+        assert_eq!(args.get_interior_color(), [0, 0, 0]);
+        assert_eq!(args.get_glow(), None);
+        assert!(args.get_pan_inertia());
+
+        // Without --color the hex color picks its scheme
+        let args = Cli::try_parse_from(["frustal", "--color-hex", "ff8800"])
+            .unwrap()
+            .apply(Args::default());
+        assert_eq!(args.get_color_scheme(), ColorScheme::SingleHue);
+        assert_eq!(args.get_single_hue(), [255, 136, 0]);
+        let args = Cli::try_parse_from(["frustal", "--stripe-frequency", "3"])
+            .unwrap()
+            .apply(Args::default());
+        assert_eq!(args.get_color_scheme(), ColorScheme::StripeAverage);
+        assert_eq!(args.get_stripe_frequency(), 3.0);
+
+        let cli = Cli::try_parse_from(["frustal", "--max-iter", "500", "bench", "--frames", "3"]);
+        assert_eq!(cli.unwrap().command(), Some(Command::Bench { frames: 3 }));
+        let cli = Cli::try_parse_from(["frustal", "--dump-iterations", "counts.pgm"]).unwrap();
+        assert_eq!(cli.dump_iterations(), Some(PathBuf::from("counts.pgm")));
+        let cli =
+            Cli::try_parse_from(["frustal", "--output", "out.png", "--width", "1920"]).unwrap();
+        assert_eq!(cli.output(), Some(PathBuf::from("out.png")));
+        assert!(!args.get_gpu());
+
+        // Flags given alongside a location win over it
+        let token = ViewState {
+            center_x: -1.25,
+            center_y: 0.02,
+            scale: 1e-6,
+            max_iterations: 4000,
+            color_scheme: ColorScheme::Rainbow,
+        }
+        .to_location_string();
+        let args = Cli::try_parse_from(["frustal", "--location", &token, "--max-iter", "900"])
+            .unwrap()
+            .apply(Args::default());
+        assert_eq!(args.get_center(), Some(Complex::new(-1.25, 0.02)));
+        assert_eq!(args.get_scale(), Some(1e-6));
+        assert_eq!(args.get_max_iterations(), 900);
+        assert_eq!(args.get_color_scheme(), ColorScheme::Rainbow);
+    }
+
+    #[test]
+    fn test_invalid_cli_values_error() {
+        for flags in [
+            &["--width", "0"][..],
+            &["--height", "12.5"],
+            &["--max-iter", "lots"],
+            &["--scale", "-1"],
+            &["--center-x", "0.5"],
+            &["--center-x", "NaN", "--center-y", "0"],
+            &["--color", "plaid"],
+            &["--scan-stride", "0"],
+            &["--max-fps", "0"],
+            &["--power", "1"],
+            &["--pan-speed", "0"],
+            &["--gamma", "-2.2"],
+            &["--glow", "0"],
+            &["--glow", "0.5", "--glow-radius", "0"],
+            &["--glow-radius", "4"],
+            &["--zoom-step", "1"],
+            &["--zoom-step", "1.2"],
+            &["--max-scale", "0"],
+            &["bench", "--frames", "0"],
+            &["--palette", "no/such/palette.gpl"],
+            &["--keymap", "no/such/keys.txt"],
+            &["--interior-color", "10,20"],
+            &["--interior-color", "10,20,256"],
+            &["--color-hex", "ff880"],
+            &["--color-hex", "gg8800"],
+            &["--stripe-frequency", "0"],
+            &["--stripe-frequency", "inf"],
+            &["--location", "0123"],
+        ] {
+            let argv = ["frustal"].iter().chain(flags);
+            assert!(Cli::try_parse_from(argv).is_err(), "{flags:?}");
         }
     }
 }
@@ -0,0 +1,228 @@
+use crate::fractals::{mandelbrot, EscapeResult};
+
+/// The kernel used to compute iteration counts for whole rows of pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderBackend {
+    Scalar,
+    /// Four pixels at a time using 256-bit AVX2 vectors
+    Avx2,
+}
+
+impl RenderBackend {
+    /// Picks the widest kernel the current CPU supports.
+    pub fn detect() -> Self {
+        Self::available()
+            .last()
+            .copied()
+            .unwrap_or(RenderBackend::Scalar)
+    }
+
+    /// Every kernel the current CPU can run, narrowest first.
+    pub fn available() -> Vec<Self> {
+        let mut backends = vec![RenderBackend::Scalar];
+
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            backends.push(RenderBackend::Avx2);
+        }
+
+        backends
+    }
+
+    pub fn is_supported(&self) -> bool {
+        Self::available().contains(self)
+    }
+
+    /// Computes the escape of each `(reals[i], imag)` into `out[i]`, as
+    /// [`mandelbrot`] returns it.
+    pub fn mandelbrot_row(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+        out: &mut [EscapeResult],
+    ) {
+        match self {
+            RenderBackend::Scalar => {
+                for (escape, &real) in out.iter_mut().zip(reals) {
+                    *escape = mandelbrot(real, imag, max_iter, bailout);
+                }
+            }
+            #[cfg(target_arch = "x86_64")]
+            RenderBackend::Avx2 if is_x86_feature_detected!("avx2") => {
+                // SAFETY: AVX2 support was just checked
+                unsafe { avx2::mandelbrot_row(reals, imag, max_iter, bailout, out) }
+            }
+            RenderBackend::Avx2 => {
+                RenderBackend::Scalar.mandelbrot_row(reals, imag, max_iter, bailout, out)
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use crate::fractals::{mandelbrot, EscapeResult, PERIOD_EPSILON};
+    use num_complex::Complex64;
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 4;
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn mandelbrot_row(
+        reals: &[f64],
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+        out: &mut [EscapeResult],
+    ) {
+        let mut real_chunks = reals.chunks_exact(LANES);
+        let mut out_chunks = out.chunks_exact_mut(LANES);
+
+        for (real, escapes) in (&mut real_chunks).zip(&mut out_chunks) {
+            escapes.copy_from_slice(&mandelbrot_x4(real, imag, max_iter, bailout));
+        }
+
+        // Leftover pixels at the end of the row
+        for (escape, &real) in out_chunks
+            .into_remainder()
+            .iter_mut()
+            .zip(real_chunks.remainder())
+        {
+            *escape = mandelbrot(real, imag, max_iter, bailout);
+        }
+    }
+
+    /// Mirrors `fractals::mandelbrot` operation for operation, so every lane
+    /// ends with exactly the count the scalar version would return.
+    #[target_feature(enable = "avx2")]
+    unsafe fn mandelbrot_x4(
+        real: &[f64],
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+    ) -> [EscapeResult; LANES] {
+        let c_re = _mm256_loadu_pd(real.as_ptr());
+        let c_im = _mm256_set1_pd(imag);
+        let bailout_sqr = _mm256_set1_pd(bailout * bailout);
+        let period_sqr = _mm256_set1_pd(PERIOD_EPSILON * PERIOD_EPSILON);
+
+        let mut z_re = _mm256_setzero_pd();
+        let mut z_im = _mm256_setzero_pd();
+        let mut counts = _mm256_setzero_si256();
+        let mut final_re = _mm256_setzero_pd();
+        let mut final_im = _mm256_setzero_pd();
+        // All bits set in lanes that haven't escaped yet
+        let mut active = _mm256_castsi256_pd(_mm256_set1_epi64x(-1));
+        // All bits set in lanes found to be caught in a cycle
+        let mut periodic = _mm256_setzero_pd();
+        let mut reference_re = z_re;
+        let mut reference_im = z_im;
+        let mut next_save = 1u64;
+
+        for iteration in 0..max_iter {
+            let re_sq = _mm256_mul_pd(z_re, z_re);
+            let im_sq = _mm256_mul_pd(z_im, z_im);
+            let norm_sqr = _mm256_add_pd(re_sq, im_sq);
+
+            // Lanes still going keep track of their latest z, so escaped
+            // lanes end up holding the value they escaped with
+            final_re = _mm256_blendv_pd(final_re, z_re, active);
+            final_im = _mm256_blendv_pd(final_im, z_im, active);
+            active = _mm256_and_pd(active, _mm256_cmp_pd::<_CMP_LE_OQ>(norm_sqr, bailout_sqr));
+            if _mm256_movemask_pd(active) == 0 {
+                break;
+            }
+
+            // Active lanes hold -1, so subtracting the mask counts them up
+            counts = _mm256_sub_epi64(counts, _mm256_castpd_si256(active));
+
+            let cross = _mm256_add_pd(_mm256_mul_pd(z_re, z_im), _mm256_mul_pd(z_im, z_re));
+            z_re = _mm256_add_pd(_mm256_sub_pd(re_sq, im_sq), c_re);
+            z_im = _mm256_add_pd(cross, c_im);
+
+            let d_re = _mm256_sub_pd(z_re, reference_re);
+            let d_im = _mm256_sub_pd(z_im, reference_im);
+            let distance = _mm256_add_pd(_mm256_mul_pd(d_re, d_re), _mm256_mul_pd(d_im, d_im));
+            let cycled = _mm256_and_pd(active, _mm256_cmp_pd::<_CMP_LT_OQ>(distance, period_sqr));
+            final_re = _mm256_blendv_pd(final_re, z_re, cycled);
+            final_im = _mm256_blendv_pd(final_im, z_im, cycled);
+            periodic = _mm256_or_pd(periodic, cycled);
+            active = _mm256_andnot_pd(cycled, active);
+            if u64::from(iteration) + 1 == next_save {
+                reference_re = z_re;
+                reference_im = z_im;
+                next_save *= 2;
+            }
+        }
+
+        // Interior lanes report the z they finished on, like the scalar version
+        final_re = _mm256_blendv_pd(final_re, z_re, active);
+        final_im = _mm256_blendv_pd(final_im, z_im, active);
+
+        // Cycling lanes never escape
+        let counts = _mm256_castpd_si256(_mm256_blendv_pd(
+            _mm256_castsi256_pd(counts),
+            _mm256_castsi256_pd(_mm256_set1_epi64x(max_iter as i64)),
+            periodic,
+        ));
+
+        let mut lanes = [0i64; LANES];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, counts);
+        let mut re = [0.0; LANES];
+        _mm256_storeu_pd(re.as_mut_ptr(), final_re);
+        let mut im = [0.0; LANES];
+        _mm256_storeu_pd(im.as_mut_ptr(), final_im);
+
+        let mut escapes = [EscapeResult::default(); LANES];
+        for (lane, escape) in escapes.iter_mut().enumerate() {
+            *escape = EscapeResult {
+                iterations: lanes[lane] as u32,
+                escaped: (lanes[lane] as u32) < max_iter,
+                final_z: Complex64::new(re[lane], im[lane]),
+                stripe: 0.0,
+            };
+        }
+        escapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_match_scalar() {
+        let max_iter = 500;
+        // An odd width so the vector kernels also hit their remainder path
+        let reals: Vec<f64> = (0..203).map(|x| -2.0 + x as f64 * 0.0125).collect();
+
+        for backend in RenderBackend::available() {
+            for bailout in [2.0, 100.0] {
+                for row in 0..40 {
+                    let imag = -1.2 + row as f64 * 0.06;
+                    let mut expected = vec![EscapeResult::default(); reals.len()];
+                    let mut actual = vec![EscapeResult::default(); reals.len()];
+
+                    RenderBackend::Scalar.mandelbrot_row(
+                        &reals,
+                        imag,
+                        max_iter,
+                        bailout,
+                        &mut expected,
+                    );
+                    backend.mandelbrot_row(&reals, imag, max_iter, bailout, &mut actual);
+
+                    assert_eq!(
+                        expected, actual,
+                        "{backend:?} differs on row {row} with bailout {bailout}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use dashu_float::round::mode::HalfEven;
+use dashu_float::FBig;
+use num_complex::Complex64;
+
+type BigFloat = FBig<HalfEven>;
+
+/// Scale below which neighbouring pixels get too close together for `f64`
+/// to keep them apart, and views need a [`DeepRenderer`].
+pub const DEEP_ZOOM_SCALE: f64 = 1e-13;
+
+/// Bits kept beyond what is needed to tell pixels apart, so rounding errors
+/// in the reference orbit stay well below a pixel.
+const GUARD_BITS: usize = 64;
+
+/// A view whose center is stored in arbitrary precision, for zooming past
+/// [`DEEP_ZOOM_SCALE`].
+///
+/// Deep views are rendered with perturbation theory: a single reference
+/// orbit is computed at the center in full precision, see
+/// [`Self::reference_orbit`], and each pixel then only iterates its small
+/// offset from that orbit, which `f64` handles fine.
+#[derive(Clone, Debug)]
+pub struct DeepRenderer {
+    width: u32,
+    height: u32,
+    center_re: BigFloat,
+    center_im: BigFloat,
+    /// Only needs range, not precision, and `f64` goes down to 1e-308
+    scale: f64,
+}
+
+impl DeepRenderer {
+    /// Whether a view at `scale` is too deep for plain `f64` rendering.
+    pub fn is_needed(scale: f64) -> bool {
+        scale < DEEP_ZOOM_SCALE
+    }
+
+    /// Takes over a view of the same size, center and scale as a
+    /// [`Renderer`](crate::renderer::Renderer).
+    ///
+    /// # Panics
+    ///
+    /// If the center isn't finite or the scale isn't greater than 0.
+    pub fn new(width: u32, height: u32, center: Complex64, scale: f64) -> Self {
+        if !(scale.is_finite() && scale > 0.0) {
+            panic!("Scale must be greater than 0");
+        }
+
+        let mut view = Self {
+            width,
+            height,
+            center_re: BigFloat::ZERO,
+            center_im: BigFloat::ZERO,
+            scale,
+        };
+        let precision = view.precision();
+        view.center_re = to_big(center.re, precision);
+        view.center_im = to_big(center.im, precision);
+        view
+    }
+
+    /// The center, rounded to the nearest `f64`.
+    pub fn center(&self) -> Complex64 {
+        Complex64::new(
+            self.center_re.to_f64().value(),
+            self.center_im.to_f64().value(),
+        )
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Bits of precision the center needs to resolve single pixels.
+    pub fn precision(&self) -> usize {
+        let pixels = self.width.max(self.height).max(1) as f64;
+        let pixel_bits = (pixels / self.scale).log2().max(0.0).ceil() as usize;
+        pixel_bits + GUARD_BITS
+    }
+
+    /// Moves the view by a fraction of its size, like
+    /// [`Renderer::pan`](crate::renderer::Renderer::pan).
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let precision = self.precision();
+        self.center_re = &self.center_re + to_big(dx * self.scale, precision);
+        self.center_im = &self.center_im + to_big(dy * self.scale, precision);
+    }
+
+    /// Scales the view by `factor`, keeping the center. The center's
+    /// precision grows along with the zoom.
+    pub fn zoom(&mut self, factor: f64) {
+        self.scale *= factor;
+
+        let precision = self.precision();
+        if precision > self.center_re.precision() {
+            self.center_re = self.center_re.clone().with_precision(precision).value();
+            self.center_im = self.center_im.clone().with_precision(precision).value();
+        }
+    }
+
+    /// The offset of the pixel `(x, y)` from the center, which is small
+    /// enough to hold in `f64` at any depth.
+    pub fn pixel_offset(&self, x: f64, y: f64) -> Complex64 {
+        let pixel_size = self.scale / self.width.min(self.height).max(1) as f64;
+        Complex64::new(
+            (x - self.width as f64 / 2.0) * pixel_size,
+            (y - self.height as f64 / 2.0) * pixel_size,
+        )
+    }
+
+    /// The Mandelbrot orbit `z0 = 0, z1 = c, ...` of the center, computed in
+    /// full precision and rounded to `f64` afterwards. Ends with the first
+    /// value that escapes, or after `max_iter` steps.
+    pub fn reference_orbit(&self, max_iter: u32) -> Vec<Complex64> {
+        let precision = self.precision();
+        let mut z_re = BigFloat::ZERO.with_precision(precision).value();
+        let mut z_im = BigFloat::ZERO.with_precision(precision).value();
+        let two = to_big(2.0, precision);
+
+        let mut orbit = Vec::with_capacity(max_iter as usize + 1);
+        for _ in 0..max_iter {
+            let z = Complex64::new(z_re.to_f64().value(), z_im.to_f64().value());
+            orbit.push(z);
+            if z.norm_sqr() > 4.0 {
+                return orbit;
+            }
+
+            let re = z_re.sqr() - z_im.sqr() + &self.center_re;
+            z_im = &two * &z_re * &z_im + &self.center_im;
+            z_re = re;
+        }
+
+        orbit.push(Complex64::new(z_re.to_f64().value(), z_im.to_f64().value()));
+        orbit
+    }
+}
+
+/// Converts exactly, then widens to `precision` bits.
+fn to_big(value: f64, precision: usize) -> BigFloat {
+    BigFloat::try_from(value)
+        .expect("deep zoom coordinates must be finite")
+        .with_precision(precision)
+        .value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fractals::{orbit, DEFAULT_BAILOUT};
+
+    #[test]
+    fn test_reference_orbit_matches_f64_orbit() {
+        // An interior point, so the two orbits can't drift apart chaotically
+        let center = Complex64::new(-0.1, 0.2);
+        let view = DeepRenderer::new(800, 600, center, 2.5);
+        let reference = view.reference_orbit(100);
+
+        assert_eq!(reference.len(), 101);
+        assert_eq!(reference[0], Complex64::new(0.0, 0.0));
+        for (deep, plain) in
+            reference[1..]
+                .iter()
+                .zip(orbit(center.re, center.im, 100, DEFAULT_BAILOUT))
+        {
+            assert!((deep - plain).norm() < 1e-12);
+        }
+
+        // Escaping centers end with the escaped value
+        let escaping = DeepRenderer::new(800, 600, Complex64::new(1.0, 1.0), 2.5);
+        let reference = escaping.reference_orbit(100);
+        assert!(reference.len() < 101);
+        assert!(reference.last().unwrap().norm_sqr() > 4.0);
+    }
+
+    #[test]
+    fn test_deep_pan_keeps_sub_f64_offsets() {
+        let center = Complex64::new(-0.75, 0.1);
+        let mut view = DeepRenderer::new(800, 600, center, 1e-30);
+        assert!(DeepRenderer::is_needed(view.scale()));
+        assert!(view.precision() > 100);
+
+        // Far below what f64 can add to -0.75, but kept in full precision
+        let original = view.clone();
+        view.pan(0.01, 0.0);
+        assert_eq!(view.center(), center);
+        let moved = (&view.center_re - &original.center_re).to_f64().value();
+        assert!((moved - 1e-32).abs() < 1e-40);
+
+        let precision = view.precision();
+        view.zoom(1e-20);
+        assert!(view.precision() > precision);
+        assert!(view.center_re.precision() >= view.precision());
+    }
+}
@@ -0,0 +1,358 @@
+use crate::fractals::{color_map, ColorMode};
+use crate::renderer::Renderer;
+use crate::viewport::buffer_len;
+use image::ImageError;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Highest count a 16 bit PGM can hold. Counts above it, which only occur
+/// with iteration limits past it, are clamped to it.
+pub const PGM_MAX_VALUE: u32 = u16::MAX as u32;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Cancelled,
+    /// The image has more pixels than a buffer can address
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+    Image(ImageError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Cancelled => write!(f, "export cancelled"),
+            ExportError::TooLarge { width, height } => {
+                write!(f, "a {width}x{height} image is too large to hold in memory")
+            }
+            ExportError::Image(err) => write!(f, "{err}"),
+            ExportError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for ExportError {}
+
+/// An export running on a background thread, so the window stays responsive
+/// and shutdown can wait for it or cancel it.
+pub struct ExportTask {
+    path: PathBuf,
+    cancel: Arc<AtomicBool>,
+    handle: JoinHandle<Result<(), ExportError>>,
+}
+
+impl ExportTask {
+    /// Starts saving the view of `renderer` as a 16 bit PNG at `path`.
+    pub fn spawn_png16(renderer: Renderer, path: PathBuf) -> Self {
+        Self::spawn(path, move |path, cancel| {
+            write_png16(&renderer, path, cancel)
+        })
+    }
+
+    /// Starts saving the view of `renderer` as an 8 bit PNG of the given
+    /// size at `path`.
+    pub fn spawn_png(renderer: Renderer, path: PathBuf, width: u32, height: u32) -> Self {
+        Self::spawn(path, move |path, cancel| {
+            write_png(&renderer, path, cancel, width, height)
+        })
+    }
+
+    /// Starts saving the iteration count of every pixel in the view of
+    /// `renderer` as a 16 bit PGM at `path`, see [`PGM_MAX_VALUE`].
+    pub fn spawn_iterations(renderer: Renderer, path: PathBuf) -> Self {
+        Self::spawn(path, move |path, cancel| {
+            write_iterations(&renderer, path, cancel)
+        })
+    }
+
+    fn spawn<F>(path: PathBuf, export: F) -> Self
+    where
+        F: FnOnce(&Path, &AtomicBool) -> Result<(), ExportError> + Send + 'static,
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let cancel = Arc::clone(&cancel);
+            let path = path.clone();
+            thread::spawn(move || export(&path, &cancel))
+        };
+
+        Self {
+            path,
+            cancel,
+            handle,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Asks the export to stop. Its partial file is removed once it has.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Blocks until the export has finished or given up.
+    pub fn wait(self) -> Result<(), ExportError> {
+        self.handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+/// Where an export is written before it is complete.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    PathBuf::from(partial)
+}
+
+/// A zeroed buffer for a `width` by `height` image with `channels` values
+/// per pixel. Fails with [`ExportError::TooLarge`] if it can't be addressed
+/// or allocated, rather than aborting.
+fn image_buffer<T: Clone + Default>(
+    width: u32,
+    height: u32,
+    channels: usize,
+) -> Result<Vec<T>, ExportError> {
+    let too_large = || ExportError::TooLarge { width, height };
+    let len = buffer_len(width, height, channels).ok_or_else(too_large)?;
+    let mut buffer = Vec::new();
+    buffer.try_reserve_exact(len).map_err(|_| too_large())?;
+    buffer.resize(len, T::default());
+    Ok(buffer)
+}
+
+/// Saves an RGBA buffer as a PNG, whatever the extension of `path`.
+pub fn save_png<P: AsRef<Path>>(
+    path: P,
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+) -> image::ImageResult<()> {
+    image::save_buffer_with_format(
+        path,
+        buffer,
+        width,
+        height,
+        image::ExtendedColorType::Rgba8,
+        image::ImageFormat::Png,
+    )
+}
+
+/// Draws `mode` as a ramp from no iterations on the left to just short of
+/// `max_iterations` on the right, `height` rows of RGBA pixels. Every
+/// column is [`color_map`] of its share of the iteration limit, so schemes
+/// can be compared apart from any fractal.
+pub fn color_map_strip(mode: ColorMode, max_iterations: u32, width: u32, height: u32) -> Vec<u8> {
+    let row: Vec<u8> = (0..width as u64)
+        .flat_map(|x| {
+            let iterations = x * max_iterations as u64 / width as u64;
+            let [r, g, b] = color_map(iterations as u32, max_iterations, mode);
+            [r, g, b, 255]
+        })
+        .collect();
+    row.repeat(height as usize)
+}
+
+/// Runs `write` on a temporary file next to `path`, only moving it into
+/// place once it is complete. A cancelled or failed export leaves nothing
+/// behind, so `path` never holds a truncated image.
+fn write_complete<F>(path: &Path, cancel: &AtomicBool, write: F) -> Result<(), ExportError>
+where
+    F: FnOnce(&Path) -> Result<(), ExportError>,
+{
+    let partial = partial_path(path);
+    let result = write(&partial).and_then(|()| match cancel.load(Ordering::Relaxed) {
+        true => Err(ExportError::Cancelled),
+        false => std::fs::rename(&partial, path).map_err(ExportError::Io),
+    });
+
+    if result.is_err() {
+        // It may not have been created yet
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn write_png(
+    renderer: &Renderer,
+    path: &Path,
+    cancel: &AtomicBool,
+    width: u32,
+    height: u32,
+) -> Result<(), ExportError> {
+    write_complete(path, cancel, |partial| {
+        let mut buffer = image_buffer(width, height, 4)?;
+        renderer
+            .render_to_buffer_cancellable(width, height, &mut buffer, cancel)
+            .ok_or(ExportError::Cancelled)?;
+        save_png(partial, &buffer, width, height).map_err(ExportError::Image)
+    })
+}
+
+fn write_png16(renderer: &Renderer, path: &Path, cancel: &AtomicBool) -> Result<(), ExportError> {
+    write_complete(path, cancel, |partial| {
+        let mut buffer = image_buffer(renderer.width(), renderer.height(), 3)?;
+        renderer
+            .render_rgb16(&mut buffer, cancel)
+            .ok_or(ExportError::Cancelled)?;
+        image::save_buffer_with_format(
+            partial,
+            bytemuck::cast_slice(&buffer),
+            renderer.width(),
+            renderer.height(),
+            image::ExtendedColorType::Rgb16,
+            image::ImageFormat::Png,
+        )
+        .map_err(ExportError::Image)
+    })
+}
+
+/// Writes the raw iteration counts as a binary PGM with two big-endian bytes
+/// per pixel, row by row. Points that never escaped hold the iteration
+/// limit, which a comment in the header records.
+fn write_iterations(
+    renderer: &Renderer,
+    path: &Path,
+    cancel: &AtomicBool,
+) -> Result<(), ExportError> {
+    write_complete(path, cancel, |partial| {
+        // Two big-endian bytes per count
+        let mut bytes = image_buffer(renderer.width(), renderer.height(), 2)?;
+        let counts = renderer
+            .iteration_counts_cancellable(cancel)
+            .ok_or(ExportError::Cancelled)?;
+        for (pair, count) in bytes.chunks_exact_mut(2).zip(counts) {
+            pair.copy_from_slice(&(count.min(PGM_MAX_VALUE) as u16).to_be_bytes());
+        }
+
+        let header = format!(
+            "P5\n# iteration counts, max_iterations {}\n{} {}\n{PGM_MAX_VALUE}\n",
+            renderer.max_iterations(),
+            renderer.width(),
+            renderer.height()
+        );
+        let mut file = File::create(partial).map_err(ExportError::Io)?;
+        file.write_all(header.as_bytes())
+            .and_then(|()| file.write_all(&bytes))
+            .map_err(ExportError::Io)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Args;
+
+    #[test]
+    fn test_cancelled_export_leaves_no_file() {
+        let renderer = Renderer::from_args(&Args::default().with_size(16, 12));
+
+        let path = crate::temp_path("export_done.png");
+        write_png16(&renderer, &path, &AtomicBool::new(false)).unwrap();
+        assert!(path.exists());
+        assert!(!partial_path(&path).exists());
+        std::fs::remove_file(&path).unwrap();
+
+        let path = crate::temp_path("export_cancelled.png");
+        let cancelled = AtomicBool::new(true);
+        let results = [
+            write_png16(&renderer, &path, &cancelled),
+            write_png(&renderer, &path, &cancelled, 16, 12),
+            write_iterations(&renderer, &path, &cancelled),
+        ];
+        for result in results {
+            assert!(matches!(result, Err(ExportError::Cancelled)));
+            assert!(!path.exists());
+            assert!(!partial_path(&path).exists());
+        }
+    }
+
+    #[test]
+    fn test_png_export_uses_requested_size() {
+        let renderer = Renderer::from_args(&Args::default().with_size(16, 12));
+        let path = crate::temp_path("export_2x.png");
+
+        ExportTask::spawn_png(renderer, path.clone(), 32, 24)
+            .wait()
+            .unwrap();
+        assert_eq!(image::image_dimensions(&path).unwrap(), (32, 24));
+        std::fs::remove_file(&path).unwrap();
+
+        // Refused before anything is allocated
+        let path = crate::temp_path("export_huge.png");
+        let renderer = Renderer::from_args(&Args::default().with_size(16, 12));
+        let result = ExportTask::spawn_png(renderer, path.clone(), u32::MAX, u32::MAX).wait();
+        assert!(matches!(result, Err(ExportError::TooLarge { .. })));
+        assert!(!path.exists());
+        // Addressable on 64 bit targets, but more than can be allocated
+        let result = image_buffer::<u8>(u32::MAX, u32::MAX, 1);
+        assert!(matches!(result, Err(ExportError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn test_color_map_strip_ramps_across_the_width() {
+        let strip = color_map_strip(ColorMode::Red, 256, 512, 4);
+        assert_eq!(strip.len(), 512 * 4 * 4);
+        let rows: Vec<&[u8]> = strip.chunks_exact(512 * 4).collect();
+        assert!(rows.iter().all(|row| *row == rows[0]));
+
+        // Two columns per iteration count, from black up to almost full red
+        assert_eq!(rows[0][..8], [0, 0, 0, 255, 0, 0, 0, 255]);
+        let last = &rows[0][511 * 4..];
+        assert_eq!(last[..3], color_map(255, 256, ColorMode::Red));
+        assert!(last[0] > 250);
+        assert!(rows[0]
+            .chunks_exact(8)
+            .zip(rows[0].chunks_exact(8).skip(1))
+            .all(|(left, right)| left[0] <= right[0]));
+
+        assert!(color_map_strip(ColorMode::Smooth, 100, 0, 32).is_empty());
+    }
+
+    #[test]
+    fn test_iterations_export_keeps_raw_counts() {
+        for max_iterations in [300, 70_000] {
+            let args = Args::default()
+                .with_size(16, 12)
+                .with_max_iterations(max_iterations);
+            let renderer = Renderer::from_args(&args);
+            let path = crate::temp_path(&format!("iterations_{max_iterations}.pgm"));
+            ExportTask::spawn_iterations(renderer.clone(), path.clone())
+                .wait()
+                .unwrap();
+
+            let bytes = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            let header =
+                format!("P5\n# iteration counts, max_iterations {max_iterations}\n16 12\n65535\n");
+            assert!(bytes.starts_with(header.as_bytes()));
+            let counts: Vec<u32> = bytes[header.len()..]
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]).into())
+                .collect();
+            assert_eq!(counts.len(), 16 * 12);
+
+            // The interior holds the limit, or as much of it as fits
+            let expected = renderer.iteration_counts();
+            assert!(expected.contains(&max_iterations));
+            for (count, expected) in counts.iter().zip(expected) {
+                assert_eq!(*count, expected.min(PGM_MAX_VALUE));
+            }
+        }
+    }
+}
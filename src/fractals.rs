@@ -1,20 +1,1040 @@
 use num_complex::Complex64;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
-pub fn mandelbrot(real: f64, imag: f64, max_iter: u32) -> u32 {
-    let c = Complex64::new(real, imag);
-    let mut z = Complex64::new(0.0, 0.0);
+/// The classic escape radius: once `|z| > 2` the orbit is bound to diverge.
+pub const DEFAULT_BAILOUT: f64 = 2.0;
 
+/// How close an orbit has to come back to a value it had before to count
+/// as caught in a cycle, see [`mandelbrot`].
+pub const PERIOD_EPSILON: f64 = 1e-14;
+
+/// Stripes per turn around the origin of [`ColorMode::StripeAverage`] until
+/// another frequency is picked.
+pub const DEFAULT_STRIPE_FREQUENCY: f64 = 5.0;
+
+/// Base color of [`ColorMode::SingleHue`] until another is picked.
+pub const DEFAULT_SINGLE_HUE: [u8; 3] = [255, 160, 0];
+
+/// How far either side of a whole potential level, in levels, the
+/// equipotential lines of [`ColorMode::Potential`] reach.
+pub const POTENTIAL_CONTOUR_WIDTH: f64 = 0.08;
+
+/// The outcome of iterating a single point.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EscapeResult {
+    /// Steps taken before escaping, or the iteration limit for points that
+    /// never did
+    pub iterations: u32,
+    pub escaped: bool,
+    /// The last `z` reached, which is the escaped value for points outside
+    /// the set
+    pub final_z: Complex64,
+    /// Average of `(1 + sin(f·arg z)) / 2` over the orbit, for the stripe
+    /// frequency `f` it was iterated with, see [`mandelbrot_stripe`]. 0.0
+    /// from the iterations that don't follow it.
+    pub stripe: f64,
+}
+
+impl EscapeResult {
+    /// The continuous potential `ln |z| / 2^n` of an escaped point, which
+    /// falls towards 0 closer to the set. Underflows to 0 after about a
+    /// thousand steps, past which [`Self::smooth_iterations`], its negated
+    /// base 2 logarithm plus one, still tells points apart.
+    pub fn potential(&self) -> f64 {
+        let log_z = self.final_z.norm_sqr().ln() / 2.0;
+        log_z * (-f64::from(self.iterations)).exp2()
+    }
+
+    /// The continuous escape value `n + 1 - log2(ln |z|)`. Unlike the step
+    /// count it doesn't jump between neighbouring points, so colors based on
+    /// it form smooth gradients instead of bands.
+    pub fn smooth_iterations(&self) -> f64 {
+        // ln |z| = ln(|z|²) / 2
+        let log_z = self.final_z.norm_sqr().ln() / 2.0;
+        self.iterations as f64 + 1.0 - log_z.log2()
+    }
+
+    /// The argument of [`Self::final_z`] in turns, from 0.0 up to but not
+    /// including 1.0. Only meaningful for escaped points.
+    pub fn angle(&self) -> f64 {
+        let turns = self.final_z.im.atan2(self.final_z.re) / core::f64::consts::TAU;
+        // Wrapping a tiny negative angle can round up to exactly 1.0
+        let turns = turns - turns.floor();
+        if turns < 1.0 {
+            turns
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Counts the steps until `z` escapes for `c = real + imag·i`, up to
+/// `max_iter`. A point escapes once `|z| > bailout`, which has to be at
+/// least [`DEFAULT_BAILOUT`] for the result to be exact. Raising it costs a
+/// few extra iterations per pixel but gives smoother gradients when coloring
+/// by [`EscapeResult::smooth_iterations`].
+///
+/// Orbits that return to within [`PERIOD_EPSILON`] of an earlier value are
+/// periodic and never escape, so they stop early with `max_iter`. The value
+/// compared against is saved after 1, 2, 4, 8, ... steps, which catches
+/// cycles of any length once the gap between saves exceeds it.
+pub fn mandelbrot(real: f64, imag: f64, max_iter: u32, bailout: f64) -> EscapeResult {
+    escape_from(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        Step::Square,
+    )
+}
+
+/// Iterates `z = z² + c` from the point `z_real + z_imag·i` with a fixed `c`,
+/// counting steps until escape the same way as [`mandelbrot`].
+pub fn julia(z_real: f64, z_imag: f64, c: Complex64, max_iter: u32, bailout: f64) -> EscapeResult {
+    escape_from(
+        Complex64::new(z_real, z_imag),
+        c,
+        max_iter,
+        bailout,
+        Step::Square,
+    )
+}
+
+/// Iterates `z = z^power + c` from `z = 0` for `c = real + imag·i`, the
+/// multibrot set of degree `power`, counting steps like [`mandelbrot`].
+/// Whole powers are computed by repeated multiplication, others with
+/// [`Complex64::powf`].
+pub fn multibrot(real: f64, imag: f64, power: f64, max_iter: u32, bailout: f64) -> EscapeResult {
+    escape_from(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        Step::Power(power),
+    )
+}
+
+/// The roots of `z³ - 1` that [`newton`] converges to, in index order.
+pub const NEWTON_ROOTS: [Complex64; 3] = [
+    Complex64::new(1.0, 0.0),
+    Complex64::new(-0.5, 0.866_025_403_784_438_6),
+    Complex64::new(-0.5, -0.866_025_403_784_438_6),
+];
+
+/// How little a step of [`newton`] has to move `z` for it to have converged.
+pub const NEWTON_EPSILON: f64 = 1e-9;
+
+/// Runs Newton's method `z = z - p(z)/p'(z)` for `p(z) = z³ - 1` from `z0`.
+/// Returns the index into [`NEWTON_ROOTS`] of the root it converged to,
+/// once a step moves `z` by less than [`NEWTON_EPSILON`], and the steps
+/// taken before that. Points that don't converge within `max_iter` steps
+/// return `(None, max_iter)`.
+pub fn newton(z0: Complex64, max_iter: u32) -> (Option<usize>, u32) {
+    let mut z = z0;
+    for iteration in 0..max_iter {
+        let next = Step::Newton.apply(z, Complex64::new(0.0, 0.0));
+        // At z = 0 the tangent is flat and leads nowhere
+        if !next.is_finite() {
+            break;
+        }
+        if (next - z).norm_sqr() < NEWTON_EPSILON * NEWTON_EPSILON {
+            let root = (0..NEWTON_ROOTS.len())
+                .min_by(|&a, &b| {
+                    let distance = |root: usize| (NEWTON_ROOTS[root] - next).norm_sqr();
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap_or(0);
+            return (Some(root), iteration);
+        }
+        z = next;
+    }
+
+    (None, max_iter)
+}
+
+/// Iterates `z = (|Re(z)| + |Im(z)|·i)² + c` for `c = real + imag·i`,
+/// counting steps until escape the same way as [`mandelbrot`].
+pub fn burning_ship(real: f64, imag: f64, max_iter: u32, bailout: f64) -> EscapeResult {
+    escape_from(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        Step::BurningShip,
+    )
+}
+
+/// Iterates `z = conj(z)² + c` for `c = real + imag·i`, the Tricorn or
+/// Mandelbar set, counting steps until escape the same way as
+/// [`mandelbrot`].
+pub fn tricorn(real: f64, imag: f64, max_iter: u32, bailout: f64) -> EscapeResult {
+    escape_from(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        Step::Tricorn,
+    )
+}
+
+fn escape_from(
+    mut z: Complex64,
+    c: Complex64,
+    max_iter: u32,
+    bailout: f64,
+    step: Step,
+) -> EscapeResult {
+    // Comparing squared magnitudes saves a square root per step
+    let bailout_sqr = bailout * bailout;
+    let period_sqr = PERIOD_EPSILON * PERIOD_EPSILON;
+    let mut reference = z;
+    let mut next_save = 1u64;
     for iteration in 0..max_iter {
-        if z.norm() > 2.0 {
-            return iteration;
+        if z.norm_sqr() > bailout_sqr {
+            return EscapeResult {
+                iterations: iteration,
+                escaped: true,
+                final_z: z,
+                stripe: 0.0,
+            };
+        }
+        z = step.apply(z, c);
+
+        if (z - reference).norm_sqr() < period_sqr {
+            break;
+        }
+        if u64::from(iteration) + 1 == next_save {
+            reference = z;
+            next_save *= 2;
         }
-        z = z * z + c;
     }
 
-    max_iter
+    EscapeResult {
+        iterations: max_iter,
+        escaped: false,
+        final_z: z,
+        stripe: 0.0,
+    }
 }
 
+/// The smallest magnitude on the cycle the orbit from `z` settles on, see
+/// [`FractalKind::cycle_magnitude`]. Iterates like [`escape_from`], and once
+/// the orbit comes back to the saved value, the steps since then went once
+/// around the cycle.
+fn cycle_magnitude_from(
+    mut z: Complex64,
+    c: Complex64,
+    max_iter: u32,
+    bailout: f64,
+    step: Step,
+) -> f64 {
+    let bailout_sqr = bailout * bailout;
+    let period_sqr = PERIOD_EPSILON * PERIOD_EPSILON;
+    let mut reference = z;
+    let mut next_save = 1u64;
+    // Smallest squared magnitude since `reference` was saved
+    let mut smallest = f64::INFINITY;
+    for iteration in 0..max_iter {
+        if z.norm_sqr() > bailout_sqr {
+            return z.norm();
+        }
+        z = step.apply(z, c);
+        smallest = smallest.min(z.norm_sqr());
+
+        if (z - reference).norm_sqr() < period_sqr {
+            break;
+        }
+        if u64::from(iteration) + 1 == next_save {
+            reference = z;
+            next_save *= 2;
+            smallest = f64::INFINITY;
+        }
+    }
+
+    if smallest.is_finite() {
+        smallest.sqrt()
+    } else {
+        z.norm()
+    }
+}
+
+/// One step of an iteration `z -> f(z, c)`.
 #[derive(Clone, Copy)]
+enum Step {
+    /// `z² + c`
+    Square,
+    /// `(|Re(z)| + |Im(z)|·i)² + c`
+    BurningShip,
+    /// `conj(z)² + c`
+    Tricorn,
+    /// `z^d + c`
+    Power(f64),
+    /// `z - (z³ - 1) / 3z²`, ignoring `c`
+    Newton,
+}
+
+impl Step {
+    fn apply(self, z: Complex64, c: Complex64) -> Complex64 {
+        match self {
+            Step::Square => z * z + c,
+            Step::BurningShip => {
+                let z = Complex64::new(z.re.abs(), z.im.abs());
+                z * z + c
+            }
+            Step::Tricorn => {
+                let z = z.conj();
+                z * z + c
+            }
+            Step::Power(power) if power.fract() == 0.0 => {
+                // Repeated multiplication is exact where powf goes through polar form
+                let mut result = z;
+                for _ in 1..power as u32 {
+                    result *= z;
+                }
+                result + c
+            }
+            Step::Power(power) => z.powf(power) + c,
+            Step::Newton => z - (z * z * z - 1.0) / (3.0 * z * z),
+        }
+    }
+
+    /// The derivative of the step at `z`, times `dz`, for following
+    /// `dz` along the orbit.
+    fn derivative(self, z: Complex64, dz: Complex64) -> Complex64 {
+        match self {
+            Step::Square => 2.0 * z * dz,
+            // The fold only mirrors z, so this keeps the right magnitude
+            Step::BurningShip => 2.0 * Complex64::new(z.re.abs(), z.im.abs()) * dz,
+            // Conjugating isn't complex differentiable either, but keeps the magnitude
+            Step::Tricorn => 2.0 * z.conj() * dz,
+            Step::Power(power) if power.fract() == 0.0 => power * z.powi(power as i32 - 1) * dz,
+            Step::Power(power) => power * z.powf(power - 1.0) * dz,
+            Step::Newton => {
+                let cube = z * z * z;
+                2.0 * (cube - 1.0) / (3.0 * cube) * dz
+            }
+        }
+    }
+}
+
+/// The successive `z` values of the Mandelbrot iteration for `c = real + imag*i`,
+/// starting at `z1 = c`. Ends after the first value that escapes, or after
+/// `max_iter` values, so it yields as many values as [`mandelbrot`] counts
+/// with the same `bailout`.
+pub fn orbit(real: f64, imag: f64, max_iter: u32, bailout: f64) -> impl Iterator<Item = Complex64> {
+    orbit_from(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        Step::Square,
+    )
+}
+
+fn orbit_from(
+    z: Complex64,
+    c: Complex64,
+    max_iter: u32,
+    bailout: f64,
+    step: Step,
+) -> impl Iterator<Item = Complex64> {
+    let bailout_sqr = bailout * bailout;
+    let first = (z.norm_sqr() <= bailout_sqr).then(|| step.apply(z, c));
+    core::iter::successors(first, move |&z| {
+        (z.norm_sqr() <= bailout_sqr).then(|| step.apply(z, c))
+    })
+    .take(max_iter as usize)
+}
+
+/// Which fractal a point is iterated for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FractalKind {
+    Mandelbrot,
+    /// Points are the starting `z`, iterated with this fixed `c`
+    Julia {
+        c: Complex64,
+    },
+    BurningShip,
+    /// `z = conj(z)² + c`, see [`tricorn`]
+    Tricorn,
+    /// `z = z^power + c`, see [`multibrot`]. Power 2 is the Mandelbrot set,
+    /// which [`FractalKind::multibrot`] returns as such.
+    Multibrot {
+        power: f64,
+    },
+    /// Newton's method for the roots of `z³ - 1`, see [`newton`]. Its
+    /// escape counts the steps to converge, with `escaped` set for points
+    /// that reached a root and `final_z` being that root.
+    Newton,
+}
+
+impl FractalKind {
+    /// A Julia set with a connected, dendrite-like shape.
+    pub const DEFAULT_JULIA: FractalKind = FractalKind::Julia {
+        c: Complex64::new(-0.8, 0.156),
+    };
+
+    /// The multibrot set of degree `power`, or [`FractalKind::Mandelbrot`]
+    /// for power 2 so it keeps its faster kernels.
+    pub fn multibrot(power: f64) -> FractalKind {
+        if power == 2.0 {
+            FractalKind::Mandelbrot
+        } else {
+            FractalKind::Multibrot { power }
+        }
+    }
+
+    /// The exponent of `z` in the iteration, if it is a power of `z`.
+    pub fn power(&self) -> Option<f64> {
+        match *self {
+            FractalKind::Mandelbrot | FractalKind::Julia { .. } => Some(2.0),
+            FractalKind::Multibrot { power } => Some(power),
+            FractalKind::BurningShip | FractalKind::Tricorn | FractalKind::Newton => None,
+        }
+    }
+
+    /// Center and scale of the view each fractal starts out with.
+    pub fn default_view(&self) -> (Complex64, f64) {
+        match self {
+            FractalKind::Mandelbrot => (Complex64::new(-0.5, 0.0), 2.5),
+            FractalKind::Julia { .. } => (Complex64::new(0.0, 0.0), 3.0),
+            // The ship itself, upright as the imaginary axis points down the screen
+            FractalKind::BurningShip => (Complex64::new(-1.75, -0.03), 0.15),
+            // Reaches further right than the Mandelbrot set, and higher up
+            FractalKind::Tricorn => (Complex64::new(-0.56, 0.0), 3.2),
+            FractalKind::Multibrot { .. } => (Complex64::new(0.0, 0.0), 3.0),
+            FractalKind::Newton => (Complex64::new(0.0, 0.0), 3.0),
+        }
+    }
+
+    /// The next kind to cycle to, for switching between them with one key.
+    pub fn next(&self) -> FractalKind {
+        match self {
+            FractalKind::Mandelbrot | FractalKind::Multibrot { .. } => FractalKind::BurningShip,
+            FractalKind::BurningShip => FractalKind::Tricorn,
+            FractalKind::Tricorn => FractalKind::DEFAULT_JULIA,
+            FractalKind::Julia { .. } => FractalKind::Newton,
+            FractalKind::Newton => FractalKind::Mandelbrot,
+        }
+    }
+
+    /// Escape of the point `real + imag·i`, like [`mandelbrot`].
+    pub fn escape(&self, real: f64, imag: f64, max_iter: u32, bailout: f64) -> EscapeResult {
+        match *self {
+            FractalKind::Mandelbrot => mandelbrot(real, imag, max_iter, bailout),
+            FractalKind::Julia { c } => julia(real, imag, c, max_iter, bailout),
+            FractalKind::BurningShip => burning_ship(real, imag, max_iter, bailout),
+            FractalKind::Tricorn => tricorn(real, imag, max_iter, bailout),
+            FractalKind::Multibrot { power } => multibrot(real, imag, power, max_iter, bailout),
+            FractalKind::Newton => {
+                let z0 = Complex64::new(real, imag);
+                let (root, iterations) = newton(z0, max_iter);
+                EscapeResult {
+                    iterations,
+                    escaped: root.is_some(),
+                    final_z: root.map_or(z0, |root| NEWTON_ROOTS[root]),
+                    stripe: 0.0,
+                }
+            }
+        }
+    }
+
+    /// Like [`mandelbrot_trap`], for this kind.
+    pub fn trap(
+        &self,
+        real: f64,
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+        trap: &TrapShape,
+    ) -> (EscapeResult, f64) {
+        match *self {
+            FractalKind::Mandelbrot => mandelbrot_trap(real, imag, max_iter, bailout, trap),
+            FractalKind::Julia { c } => escape_trap(
+                Complex64::new(real, imag),
+                c,
+                max_iter,
+                bailout,
+                trap,
+                Step::Square,
+            ),
+            FractalKind::BurningShip => escape_trap(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                trap,
+                Step::BurningShip,
+            ),
+            FractalKind::Tricorn => escape_trap(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                trap,
+                Step::Tricorn,
+            ),
+            FractalKind::Multibrot { power } => escape_trap(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                trap,
+                Step::Power(power),
+            ),
+            FractalKind::Newton => escape_trap(
+                Complex64::new(real, imag),
+                Complex64::new(0.0, 0.0),
+                max_iter,
+                bailout,
+                trap,
+                Step::Newton,
+            ),
+        }
+    }
+
+    /// Like [`mandelbrot_stripe`], for this kind. The Newton fractal has no
+    /// stripes and escapes like [`Self::escape`].
+    pub fn stripe(
+        &self,
+        real: f64,
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+        frequency: f64,
+    ) -> EscapeResult {
+        let point = Complex64::new(real, imag);
+        let origin = Complex64::new(0.0, 0.0);
+        match *self {
+            FractalKind::Mandelbrot => mandelbrot_stripe(real, imag, max_iter, bailout, frequency),
+            FractalKind::Julia { c } => {
+                escape_stripe(point, c, max_iter, bailout, frequency, Step::Square)
+            }
+            FractalKind::BurningShip => escape_stripe(
+                origin,
+                point,
+                max_iter,
+                bailout,
+                frequency,
+                Step::BurningShip,
+            ),
+            FractalKind::Tricorn => {
+                escape_stripe(origin, point, max_iter, bailout, frequency, Step::Tricorn)
+            }
+            FractalKind::Multibrot { power } => escape_stripe(
+                origin,
+                point,
+                max_iter,
+                bailout,
+                frequency,
+                Step::Power(power),
+            ),
+            FractalKind::Newton => self.escape(real, imag, max_iter, bailout),
+        }
+    }
+
+    /// The smallest magnitude on the cycle the orbit of a point inside the
+    /// set settles on, for shading the interior. Every point of the cycle
+    /// gives the same value, unlike [`EscapeResult::final_z`], which is
+    /// whichever one the iteration stopped at. Newton basins have no cycles
+    /// and give the magnitude [`Self::escape`] ends at.
+    pub fn cycle_magnitude(&self, real: f64, imag: f64, max_iter: u32, bailout: f64) -> f64 {
+        let point = Complex64::new(real, imag);
+        let origin = Complex64::new(0.0, 0.0);
+        match *self {
+            FractalKind::Mandelbrot => {
+                cycle_magnitude_from(origin, point, max_iter, bailout, Step::Square)
+            }
+            FractalKind::Julia { c } => {
+                cycle_magnitude_from(point, c, max_iter, bailout, Step::Square)
+            }
+            FractalKind::BurningShip => {
+                cycle_magnitude_from(origin, point, max_iter, bailout, Step::BurningShip)
+            }
+            FractalKind::Tricorn => {
+                cycle_magnitude_from(origin, point, max_iter, bailout, Step::Tricorn)
+            }
+            FractalKind::Multibrot { power } => {
+                cycle_magnitude_from(origin, point, max_iter, bailout, Step::Power(power))
+            }
+            FractalKind::Newton => self.escape(real, imag, max_iter, bailout).final_z.norm(),
+        }
+    }
+
+    /// Like [`mandelbrot_distance`], for this kind. Julia sets take the
+    /// derivative with respect to the starting point instead of `c`.
+    pub fn distance(
+        &self,
+        real: f64,
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+    ) -> (EscapeResult, f64) {
+        let point = Complex64::new(real, imag);
+        let origin = Complex64::new(0.0, 0.0);
+        match *self {
+            FractalKind::Mandelbrot => mandelbrot_distance(real, imag, max_iter, bailout),
+            FractalKind::Julia { c } => {
+                escape_distance(point, c, false, max_iter, bailout, Step::Square)
+            }
+            FractalKind::BurningShip => {
+                escape_distance(origin, point, true, max_iter, bailout, Step::BurningShip)
+            }
+            FractalKind::Tricorn => {
+                escape_distance(origin, point, true, max_iter, bailout, Step::Tricorn)
+            }
+            FractalKind::Multibrot { power } => {
+                escape_distance(origin, point, true, max_iter, bailout, Step::Power(power))
+            }
+            FractalKind::Newton => {
+                escape_distance(point, origin, false, max_iter, bailout, Step::Newton)
+            }
+        }
+    }
+
+    /// Like [`orbit`], for this kind. Julia orbits start at `z1`, one step
+    /// on from the point itself.
+    pub fn orbit(
+        &self,
+        real: f64,
+        imag: f64,
+        max_iter: u32,
+        bailout: f64,
+    ) -> impl Iterator<Item = Complex64> {
+        match *self {
+            FractalKind::Mandelbrot => orbit_from(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                Step::Square,
+            ),
+            FractalKind::Julia { c } => orbit_from(
+                Complex64::new(real, imag),
+                c,
+                max_iter,
+                bailout,
+                Step::Square,
+            ),
+            FractalKind::BurningShip => orbit_from(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                Step::BurningShip,
+            ),
+            FractalKind::Tricorn => orbit_from(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                Step::Tricorn,
+            ),
+            FractalKind::Multibrot { power } => orbit_from(
+                Complex64::new(0.0, 0.0),
+                Complex64::new(real, imag),
+                max_iter,
+                bailout,
+                Step::Power(power),
+            ),
+            FractalKind::Newton => orbit_from(
+                Complex64::new(real, imag),
+                Complex64::new(0.0, 0.0),
+                max_iter,
+                bailout,
+                Step::Newton,
+            ),
+        }
+    }
+}
+
+/// An escape-time fractal the renderer can draw in place of the built-in
+/// ones, see `Renderer::set_custom_fractal`. Every render thread calls it
+/// through the same shared reference, hence `Sync`.
+pub trait Fractal: Send + Sync {
+    /// Escape of the point `c`, iterating at most `max_iter` times or until
+    /// `|z|` passes `bailout`, the renderer's escape radius.
+    fn iterate(&self, c: Complex64, max_iter: u32, bailout: f64) -> EscapeResult;
+}
+
+impl Fractal for FractalKind {
+    fn iterate(&self, c: Complex64, max_iter: u32, bailout: f64) -> EscapeResult {
+        self.escape(c.re, c.im, max_iter, bailout)
+    }
+}
+
+/// Whether `re + im·i` lies strictly inside the main cardioid, the large
+/// body of the Mandelbrot set. Points on its edge return `false`.
+pub fn in_main_cardioid(re: f64, im: f64) -> bool {
+    let x = re - 0.25;
+    let q = x * x + im * im;
+    q * (q + x) < 0.25 * im * im
+}
+
+/// Whether `re + im·i` lies strictly inside the period-2 bulb, the disc of
+/// radius 1/4 around -1 left of the main cardioid. Points on its edge return
+/// `false`.
+pub fn in_period2_bulb(re: f64, im: f64) -> bool {
+    let x = re + 1.0;
+    x * x + im * im < 0.0625
+}
+
+/// An 8x8 bitmap used by [`TrapShape::Texture`], one row per byte.
+const TRAP_TEXTURE: [u8; 8] = [
+    0b0011_1100,
+    0b0100_0010,
+    0b1010_0101,
+    0b1000_0001,
+    0b1010_0101,
+    0b1001_1001,
+    0b0100_0010,
+    0b0011_1100,
+];
+
+/// A shape the orbit of a point is measured against for orbit-trap coloring.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrapShape {
+    Point {
+        center: Complex64,
+    },
+    /// A line through the origin at `angle` radians from the real axis
+    Line {
+        angle: f64,
+    },
+    /// Horizontal and vertical lines crossing at `center`
+    Cross {
+        center: Complex64,
+    },
+    Circle {
+        center: Complex64,
+        radius: f64,
+    },
+    /// A small bitmap centered at `center`, `size` units wide
+    Texture {
+        center: Complex64,
+        size: f64,
+    },
+}
+
+impl TrapShape {
+    /// One of each shape with reasonable parameters, in cycling order.
+    pub const DEFAULTS: [TrapShape; 5] = [
+        TrapShape::Point {
+            center: Complex64::new(0.0, 0.0),
+        },
+        TrapShape::Line { angle: 0.0 },
+        TrapShape::Cross {
+            center: Complex64::new(0.0, 0.0),
+        },
+        TrapShape::Circle {
+            center: Complex64::new(0.0, 0.0),
+            radius: 0.5,
+        },
+        TrapShape::Texture {
+            center: Complex64::new(0.0, 0.0),
+            size: 1.0,
+        },
+    ];
+
+    pub fn distance(&self, z: Complex64) -> f64 {
+        match *self {
+            TrapShape::Point { center } => (z - center).norm(),
+            TrapShape::Line { angle } => (z.im * angle.cos() - z.re * angle.sin()).abs(),
+            TrapShape::Cross { center } => (z.re - center.re).abs().min((z.im - center.im).abs()),
+            TrapShape::Circle { center, radius } => ((z - center).norm() - radius).abs(),
+            TrapShape::Texture { center, size } => {
+                let texel_size = size / 8.0;
+                let local = (z - center) / texel_size + Complex64::new(4.0, 4.0);
+                let (x, y) = (local.re.floor(), local.im.floor());
+
+                if (0.0..8.0).contains(&x) && (0.0..8.0).contains(&y) {
+                    // Set texels trap the orbit, clear ones are a texel away
+                    let lit = TRAP_TEXTURE[y as usize] & (0b1000_0000 >> x as usize) != 0;
+                    if lit {
+                        0.0
+                    } else {
+                        texel_size
+                    }
+                } else {
+                    // Chebyshev distance to the edge of the bitmap
+                    let offset = z - center;
+                    (offset.re.abs().max(offset.im.abs()) - size / 2.0).max(texel_size)
+                }
+            }
+        }
+    }
+
+    /// The next shape in [`TrapShape::DEFAULTS`].
+    pub fn next(&self) -> TrapShape {
+        let index = TrapShape::DEFAULTS
+            .iter()
+            .position(|shape| core::mem::discriminant(shape) == core::mem::discriminant(self))
+            .unwrap_or(0);
+        TrapShape::DEFAULTS[(index + 1) % TrapShape::DEFAULTS.len()]
+    }
+
+    /// Tweaks the shape's main parameter by `steps`: lines rotate, circles
+    /// and textures resize, points and crosses slide along the real axis.
+    pub fn adjust(&mut self, steps: f64) {
+        match self {
+            TrapShape::Point { center } | TrapShape::Cross { center } => center.re += 0.05 * steps,
+            TrapShape::Line { angle } => *angle += core::f64::consts::PI / 12.0 * steps,
+            TrapShape::Circle { radius, .. } => *radius *= 1.2_f64.powf(steps),
+            TrapShape::Texture { size, .. } => *size *= 1.2_f64.powf(steps),
+        }
+    }
+}
+
+/// Iterates like [`mandelbrot`] while tracking how close the orbit gets to
+/// `trap`. Returns the escape result and the smallest distance seen.
+pub fn mandelbrot_trap(
+    real: f64,
+    imag: f64,
+    max_iter: u32,
+    bailout: f64,
+    trap: &TrapShape,
+) -> (EscapeResult, f64) {
+    escape_trap(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        trap,
+        Step::Square,
+    )
+}
+
+fn escape_trap(
+    mut z: Complex64,
+    c: Complex64,
+    max_iter: u32,
+    bailout: f64,
+    trap: &TrapShape,
+    step: Step,
+) -> (EscapeResult, f64) {
+    let bailout_sqr = bailout * bailout;
+    let mut closest = f64::INFINITY;
+
+    for iteration in 0..max_iter {
+        if z.norm_sqr() > bailout_sqr {
+            let escape = EscapeResult {
+                iterations: iteration,
+                escaped: true,
+                final_z: z,
+                stripe: 0.0,
+            };
+            return (escape, closest);
+        }
+        z = step.apply(z, c);
+        closest = closest.min(trap.distance(z));
+    }
+
+    let escape = EscapeResult {
+        iterations: max_iter,
+        escaped: false,
+        final_z: z,
+        stripe: 0.0,
+    };
+    (escape, closest)
+}
+
+/// Iterates like [`mandelbrot`] while averaging `(1 + sin(frequency·arg z))
+/// / 2` over the orbit into [`EscapeResult::stripe`]. For escaped points the
+/// average is blended with the one before the last step by how far past the
+/// bailout `z` went, so it doesn't jump between escape bands.
+pub fn mandelbrot_stripe(
+    real: f64,
+    imag: f64,
+    max_iter: u32,
+    bailout: f64,
+    frequency: f64,
+) -> EscapeResult {
+    escape_stripe(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        max_iter,
+        bailout,
+        frequency,
+        Step::Square,
+    )
+}
+
+fn escape_stripe(
+    mut z: Complex64,
+    c: Complex64,
+    max_iter: u32,
+    bailout: f64,
+    frequency: f64,
+    step: Step,
+) -> EscapeResult {
+    let bailout_sqr = bailout * bailout;
+    let mut sum = 0.0;
+    let mut last = 0.0;
+
+    for iteration in 0..max_iter {
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr > bailout_sqr {
+            let steps = f64::from(iteration);
+            let stripe = if iteration > 1 {
+                let average = sum / steps;
+                let previous = (sum - last) / (steps - 1.0);
+                // 1 where z only just passed the bailout, 0 at its square
+                let fraction = 1.0 + (bailout_sqr.ln() / norm_sqr.ln()).log2();
+                previous + (average - previous) * fraction.clamp(0.0, 1.0)
+            } else {
+                sum
+            };
+            return EscapeResult {
+                iterations: iteration,
+                escaped: true,
+                final_z: z,
+                stripe,
+            };
+        }
+        z = step.apply(z, c);
+        last = 0.5 + 0.5 * (frequency * z.arg()).sin();
+        sum += last;
+    }
+
+    EscapeResult {
+        iterations: max_iter,
+        escaped: false,
+        final_z: z,
+        stripe: sum / f64::from(max_iter.max(1)),
+    }
+}
+
+/// Iterates like [`mandelbrot`] while tracking the derivative
+/// `dz = 2·z·dz + 1` of the orbit with respect to `c`. Returns the escape
+/// result and the estimated distance `|z|·ln|z| / |dz|` from the point to
+/// the set, which is 0.0 for points that never escaped.
+pub fn mandelbrot_distance(
+    real: f64,
+    imag: f64,
+    max_iter: u32,
+    bailout: f64,
+) -> (EscapeResult, f64) {
+    escape_distance(
+        Complex64::new(0.0, 0.0),
+        Complex64::new(real, imag),
+        true,
+        max_iter,
+        bailout,
+        Step::Square,
+    )
+}
+
+/// Follows the derivative with respect to `c` when `by_c` is set, and with
+/// respect to the starting `z` otherwise.
+fn escape_distance(
+    mut z: Complex64,
+    c: Complex64,
+    by_c: bool,
+    max_iter: u32,
+    bailout: f64,
+    step: Step,
+) -> (EscapeResult, f64) {
+    let bailout_sqr = bailout * bailout;
+    let mut dz = if by_c {
+        Complex64::new(0.0, 0.0)
+    } else {
+        Complex64::new(1.0, 0.0)
+    };
+
+    for iteration in 0..max_iter {
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr > bailout_sqr {
+            let escape = EscapeResult {
+                iterations: iteration,
+                escaped: true,
+                final_z: z,
+                stripe: 0.0,
+            };
+            // |z|·ln|z| with ln|z| = ln(|z|²) / 2
+            let distance = norm_sqr.sqrt() * norm_sqr.ln() / 2.0 / dz.norm();
+            return (escape, distance);
+        }
+        dz = step.derivative(z, dz);
+        if by_c {
+            dz += 1.0;
+        }
+        z = step.apply(z, c);
+    }
+
+    let escape = EscapeResult {
+        iterations: max_iter,
+        escaped: false,
+        final_z: z,
+        stripe: 0.0,
+    };
+    (escape, 0.0)
+}
+
+/// Colors a point by its estimated distance to the set, in pixels of
+/// `pixel_size`, on the same 0.0..=255.0 scale as [`color_map_channels`].
+/// Filaments thinner than a pixel still light up the pixels they pass
+/// through, where escape counts would fade them out.
+pub fn distance_color_channels(
+    iterations: u32,
+    max_iterations: u32,
+    distance: f64,
+    pixel_size: f64,
+    invert: bool,
+) -> [f64; 3] {
+    if iterations == max_iterations {
+        return [0.0, 0.0, 0.0];
+    }
+
+    // Bright within a pixel or two of the set, dark blue further out
+    let glow = inverted((-distance / pixel_size / 2.0).exp(), invert);
+    [glow * 255.0, glow * 235.0, 40.0 + glow * 215.0]
+}
+
+/// Colors a point of the Newton fractal by the root it converged to, with
+/// the hue of the root's angle, darkening the longer it took to get there.
+/// Same 0.0..=255.0 scale as [`color_map_channels`].
+pub fn newton_color_channels(
+    iterations: u32,
+    max_iterations: u32,
+    root: Complex64,
+    invert: bool,
+) -> [f64; 3] {
+    if iterations == max_iterations {
+        return [0.0, 0.0, 0.0];
+    }
+
+    let shade = inverted(1.0 / (1.0 + iterations as f64 * 0.08), invert);
+    hsv_to_rgb(root.arg().to_degrees(), 0.8, shade)
+}
+
+/// Colors a point by how close its orbit came to the trap, on the same
+/// 0.0..=255.0 scale as [`color_map_channels`].
+pub fn trap_color_channels(
+    iterations: u32,
+    max_iterations: u32,
+    distance: f64,
+    invert: bool,
+) -> [f64; 3] {
+    if iterations == max_iterations {
+        return [0.0, 0.0, 0.0];
+    }
+
+    // Close approaches glow, distant ones fade out
+    let glow = inverted((-distance * 6.0).exp(), invert);
+    [glow * 255.0, glow.sqrt() * 200.0, (1.0 - glow) * 110.0]
+}
+
+/// `value` in `0.0..=1.0` the other way round when `invert` is set, so the
+/// palettes that aren't indexed by the iteration count can be inverted too.
+fn inverted(value: f64, invert: bool) -> f64 {
+    match invert {
+        true => 1.0 - value,
+        false => value,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ColorMode {
     Smooth,
     Zebra,
@@ -25,126 +1045,394 @@ pub enum ColorMode {
     Psychedelic,
     GreenGradient,
     Electric,
+    /// Emphasizes slowly escaping points near the boundary; a higher
+    /// exponent widens the bright band around the set.
+    EscapeVelocity(f64),
+    /// A fire gradient meant for histogram-equalized values, where the
+    /// iteration value is the point's rank among all escaped points.
+    Histogram,
+    /// Interpolates between color stops, see [`gradient_color`]. Uses
+    /// [`DEFAULT_GRADIENT`] here; the renderer colors it with the stops it
+    /// was given instead.
+    Gradient,
+    /// A glow by the continuous potential, taking the value to be
+    /// [`EscapeResult::smooth_iterations`]. With `contours`, pixels near
+    /// whole potential levels are darkened into equipotential lines.
+    Potential {
+        contours: bool,
+    },
+    /// Fades from black up to the given color with the iteration count.
+    /// `Red`, `Blue` and `GreenGradient` are this with their primaries.
+    SingleHue([u8; 3]),
+    /// Hue by the angle the orbit escaped at, see [`angle_color_channels`].
+    /// Only the escape value reaches [`color_map_value`], which takes the
+    /// angle to be 0.
+    Angle,
+    /// Stripes that flow along the orbits, from the average of
+    /// [`mandelbrot_stripe`] with the given frequency, see
+    /// [`stripe_color_channels`]. Only the escape value reaches
+    /// [`color_map_value`], which takes the average to be 0.5.
+    StripeAverage(f64),
 }
 
-pub fn color_map(iterations: u32, max_iterations: u32, mode: ColorMode) -> [u8; 3] {
-    if iterations == max_iterations {
-        // Black for points inside the set
-        return [0, 0, 0]; 
+/// A color at a position between 0.0 and 1.0 along a gradient.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    pub position: f64,
+    pub color: [u8; 3],
+}
+
+/// Dark blue through white to orange, the stops of a well-known fractal
+/// palette.
+pub const DEFAULT_GRADIENT: [GradientStop; 5] = [
+    GradientStop {
+        position: 0.0,
+        color: [0, 7, 100],
+    },
+    GradientStop {
+        position: 0.16,
+        color: [32, 107, 203],
+    },
+    GradientStop {
+        position: 0.42,
+        color: [237, 255, 255],
+    },
+    GradientStop {
+        position: 0.6425,
+        color: [255, 170, 0],
+    },
+    GradientStop {
+        position: 0.8575,
+        color: [0, 2, 0],
+    },
+];
+
+/// Linearly interpolates between `stops`, which must be sorted by position,
+/// at `t`. Before the first stop and after the last one the color is held.
+pub fn gradient_color(stops: &[GradientStop], t: f64) -> [f64; 3] {
+    blend_stops(stops, t, mix_rgb)
+}
+
+/// Like [`gradient_color`], but blends neighbouring stops in Oklab, see
+/// [`mix_oklab`].
+pub fn gradient_color_oklab(stops: &[GradientStop], t: f64) -> [f64; 3] {
+    blend_stops(stops, t, mix_oklab)
+}
+
+fn blend_stops(
+    stops: &[GradientStop],
+    t: f64,
+    mix: fn([f64; 3], [f64; 3], f64) -> [f64; 3],
+) -> [f64; 3] {
+    let Some(next) = stops.iter().position(|stop| stop.position > t) else {
+        return stops
+            .last()
+            .map_or([0.0; 3], |stop| stop.color.map(f64::from));
+    };
+    if next == 0 {
+        return stops[0].color.map(f64::from);
     }
 
-    let normalized_iter = iterations as f64 / max_iterations as f64;
+    let (from, to) = (stops[next - 1], stops[next]);
+    let blend = (t - from.position) / (to.position - from.position);
+    mix(from.color.map(f64::from), to.color.map(f64::from), blend)
+}
 
-    match mode {
-        ColorMode::Smooth => {
-            // Original coloring
-            let log_zn = (iterations as f64 * 1.0).log2();
-            let nu = log_zn / (max_iterations as f64).log2();
+/// Blends the channels of `from` into those of `to` by `t`.
+fn mix_rgb(from: [f64; 3], to: [f64; 3], t: f64) -> [f64; 3] {
+    let mut channels = from;
+    for (channel, to) in channels.iter_mut().zip(to) {
+        *channel += (to - *channel) * t;
+    }
+    channels
+}
 
-            let t = nu.fract();
-            let r = ((1.0 - t) * 9.0 + t * 15.0) as u8;
-            let g = ((1.0 - t) * 0.0 + t * 7.0) as u8;
-            let b = ((1.0 - t) * 255.0 + t * 100.0) as u8;
+/// Blends the sRGB color `from` into `to` by `t` through Oklab, where equal
+/// steps look about equally far apart. Blending the channels directly dips
+/// through dull, dark colors between distant hues instead.
+pub fn mix_oklab(from: [f64; 3], to: [f64; 3], t: f64) -> [f64; 3] {
+    oklab_to_srgb(mix_rgb(srgb_to_oklab(from), srgb_to_oklab(to), t))
+}
+
+/// Converts an sRGB color with channels in `0.0..=255.0` to Oklab's
+/// `[L, a, b]`, with the lightness `L` in `0.0..=1.0`.
+pub fn srgb_to_oklab(color: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = color.map(|channel| srgb_to_linear(channel / 255.0));
+    let l = 0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b;
+    let m = 0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b;
+    let s = 0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b;
+    let [l, m, s] = [l, m, s].map(|cone: f64| cone.cbrt());
+    [
+        0.210_454_255_3 * l + 0.793_617_785 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766 * s,
+    ]
+}
 
-            [r, g, b]
+/// Converts an Oklab `[L, a, b]` color back to sRGB channels in
+/// `0.0..=255.0`, clamping colors sRGB can't show.
+pub fn oklab_to_srgb(lab: [f64; 3]) -> [f64; 3] {
+    let [lightness, a, b] = lab;
+    let l = lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b;
+    let m = lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b;
+    let s = lightness - 0.089_484_177_5 * a - 1.291_485_548 * b;
+    let [l, m, s] = [l, m, s].map(|cone| cone * cone * cone);
+    [
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701 * s,
+    ]
+    .map(|channel| linear_to_srgb(channel.clamp(0.0, 1.0)) * 255.0)
+}
+
+/// Undoes the sRGB transfer curve of a channel in `0.0..=1.0`.
+fn srgb_to_linear(channel: f64) -> f64 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Applies the sRGB transfer curve to a linear channel in `0.0..=1.0`.
+fn linear_to_srgb(channel: f64) -> f64 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// The colors of [`ColorMode::Smooth`], fading from bright to dark blue
+/// once per wrap of the log of the iteration count, blended in Oklab with
+/// `perceptual`.
+pub fn smooth_color(iterations: f64, max_iterations: u32, perceptual: bool) -> [f64; 3] {
+    // From 1 like the GPU shader as log2(0) is -inf
+    let log_zn = iterations.max(1.0).log2();
+    // A limit of 1 would divide by log2(1) = 0
+    let nu = log_zn / (max_iterations as f64).log2().max(1.0);
+
+    let t = nu.fract();
+    let (from, to) = ([9.0, 0.0, 255.0], [15.0, 7.0, 100.0]);
+    match perceptual {
+        true => mix_oklab(from, to, t),
+        false => mix_rgb(from, to, t),
+    }
+}
+
+/// Compresses the skewed iteration distribution before coloring, like an
+/// HDR tonemap. Low iteration counts are stretched over more of the palette
+/// while high counts near the boundary still stay distinct.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMap {
+    None,
+    /// `L / (1 + L)`
+    Reinhard {
+        exposure: f64,
+    },
+    /// `ln(1 + L)`
+    Log {
+        exposure: f64,
+    },
+}
+
+impl ToneMap {
+    /// The operators in cycling order, with a reasonable exposure.
+    pub const DEFAULTS: [ToneMap; 3] = [
+        ToneMap::None,
+        ToneMap::Reinhard { exposure: 8.0 },
+        ToneMap::Log { exposure: 8.0 },
+    ];
+
+    /// Maps a normalized iteration value in `0.0..=1.0` back onto `0.0..=1.0`,
+    /// where `L = normalized * exposure` and the result is scaled so that
+    /// 1.0 still maps to 1.0.
+    pub fn apply(&self, normalized: f64) -> f64 {
+        match *self {
+            ToneMap::None => normalized,
+            ToneMap::Reinhard { exposure } => {
+                let luminance = normalized * exposure;
+                luminance / (1.0 + luminance) * (1.0 + exposure) / exposure
+            }
+            ToneMap::Log { exposure } => (normalized * exposure).ln_1p() / exposure.ln_1p(),
         }
+    }
+
+    /// The next operator in [`ToneMap::DEFAULTS`].
+    pub fn next(&self) -> ToneMap {
+        let index = ToneMap::DEFAULTS
+            .iter()
+            .position(|tonemap| core::mem::discriminant(tonemap) == core::mem::discriminant(self))
+            .unwrap_or(0);
+        ToneMap::DEFAULTS[(index + 1) % ToneMap::DEFAULTS.len()]
+    }
+}
+
+/// Computes the color of a point on a continuous 0.0..=255.0 scale per channel,
+/// so callers can quantize it to whatever bit depth they need. Counts at or
+/// past the limit never escaped within it, so they count as inside the set.
+pub fn color_map_channels(iterations: u32, max_iterations: u32, mode: ColorMode) -> [f64; 3] {
+    if iterations >= max_iterations {
+        // Black for points inside the set
+        return [0.0, 0.0, 0.0];
+    }
+
+    color_map_value(iterations as f64, max_iterations, mode)
+}
+
+/// Colors an escaped point from a possibly fractional iteration value, on the
+/// same scale as [`color_map_channels`]. Whole values give exactly the colors
+/// of the matching iteration counts. Values outside `0.0..=max_iterations`
+/// are clamped into it, so no mode scales or wraps past its gradient.
+pub fn color_map_value(iterations: f64, max_iterations: u32, mode: ColorMode) -> [f64; 3] {
+    let iterations = iterations.clamp(0.0, max_iterations as f64);
+    let normalized_iter = iterations / max_iterations as f64;
+
+    match mode {
+        // Original coloring
+        ColorMode::Smooth => smooth_color(iterations, max_iterations, false),
         ColorMode::Zebra => {
             // Zebra stripes
             let stripe_width = max_iterations as f64 / 10.0;
-            let stripe_index = (iterations as f64 / stripe_width).floor() as u32;
+            let stripe_index = (iterations / stripe_width).floor() as u32;
 
-            if stripe_index % 2 == 0 {
-                [255, 255, 255]
+            if stripe_index.is_multiple_of(2) {
+                [255.0, 255.0, 255.0]
             } else {
-                [0, 0, 0]
+                [0.0, 0.0, 0.0]
             }
         }
-        ColorMode::Red => {
-            // Red gradient
-            let red = (normalized_iter * 255.0) as u8;
-            [red, 0, 0]
-        }
-        ColorMode::Blue => {
-            // Blue gradient
-            let blue = (normalized_iter * 255.0) as u8;
-            [0, 0, blue]
+        ColorMode::Red => color_map_value(
+            iterations,
+            max_iterations,
+            ColorMode::SingleHue([255, 0, 0]),
+        ),
+        ColorMode::Blue => color_map_value(
+            iterations,
+            max_iterations,
+            ColorMode::SingleHue([0, 0, 255]),
+        ),
+        ColorMode::SingleHue(color) => {
+            // From black up to the base color
+            color.map(|channel| normalized_iter * channel as f64)
         }
         ColorMode::BlackAndWhite => {
             // Grayscale gradient
-            let intensity = (normalized_iter * 255.0) as u8;
+            let intensity = normalized_iter * 255.0;
             [intensity, intensity, intensity]
         }
-        ColorMode::Rainbow => {
-            // Rainbow gradient
-            let hue = normalized_iter * 6.0;
-            let r = if hue < 1.0 {
-                (hue * 255.0).floor() as u8
-            } else if hue < 2.0 {
-                (255.0 - ((hue - 1.0) * 255.0)).floor() as u8
-            } else if hue < 3.0 {
-                0
-            } else if hue < 4.0 {
-                ((hue - 3.0) * 255.0).floor() as u8
-            } else if hue < 5.0 {
-                (255.0 - ((hue - 4.0) * 255.0)).floor() as u8
-            } else {
-                0
-            };
-            let g = if hue < 1.0 {
-                (255.0 - (hue * 255.0)).floor() as u8
-            } else if hue < 2.0 {
-                255
-            } else if hue < 3.0 {
-                (255.0 - ((hue - 2.0) * 255.0)).floor() as u8
-            } else if hue < 4.0 {
-                0
-            } else if hue < 5.0 {
-                ((hue - 4.0) * 255.0).floor() as u8
-            } else {
-                255
-            };
-            let b = if hue < 1.0 {
-                0
-            } else if hue < 2.0 {
-                ((hue - 1.0) * 255.0).floor() as u8
-            } else if hue < 3.0 {
-                255
-            } else if hue < 4.0 {
-                (255.0 - ((hue - 3.0) * 255.0)).floor() as u8
-            } else if hue < 5.0 {
-                0
-            } else {
-                ((hue - 5.0) * 255.0).floor() as u8
-            };
-
-            [r, g, b]
-        }
+        ColorMode::Rainbow => hsv_to_rgb(normalized_iter * 360.0, 1.0, 1.0),
         ColorMode::Psychedelic => {
             // Psychedelic gradient
             // TODO rename this color
-            let r = ((normalized_iter * 255.0 * 3.0) as f64 % 256.0).floor() as u8;
-            let g = ((normalized_iter * 255.0 * 5.0) as f64 % 256.0).floor() as u8;
-            let b = ((normalized_iter * 255.0 * 7.0) as f64 % 256.0).floor() as u8;
+            let r = normalized_iter * 255.0 * 3.0 % 256.0;
+            let g = normalized_iter * 255.0 * 5.0 % 256.0;
+            let b = normalized_iter * 255.0 * 7.0 % 256.0;
 
-            [r, g, b]
-        }
-        ColorMode::GreenGradient => {
-            // Green gradient
-            let green = (normalized_iter * 255.0) as u8;
-            [0, green, 0]
+            // Wrapping at 256 reaches just past 255, which 8 bits truncate anyway
+            [r, g, b].map(|channel| channel.min(255.0))
         }
+        ColorMode::GreenGradient => color_map_value(
+            iterations,
+            max_iterations,
+            ColorMode::SingleHue([0, 255, 0]),
+        ),
         ColorMode::Electric => {
             // Electric gradient
             // TODO also rename this color
-            let r = ((normalized_iter * 255.0 * 2.0) as f64 % 256.0).floor() as u8;
-            let g = ((normalized_iter * 255.0 * 3.0) as f64 % 256.0).floor() as u8;
-            let b = ((normalized_iter * 255.0 * 5.0) as f64 % 256.0).floor() as u8;
+            let r = normalized_iter * 255.0 * 2.0 % 256.0;
+            let g = normalized_iter * 255.0 * 3.0 % 256.0;
+            let b = normalized_iter * 255.0 * 5.0 % 256.0;
+
+            [r, g, b].map(|channel| channel.min(255.0))
+        }
+        ColorMode::EscapeVelocity(exponent) => {
+            // Fast escapes have a velocity near 1, the boundary near 0
+            let velocity = (max_iterations as f64 - iterations).max(0.0) / max_iterations as f64;
+            let brightness = 1.0 - velocity.powf(exponent);
 
-            [r, g, b]
+            [
+                brightness * 255.0,
+                brightness.powi(2) * 255.0,
+                brightness.powi(4) * 255.0,
+            ]
         }
+        ColorMode::Histogram => {
+            // Red, then yellow, then white
+            let heat = normalized_iter * 3.0;
+            [
+                heat.clamp(0.0, 1.0) * 255.0,
+                (heat - 1.0).clamp(0.0, 1.0) * 255.0,
+                (heat - 2.0).clamp(0.0, 1.0) * 255.0,
+            ]
+        }
+        ColorMode::Gradient => gradient_color(&DEFAULT_GRADIENT, normalized_iter),
+        ColorMode::Potential { contours } => {
+            // -log2 of the potential, one level per step towards the set
+            let level = (iterations - 1.0).max(0.0);
+            let glow = (1.0 + level).ln() / (1.0 + max_iterations as f64).ln();
+            let shade = if contours {
+                ((level - level.round()).abs() / POTENTIAL_CONTOUR_WIDTH).min(1.0)
+            } else {
+                1.0
+            };
+
+            [glow.sqrt(), glow, glow.powi(3)].map(|channel| channel * shade * 255.0)
+        }
+        ColorMode::Angle => angle_color_channels(0.0, iterations, max_iterations),
+        ColorMode::StripeAverage(_) => stripe_color_channels(0.5, iterations, max_iterations),
     }
 }
 
+/// Colors an escaped point by its stripe average, as in
+/// [`EscapeResult::stripe`], on the same 0.0..=255.0 scale as
+/// [`color_map_channels`]. The average runs along [`DEFAULT_GRADIENT`], and
+/// the escape value `value` darkens points far from the set a little.
+pub fn stripe_color_channels(stripe: f64, value: f64, max_iterations: u32) -> [f64; 3] {
+    let glow = (1.0 + value.max(0.0)).ln() / (1.0 + max_iterations as f64).ln();
+    let shade = 0.6 + 0.4 * glow.min(1.0);
+    gradient_color(&DEFAULT_GRADIENT, stripe.clamp(0.0, 1.0)).map(|channel| channel * shade)
+}
+
+/// Colors an escaped point by the angle of its final `z`, in turns as from
+/// [`EscapeResult::angle`], on the same 0.0..=255.0 scale as
+/// [`color_map_channels`]. The angle picks the hue, which splits each escape
+/// band into the pinwheels around the set, and the escape value `value` the
+/// brightness.
+pub fn angle_color_channels(turns: f64, value: f64, max_iterations: u32) -> [f64; 3] {
+    let glow = (1.0 + value.max(0.0)).ln() / (1.0 + max_iterations as f64).ln();
+    hsv_to_rgb(turns * 360.0, 0.8, 0.25 + 0.75 * glow.min(1.0))
+}
+
+/// Converts a hue in degrees, wrapping around at 360, and a saturation and
+/// value in `0.0..=1.0` to RGB on the 0.0..=255.0 scale of
+/// [`color_map_channels`].
+pub fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> [f64; 3] {
+    let hue = hue - 360.0 * (hue / 360.0).floor();
+    let sector = hue / 60.0;
+    let chroma = value * saturation;
+    // The middle component rises and falls between the primaries
+    let middle = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, middle, 0.0],
+        1 => [middle, chroma, 0.0],
+        2 => [0.0, chroma, middle],
+        3 => [0.0, middle, chroma],
+        4 => [middle, 0.0, chroma],
+        _ => [chroma, 0.0, middle],
+    };
+    let lightest = value - chroma;
+    [r, g, b].map(|channel| (channel + lightest) * 255.0)
+}
+
+pub fn color_map(iterations: u32, max_iterations: u32, mode: ColorMode) -> [u8; 3] {
+    color_map_channels(iterations, max_iterations, mode).map(|channel| channel as u8)
+}
+
+/// Same as [`color_map`] but keeps 16 bits of precision per channel.
+pub fn color_map16(iterations: u32, max_iterations: u32, mode: ColorMode) -> [u16; 3] {
+    color_map_channels(iterations, max_iterations, mode).map(|channel| (channel * 257.0) as u16)
+}
 
 #[cfg(test)]
 mod tests {
@@ -154,12 +1442,621 @@ mod tests {
     fn test_basic_operations() {
         let real = 0.0;
         let imag = 0.0;
-        let result = mandelbrot(real, imag, 100);
-        assert_eq!(result, 100);
+        let result = mandelbrot(real, imag, 100, DEFAULT_BAILOUT);
+        assert_eq!(result.iterations, 100);
+        assert!(!result.escaped);
 
         let real = 1.0;
         let imag = 1.0;
-        let result = mandelbrot(real, imag, 100);
-        assert!(result < 100);
+        let result = mandelbrot(real, imag, 100, DEFAULT_BAILOUT);
+        assert!(result.iterations < 100);
+        assert!(result.escaped);
+        assert!(result.final_z.norm_sqr() > 4.0);
+
+        // A larger bailout keeps iterating until |z| passes it instead
+        let wide = mandelbrot(real, imag, 100, 100.0);
+        assert!(wide.escaped);
+        assert!(wide.iterations > result.iterations);
+        assert!(wide.final_z.norm_sqr() > 100.0 * 100.0);
+    }
+
+    #[test]
+    fn test_orbit_matches_iteration_count() {
+        for (real, imag) in [
+            (0.0, 0.0),
+            (-1.0, 0.0),
+            (0.3, 0.5),
+            (1.0, 1.0),
+            (-0.75, 0.1),
+        ] {
+            let result = mandelbrot(real, imag, 500, DEFAULT_BAILOUT);
+            assert_eq!(
+                orbit(real, imag, 500, DEFAULT_BAILOUT).count() as u32,
+                result.iterations
+            );
+
+            if result.escaped {
+                let last = orbit(real, imag, 500, DEFAULT_BAILOUT).last().unwrap();
+                assert!(last.norm_sqr() > 4.0);
+                assert_eq!(last, result.final_z);
+            }
+        }
+
+        // Period 2: 0 -> -1 -> 0 -> -1 ...
+        let values: Vec<_> = orbit(-1.0, 0.0, 4, DEFAULT_BAILOUT).map(|z| z.re).collect();
+        assert_eq!(values, [-1.0, 0.0, -1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_smooth_iterations_are_continuous() {
+        // Walk towards the set along the real axis, crossing several bands
+        let mut previous: Option<(u32, f64)> = None;
+        let mut bands = 0;
+        for step in 0..=2000 {
+            let real = 1.0 - step as f64 * 0.0003;
+            let result = mandelbrot(real, 0.0, 100, DEFAULT_BAILOUT);
+            let iterations = result.iterations;
+            let smooth = result.smooth_iterations();
+
+            if let Some((previous_iterations, previous_smooth)) = previous {
+                if previous_iterations != iterations {
+                    bands += 1;
+                }
+                // Whole bands differ by 1, what is left with a bailout of 2 is far smaller
+                assert!((smooth - previous_smooth).abs() < 0.2, "jump at {real}");
+            }
+            previous = Some((iterations, smooth));
+        }
+        assert!(bands >= 3);
+    }
+
+    #[test]
+    fn test_julia_kind() {
+        // With c = 0 the Julia set is the unit circle
+        let kind = FractalKind::Julia {
+            c: Complex64::new(0.0, 0.0),
+        };
+        assert_eq!(kind.escape(0.5, 0.5, 100, DEFAULT_BAILOUT).iterations, 100);
+        assert!(kind.escape(1.1, 0.0, 100, DEFAULT_BAILOUT).escaped);
+        assert_eq!(
+            kind.orbit(1.1, 0.0, 100, DEFAULT_BAILOUT).count() as u32,
+            kind.escape(1.1, 0.0, 100, DEFAULT_BAILOUT).iterations
+        );
+
+        // A Julia set's starting point at z = 0 follows the Mandelbrot orbit of c
+        let c = Complex64::new(-0.75, 0.1);
+        assert_eq!(
+            julia(0.0, 0.0, c, 300, DEFAULT_BAILOUT),
+            mandelbrot(c.re, c.im, 300, DEFAULT_BAILOUT)
+        );
+    }
+
+    #[test]
+    fn test_newton_finds_each_root() {
+        for (index, &root) in NEWTON_ROOTS.iter().enumerate() {
+            assert_eq!(newton(root, 50), (Some(index), 0));
+            // Starting a little way off still lands on the nearest root
+            let (found, iterations) = newton(root * 1.2, 50);
+            assert_eq!(found, Some(index));
+            assert!(iterations > 0);
+        }
+
+        // The basins mirror each other in the real axis
+        let z = Complex64::new(-0.3, 0.7);
+        let (above, _) = newton(z, 50);
+        let (below, _) = newton(z.conj(), 50);
+        assert_eq!([above, below], [Some(1), Some(2)]);
+
+        // Zero has no tangent to follow
+        assert_eq!(newton(Complex64::new(0.0, 0.0), 50), (None, 50));
+
+        let escape = FractalKind::Newton.escape(-0.3, 0.7, 50, DEFAULT_BAILOUT);
+        assert!(escape.escaped);
+        assert_eq!(escape.final_z, NEWTON_ROOTS[1]);
+    }
+
+    #[test]
+    fn test_fractal_trait_matches_kinds() {
+        let c = Complex64::new(-0.8, 0.156);
+        let kinds: [&dyn Fractal; 3] = [
+            &FractalKind::Mandelbrot,
+            &FractalKind::Julia { c },
+            &FractalKind::BurningShip,
+        ];
+        for (kind, expected) in kinds.into_iter().zip([
+            mandelbrot(-0.75, 0.1, 100, 10.0),
+            julia(-0.75, 0.1, c, 100, 10.0),
+            burning_ship(-0.75, 0.1, 100, 10.0),
+        ]) {
+            assert_eq!(
+                kind.iterate(Complex64::new(-0.75, 0.1), 100, 10.0),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_burning_ship_kind() {
+        // Matches the Mandelbrot set where no component goes negative
+        assert_eq!(
+            burning_ship(0.2, 0.1, 100, DEFAULT_BAILOUT),
+            mandelbrot(0.2, 0.1, 100, DEFAULT_BAILOUT)
+        );
+        // Mirroring in the real axis changes it, unlike for Mandelbrot
+        assert_ne!(
+            burning_ship(-1.75, 0.03, 200, DEFAULT_BAILOUT),
+            burning_ship(-1.75, -0.03, 200, DEFAULT_BAILOUT)
+        );
+
+        let kind = FractalKind::BurningShip;
+        for (real, imag) in [(-1.75, -0.03), (0.3, 0.5), (-0.5, -0.5)] {
+            let escape = kind.escape(real, imag, 200, DEFAULT_BAILOUT);
+            assert_eq!(
+                kind.orbit(real, imag, 200, DEFAULT_BAILOUT).count() as u32,
+                escape.iterations
+            );
+            assert_eq!(
+                kind.trap(real, imag, 200, DEFAULT_BAILOUT, &TrapShape::DEFAULTS[0])
+                    .0,
+                escape
+            );
+        }
+    }
+
+    #[test]
+    fn test_tricorn_kind() {
+        // Real orbits never see the conjugation, so the real axis matches Mandelbrot
+        for real in [-1.9, -1.3, -0.75, 0.2, 0.3] {
+            assert_eq!(
+                tricorn(real, 0.0, 200, DEFAULT_BAILOUT),
+                mandelbrot(real, 0.0, 200, DEFAULT_BAILOUT)
+            );
+        }
+        // Unlike the Mandelbrot set, it reaches out to the upper right
+        assert!(!tricorn(0.5, 0.8, 200, DEFAULT_BAILOUT).escaped);
+        assert!(mandelbrot(0.5, 0.8, 200, DEFAULT_BAILOUT).escaped);
+        // Symmetric in the real axis
+        assert_eq!(
+            tricorn(-0.2, 0.9, 200, DEFAULT_BAILOUT).iterations,
+            tricorn(-0.2, -0.9, 200, DEFAULT_BAILOUT).iterations
+        );
+
+        let kind = FractalKind::Tricorn;
+        assert_eq!(FractalKind::BurningShip.next(), kind);
+        for (real, imag) in [(-0.56, 0.0), (0.5, 0.8), (-1.2, 1.0)] {
+            let escape = kind.escape(real, imag, 200, DEFAULT_BAILOUT);
+            assert_eq!(escape, tricorn(real, imag, 200, DEFAULT_BAILOUT));
+            assert_eq!(
+                kind.orbit(real, imag, 200, DEFAULT_BAILOUT).count() as u32,
+                escape.iterations
+            );
+            assert_eq!(
+                kind.trap(real, imag, 200, DEFAULT_BAILOUT, &TrapShape::DEFAULTS[0])
+                    .0,
+                escape
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_map16_precision() {
+        assert_eq!(color_map16(100, 100, ColorMode::Red), [0, 0, 0]);
+
+        // Neighbouring iteration counts share an 8-bit value but not a 16-bit one
+        let max_iterations = 1000;
+        let low = color_map16(500, max_iterations, ColorMode::Red);
+        let high = color_map16(501, max_iterations, ColorMode::Red);
+        assert_eq!(
+            color_map(500, max_iterations, ColorMode::Red),
+            color_map(501, max_iterations, ColorMode::Red)
+        );
+        assert!(high[0] > low[0]);
+    }
+
+    #[test]
+    fn test_trap_distances() {
+        let origin = Complex64::new(0.0, 0.0);
+        let z = Complex64::new(3.0, 4.0);
+
+        assert_eq!(TrapShape::Point { center: origin }.distance(z), 5.0);
+        assert_eq!(TrapShape::Cross { center: origin }.distance(z), 3.0);
+        assert_eq!(TrapShape::Line { angle: 0.0 }.distance(z), 4.0);
+        let circle = TrapShape::Circle {
+            center: origin,
+            radius: 2.0,
+        };
+        assert_eq!(circle.distance(z), 3.0);
+
+        // Row 4 of the bitmap has texel 5 lit and texel 4 clear
+        let texture = TrapShape::Texture {
+            center: origin,
+            size: 1.0,
+        };
+        assert_eq!(texture.distance(Complex64::new(0.2, 0.05)), 0.0);
+        assert_eq!(texture.distance(Complex64::new(0.05, 0.05)), 0.125);
+        assert!(texture.distance(z) > 1.0);
+    }
+
+    #[test]
+    fn test_trap_iteration_matches_mandelbrot() {
+        let trap = TrapShape::DEFAULTS[0];
+        for (real, imag) in [(0.0, 0.0), (1.0, 1.0), (-0.75, 0.1), (0.3, -0.5)] {
+            let (escape, _) = mandelbrot_trap(real, imag, 100, DEFAULT_BAILOUT, &trap);
+            assert_eq!(escape, mandelbrot(real, imag, 100, DEFAULT_BAILOUT));
+        }
+    }
+
+    #[test]
+    fn test_distance_estimate() {
+        for (real, imag) in [(0.0, 0.0), (1.0, 1.0), (-0.75, 0.1), (0.3, -0.5)] {
+            let (escape, _) = mandelbrot_distance(real, imag, 100, DEFAULT_BAILOUT);
+            assert_eq!(escape, mandelbrot(real, imag, 100, DEFAULT_BAILOUT));
+        }
+        assert_eq!(mandelbrot_distance(-0.1, 0.2, 100, DEFAULT_BAILOUT).1, 0.0);
+
+        // The cusp at 0.25 is the closest point of the set on the real axis
+        let distances = [0.3, 0.5, 1.0].map(|real| mandelbrot_distance(real, 0.0, 1000, 100.0).1);
+        assert!(distances[0] < distances[1] && distances[1] < distances[2]);
+        let near = distances[1];
+        assert!((0.25 / 4.0..0.25 * 4.0).contains(&near), "{near}");
+
+        let julia = FractalKind::DEFAULT_JULIA.distance(1.5, 0.0, 100, 100.0);
+        assert!(julia.0.escaped && julia.1 > 0.0);
+    }
+
+    #[test]
+    fn test_periodic_orbits_stop_early() {
+        let interior = mandelbrot(0.0, 0.0, 100_000, DEFAULT_BAILOUT);
+        assert!(!interior.escaped);
+        assert_eq!(interior.iterations, 100_000);
+
+        // Orbit traps run every step, so they give the unchecked counts
+        let trap = TrapShape::DEFAULTS[0];
+        for step in 0..400 {
+            let real = -2.0 + (step % 20) as f64 * 0.13;
+            let imag = (step / 20) as f64 * 0.061;
+            let (full, _) = mandelbrot_trap(real, imag, 2000, DEFAULT_BAILOUT, &trap);
+            let checked = mandelbrot(real, imag, 2000, DEFAULT_BAILOUT);
+            assert_eq!(checked.iterations, full.iterations, "{real} {imag}");
+            assert_eq!(checked.escaped, full.escaped, "{real} {imag}");
+        }
+    }
+
+    #[test]
+    fn test_known_interior_regions() {
+        assert!(in_main_cardioid(0.0, 0.0));
+        assert!(in_main_cardioid(-0.1, 0.2));
+        assert!(in_period2_bulb(-1.0, 0.0));
+        assert!(in_period2_bulb(-1.1, 0.1));
+
+        // The cusp and the point where the two regions touch are on the boundary
+        assert!(!in_main_cardioid(0.25, 0.0));
+        assert!(!in_main_cardioid(-0.75, 0.0));
+        assert!(!in_period2_bulb(-0.75, 0.0));
+        assert!(!in_period2_bulb(-1.25, 0.0));
+
+        assert!(!in_main_cardioid(1.0, 1.0));
+        assert!(!in_period2_bulb(-1.0, 0.5));
+        assert!(!in_main_cardioid(-1.0, 0.0));
+    }
+
+    #[test]
+    fn test_tonemap_endpoints() {
+        for tonemap in ToneMap::DEFAULTS {
+            assert!(tonemap.apply(0.0).abs() < 1e-12, "{tonemap:?}");
+            assert!((tonemap.apply(1.0) - 1.0).abs() < 1e-12, "{tonemap:?}");
+        }
+
+        // Compressing operators lift the low end
+        assert!(ToneMap::DEFAULTS[1].apply(0.1) > 0.1);
+        assert!(ToneMap::DEFAULTS[2].apply(0.1) > 0.1);
+    }
+
+    #[test]
+    fn test_hsv_to_rgb_hits_primaries_and_secondaries() {
+        let colors = [
+            (0.0, [255.0, 0.0, 0.0]),
+            (60.0, [255.0, 255.0, 0.0]),
+            (120.0, [0.0, 255.0, 0.0]),
+            (180.0, [0.0, 255.0, 255.0]),
+            (240.0, [0.0, 0.0, 255.0]),
+            (300.0, [255.0, 0.0, 255.0]),
+            (360.0, [255.0, 0.0, 0.0]),
+            (-60.0, [255.0, 0.0, 255.0]),
+        ];
+        for (hue, rgb) in colors {
+            assert_eq!(hsv_to_rgb(hue, 1.0, 1.0), rgb, "hue {hue}");
+        }
+
+        assert_eq!(hsv_to_rgb(200.0, 0.0, 0.5), [127.5; 3]);
+        assert_eq!(hsv_to_rgb(30.0, 1.0, 1.0), [255.0, 127.5, 0.0]);
+
+        // No seams where the sectors meet
+        for sector in 1..6 {
+            let edge = sector as f64 * 60.0;
+            let (below, above) = (
+                hsv_to_rgb(edge - 1e-9, 1.0, 1.0),
+                hsv_to_rgb(edge, 1.0, 1.0),
+            );
+            for (a, b) in below.iter().zip(above) {
+                assert!((a - b).abs() < 1e-6, "seam at {edge}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_multibrot_powers() {
+        // Power 2 is the Mandelbrot set, down to the last bit
+        for (real, imag) in [(0.3, 0.5), (-0.75, 0.1), (1.0, 1.0), (-1.5, 0.0)] {
+            assert_eq!(
+                multibrot(real, imag, 2.0, 200, DEFAULT_BAILOUT),
+                mandelbrot(real, imag, 200, DEFAULT_BAILOUT)
+            );
+        }
+        assert_eq!(FractalKind::multibrot(2.0), FractalKind::Mandelbrot);
+
+        // The cubic set is symmetric under a half turn, and negating is exact
+        for (real, imag) in [(0.3, 0.2), (-0.5, 0.6), (0.1, 1.2)] {
+            assert_eq!(
+                multibrot(real, imag, 3.0, 100, DEFAULT_BAILOUT).iterations,
+                multibrot(-real, -imag, 3.0, 100, DEFAULT_BAILOUT).iterations
+            );
+        }
+
+        // Fractional powers go through powf and agree with whole ones
+        let whole = multibrot(0.3, 0.5, 3.0, 50, DEFAULT_BAILOUT);
+        let fractional = multibrot(0.3, 0.5, 3.0 + 1e-12, 50, DEFAULT_BAILOUT);
+        assert_eq!(whole.iterations, fractional.iterations);
+        assert!(multibrot(0.0, 0.0, 2.5, 50, DEFAULT_BAILOUT).iterations == 50);
+    }
+
+    #[test]
+    fn test_gradient_interpolates_between_stops() {
+        let stops = [
+            GradientStop {
+                position: 0.25,
+                color: [0, 100, 200],
+            },
+            GradientStop {
+                position: 0.75,
+                color: [200, 100, 0],
+            },
+        ];
+
+        assert_eq!(gradient_color(&stops, 0.0), [0.0, 100.0, 200.0]);
+        assert_eq!(gradient_color(&stops, 0.25), [0.0, 100.0, 200.0]);
+        assert_eq!(gradient_color(&stops, 0.5), [100.0, 100.0, 100.0]);
+        assert_eq!(gradient_color(&stops, 0.75), [200.0, 100.0, 0.0]);
+        assert_eq!(gradient_color(&stops, 1.0), [200.0, 100.0, 0.0]);
+
+        // Interior points stay black
+        assert_eq!(color_map(100, 100, ColorMode::Gradient), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_oklab_round_trips() {
+        let steps = [0.0, 1.0, 17.0, 64.0, 128.0, 200.0, 254.0, 255.0];
+        for r in steps {
+            for g in steps {
+                for b in steps {
+                    let back = oklab_to_srgb(srgb_to_oklab([r, g, b]));
+                    for (channel, original) in back.iter().zip([r, g, b]) {
+                        assert!((channel - original).abs() < 1e-3, "{back:?} != {r} {g} {b}");
+                    }
+                }
+            }
+        }
+
+        // Grays have no hue, and white is full lightness
+        let [lightness, a, b] = srgb_to_oklab([255.0; 3]);
+        assert!((lightness - 1.0).abs() < 1e-6);
+        assert!(a.abs() < 1e-6 && b.abs() < 1e-6);
+        assert_eq!(srgb_to_oklab([0.0; 3]), [0.0; 3]);
+    }
+
+    #[test]
+    fn test_oklab_gradients_keep_midtones_bright() {
+        let stops = [
+            GradientStop {
+                position: 0.0,
+                color: [255, 0, 0],
+            },
+            GradientStop {
+                position: 1.0,
+                color: [0, 0, 255],
+            },
+        ];
+        // The stops themselves are unchanged
+        for t in [0.0, 1.0] {
+            let perceptual = gradient_color_oklab(&stops, t);
+            for (a, b) in perceptual.iter().zip(gradient_color(&stops, t)) {
+                assert!((a - b).abs() < 1e-3);
+            }
+        }
+
+        // Halfway from red to blue, the channel mix is darker than both ends
+        let lightness = |color: [f64; 3]| srgb_to_oklab(color)[0];
+        let ends = lightness([255.0, 0.0, 0.0]).min(lightness([0.0, 0.0, 255.0]));
+        assert!(lightness(gradient_color(&stops, 0.5)) < ends);
+        assert!(lightness(gradient_color_oklab(&stops, 0.5)) >= ends);
+
+        assert_eq!(
+            smooth_color(10.0, 100, false),
+            color_map_value(10.0, 100, ColorMode::Smooth)
+        );
+        assert_ne!(
+            smooth_color(10.0, 100, true),
+            smooth_color(10.0, 100, false)
+        );
+    }
+
+    #[test]
+    fn test_color_map_stays_in_range_for_every_mode() {
+        let modes = [
+            ColorMode::Smooth,
+            ColorMode::Zebra,
+            ColorMode::Red,
+            ColorMode::Blue,
+            ColorMode::BlackAndWhite,
+            ColorMode::Rainbow,
+            ColorMode::Psychedelic,
+            ColorMode::GreenGradient,
+            ColorMode::Electric,
+            ColorMode::EscapeVelocity(0.5),
+            ColorMode::EscapeVelocity(4.0),
+            ColorMode::Histogram,
+            ColorMode::Gradient,
+            ColorMode::Potential { contours: false },
+            ColorMode::Potential { contours: true },
+            ColorMode::Angle,
+            ColorMode::StripeAverage(DEFAULT_STRIPE_FREQUENCY),
+            ColorMode::SingleHue([255, 255, 255]),
+            ColorMode::SingleHue([18, 200, 7]),
+        ];
+        for mode in modes {
+            for max_iterations in [1, 2, 3, 10, 100, 1000, 4096] {
+                // Counts past the limit, as from a lowered limit, are interior too
+                for iterations in 0..=max_iterations + 20 {
+                    let channels = color_map_channels(iterations, max_iterations, mode);
+                    assert!(
+                        channels
+                            .iter()
+                            .all(|channel| (0.0..=255.0).contains(channel)),
+                        "{mode:?} at {iterations}/{max_iterations}: {channels:?}"
+                    );
+                    if iterations >= max_iterations {
+                        assert_eq!(
+                            channels, [0.0; 3],
+                            "{mode:?} at {iterations}/{max_iterations}"
+                        );
+                        assert_eq!(color_map(iterations, max_iterations, mode), [0; 3]);
+                        assert_eq!(color_map16(iterations, max_iterations, mode), [0; 3]);
+                    }
+                }
+
+                // Fractional values are clamped to the gradient's ends
+                let max = max_iterations as f64;
+                for value in [
+                    -5.0,
+                    -0.5,
+                    0.0,
+                    0.25,
+                    0.5,
+                    max / 3.0,
+                    max - 0.5,
+                    max + 0.5,
+                    1e9,
+                ] {
+                    let channels = color_map_value(value, max_iterations, mode);
+                    assert!(
+                        channels
+                            .iter()
+                            .all(|channel| (0.0..=255.0).contains(channel)),
+                        "{mode:?} at {value}/{max_iterations}: {channels:?}"
+                    );
+                }
+                assert_eq!(
+                    color_map_value(max + 7.5, max_iterations, mode),
+                    color_map_value(max, max_iterations, mode),
+                    "{mode:?}"
+                );
+                assert_eq!(
+                    color_map_value(-3.0, max_iterations, mode),
+                    color_map_value(0.0, max_iterations, mode),
+                    "{mode:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_hue_generalizes_primaries() {
+        for (mode, primary) in [
+            (ColorMode::Red, [255, 0, 0]),
+            (ColorMode::Blue, [0, 0, 255]),
+            (ColorMode::GreenGradient, [0, 255, 0]),
+        ] {
+            for iterations in 0..=100 {
+                assert_eq!(
+                    color_map(iterations, 100, mode),
+                    color_map(iterations, 100, ColorMode::SingleHue(primary))
+                );
+            }
+        }
+
+        // Any tint fades from black towards the base color
+        let teal = ColorMode::SingleHue([0, 128, 128]);
+        assert_eq!(color_map_value(0.0, 100, teal), [0.0; 3]);
+        assert_eq!(color_map_value(50.0, 100, teal), [0.0, 64.0, 64.0]);
+        assert_eq!(color_map(100, 100, teal), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_angle_follows_the_final_z() {
+        let escape = |re: f64, im: f64| EscapeResult {
+            iterations: 3,
+            escaped: true,
+            final_z: Complex64::new(re, im),
+            stripe: 0.0,
+        };
+        assert_eq!(escape(5.0, 0.0).angle(), 0.0);
+        assert!((escape(0.0, 5.0).angle() - 0.25).abs() < 1e-12);
+        assert!((escape(-5.0, 0.0).angle() - 0.5).abs() < 1e-12);
+        assert!((escape(0.0, -5.0).angle() - 0.75).abs() < 1e-12);
+        assert!(escape(5.0, -1e-300).angle() < 1.0);
+
+        // Opposite angles get different hues at the same escape value
+        let right = angle_color_channels(0.0, 10.0, 100);
+        let left = angle_color_channels(0.5, 10.0, 100);
+        assert_ne!(right, left);
+        assert_eq!(right, color_map_value(10.0, 100, ColorMode::Angle));
+        for channel in right.into_iter().chain(left) {
+            assert!((0.0..=255.0).contains(&channel));
+        }
+    }
+
+    #[test]
+    fn test_stripes_follow_the_escape() {
+        for (real, imag) in [(0.3, 0.5), (-0.75, 0.1), (-1.2, 0.4), (0.0, 0.0)] {
+            let plain = mandelbrot(real, imag, 500, 100.0);
+            let striped = mandelbrot_stripe(real, imag, 500, 100.0, DEFAULT_STRIPE_FREQUENCY);
+            assert_eq!(striped.iterations, plain.iterations);
+            assert_eq!(striped.escaped, plain.escaped);
+            assert!((0.0..=1.0).contains(&striped.stripe), "{striped:?}");
+        }
+
+        // Another frequency wraps the stripes differently
+        let low = mandelbrot_stripe(0.3, 0.5, 500, 100.0, 1.0).stripe;
+        let high = mandelbrot_stripe(0.3, 0.5, 500, 100.0, 9.0).stripe;
+        assert_ne!(low, high);
+
+        let channels = stripe_color_channels(0.25, 10.0, 100);
+        assert_ne!(channels, stripe_color_channels(0.75, 10.0, 100));
+        assert!(channels
+            .iter()
+            .all(|channel| (0.0..=255.0).contains(channel)));
+    }
+
+    #[test]
+    fn test_potential_glows_towards_the_set() {
+        let escapes = [2.0, 0.5, 0.3].map(|real| mandelbrot(real, 0.0, 1000, 100.0));
+        let potentials = escapes.map(|escape| escape.potential());
+        assert!(potentials[0] > potentials[1] && potentials[1] > potentials[2]);
+        for escape in escapes {
+            let level = -escape.potential().log2();
+            assert!((escape.smooth_iterations() - 1.0 - level).abs() < 1e-9);
+        }
+
+        let plain = ColorMode::Potential { contours: false };
+        let glows = escapes.map(|escape| color_map_value(escape.smooth_iterations(), 1000, plain));
+        assert!(glows[0][1] < glows[1][1] && glows[1][1] < glows[2][1]);
+
+        // Lines sit on the whole levels, halfway between them is left alone
+        let lines = ColorMode::Potential { contours: true };
+        assert_eq!(color_map_value(6.0, 1000, lines), [0.0; 3]);
+        assert_eq!(
+            color_map_value(6.5, 1000, lines),
+            color_map_value(6.5, 1000, plain)
+        );
     }
 }
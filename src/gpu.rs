@@ -0,0 +1,191 @@
+//! Mandelbrot rendering in a compute shader, for `--gpu`.
+use crate::viewport::Viewport;
+use pixels::wgpu;
+use pixels::{Error, Pixels};
+
+/// Workgroup size of the shader in each direction.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Renders the Mandelbrot set on the GPU that [`Pixels`] draws with,
+/// straight into its texture instead of through the frame buffer.
+///
+/// The shader only covers the smooth color scheme and iterates in `f32`, so
+/// it is limited to views where [`Self::resolves`] holds. The
+/// [`Renderer`](crate::renderer::Renderer) draws everything else.
+pub struct GpuRenderer {
+    pipeline: wgpu::ComputePipeline,
+    params: wgpu::Buffer,
+    /// Output pixels, rows padded to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`]
+    output: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// The texture size of the [`Pixels`] it was made for
+    extent: wgpu::Extent3d,
+}
+
+impl GpuRenderer {
+    /// Sets up the shader on the GPU of `pixels`. Returns `None` if the GPU
+    /// can't run compute shaders.
+    pub fn new(pixels: &Pixels) -> Option<Self> {
+        let capabilities = pixels.adapter().get_downlevel_capabilities();
+        if !capabilities
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return None;
+        }
+
+        let device = pixels.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("frustal_mandelbrot_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/mandelbrot.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("frustal_mandelbrot_pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: "main",
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("frustal_mandelbrot_params"),
+            size: 32,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let extent = pixels.context().texture_extent;
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("frustal_mandelbrot_output"),
+            size: (padded_row_bytes(extent.width) * extent.height) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("frustal_mandelbrot_bind_group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: output.as_entire_binding(),
+                },
+            ],
+        });
+
+        Some(Self {
+            pipeline,
+            params,
+            output,
+            bind_group,
+            extent,
+        })
+    }
+
+    /// Whether it can draw into `pixels`, which has to keep the buffer size
+    /// it had when this was set up.
+    pub fn fits(&self, pixels: &Pixels) -> bool {
+        pixels.context().texture_extent == self.extent
+    }
+
+    /// Whether `f32` can tell the pixels of `view` apart. Past that, deeper
+    /// views turn blocky on the GPU and need the CPU's `f64`.
+    pub fn resolves(view: &Viewport) -> bool {
+        let magnitude = view.center_x.abs().max(view.center_y.abs()).max(1.0);
+        view.pixel_size() > 4.0 * f32::EPSILON as f64 * magnitude
+    }
+
+    /// Renders `view` into the texture of `pixels` and presents it.
+    ///
+    /// # Panics
+    ///
+    /// If `view` isn't the size of `pixels`'s buffer, see [`Self::fits`].
+    pub fn render(
+        &self,
+        pixels: &Pixels,
+        view: &Viewport,
+        max_iterations: u32,
+        bailout: f64,
+    ) -> Result<(), Error> {
+        let extent = self.extent;
+        assert!(
+            view.width == extent.width && view.height == extent.height,
+            "View doesn't match the GPU buffer size"
+        );
+
+        let row_bytes = padded_row_bytes(extent.width);
+        let params: [u32; 8] = [
+            (view.center_x as f32).to_bits(),
+            (view.center_y as f32).to_bits(),
+            (view.pixel_size() as f32).to_bits(),
+            max_iterations,
+            extent.width,
+            extent.height,
+            row_bytes / 4,
+            (bailout as f32).to_bits(),
+        ];
+        pixels
+            .queue()
+            .write_buffer(&self.params, 0, bytemuck::cast_slice(&params));
+
+        pixels.render_with(|encoder, render_target, context| {
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("frustal_mandelbrot_pass"),
+                });
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &self.bind_group, &[]);
+                pass.dispatch_workgroups(
+                    extent.width.div_ceil(WORKGROUP_SIZE),
+                    extent.height.div_ceil(WORKGROUP_SIZE),
+                    1,
+                );
+            }
+
+            // Replaces the frame buffer contents Pixels has just uploaded
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: &self.output,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(row_bytes),
+                        rows_per_image: Some(extent.height),
+                    },
+                },
+                context.texture.as_image_copy(),
+                extent,
+            );
+            context.scaling_renderer.render(encoder, render_target);
+            Ok(())
+        })
+    }
+}
+
+/// Bytes per output row, padded as texture copies require.
+fn padded_row_bytes(width: u32) -> u32 {
+    (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_complex::Complex64;
+
+    #[test]
+    fn test_resolves_until_f32_runs_out() {
+        let view = Viewport::new(Complex64::new(-0.75, 0.1), 3.0, 800, 600);
+        assert!(GpuRenderer::resolves(&view));
+        assert!(GpuRenderer::resolves(&Viewport {
+            scale: 1e-3,
+            ..view
+        }));
+        assert!(!GpuRenderer::resolves(&Viewport {
+            scale: 1e-6,
+            ..view
+        }));
+
+        assert_eq!(padded_row_bytes(64), 256);
+        assert_eq!(padded_row_bytes(65), 512);
+    }
+}
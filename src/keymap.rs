@@ -0,0 +1,325 @@
+//! The keys the window responds to, see [`KeyMap`].
+use crate::args::ColorScheme;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use winit::event::VirtualKeyCode;
+use winit_input_helper::WinitInputHelper;
+
+/// Something a key does in the window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    /// Held while clicking to drag the view without a click zooming in
+    Grab,
+    ZoomIn,
+    ZoomOut,
+    /// Held with shift it picks the right half of the split view instead
+    SelectScheme(ColorScheme),
+    ToggleJulia,
+    NextFractal,
+    RaisePower,
+    LowerPower,
+    FewerIterations,
+    MoreIterations,
+    Compare,
+    OrbitTrap,
+    ShrinkTrap,
+    GrowTrap,
+    SmoothColoring,
+    Jitter,
+    /// Held with shift it toggles adaptive supersampling instead
+    Supersampling,
+    TemporalAa,
+    Glow,
+    GammaCorrection,
+    InvertPalette,
+    DepthCoupling,
+    Tonemap,
+    PotentialContours,
+    LowerExponent,
+    RaiseExponent,
+    FocusDetail,
+    MagnificationPrompt,
+    /// Held with shift it zooms into the current detail instead
+    AutoZoom,
+    /// Held with shift it picks just enough iterations for the view instead
+    AutoIterations,
+    PaletteCycling,
+    ScaleBar,
+    Hud,
+    Loupe,
+    Orbit,
+    /// Held with shift it prints a location token instead
+    SaveView,
+    LoadView,
+    /// Held with shift it restores the starting colors and iterations too
+    Reset,
+    /// Held with shift it exports the raw iteration counts instead
+    ExportPng16,
+    Screenshot,
+    PaletteStrip,
+    SyncViews,
+    Quit,
+}
+
+/// The actions without a color scheme, by the names config files use.
+const ACTION_NAMES: [(&str, Action); 46] = [
+    ("pan-left", Action::PanLeft),
+    ("pan-right", Action::PanRight),
+    ("pan-up", Action::PanUp),
+    ("pan-down", Action::PanDown),
+    ("grab", Action::Grab),
+    ("zoom-in", Action::ZoomIn),
+    ("zoom-out", Action::ZoomOut),
+    ("toggle-julia", Action::ToggleJulia),
+    ("next-fractal", Action::NextFractal),
+    ("raise-power", Action::RaisePower),
+    ("lower-power", Action::LowerPower),
+    ("fewer-iterations", Action::FewerIterations),
+    ("more-iterations", Action::MoreIterations),
+    ("compare", Action::Compare),
+    ("orbit-trap", Action::OrbitTrap),
+    ("shrink-trap", Action::ShrinkTrap),
+    ("grow-trap", Action::GrowTrap),
+    ("smooth-coloring", Action::SmoothColoring),
+    ("jitter", Action::Jitter),
+    ("supersampling", Action::Supersampling),
+    ("temporal-aa", Action::TemporalAa),
+    ("glow", Action::Glow),
+    ("gamma-correction", Action::GammaCorrection),
+    ("invert-palette", Action::InvertPalette),
+    ("depth-coupling", Action::DepthCoupling),
+    ("tonemap", Action::Tonemap),
+    ("potential-contours", Action::PotentialContours),
+    ("lower-exponent", Action::LowerExponent),
+    ("raise-exponent", Action::RaiseExponent),
+    ("focus-detail", Action::FocusDetail),
+    ("magnification-prompt", Action::MagnificationPrompt),
+    ("auto-zoom", Action::AutoZoom),
+    ("auto-iterations", Action::AutoIterations),
+    ("palette-cycling", Action::PaletteCycling),
+    ("scale-bar", Action::ScaleBar),
+    ("hud", Action::Hud),
+    ("loupe", Action::Loupe),
+    ("orbit", Action::Orbit),
+    ("save-view", Action::SaveView),
+    ("load-view", Action::LoadView),
+    ("reset", Action::Reset),
+    ("export-png16", Action::ExportPng16),
+    ("screenshot", Action::Screenshot),
+    ("palette-strip", Action::PaletteStrip),
+    ("sync-views", Action::SyncViews),
+    ("quit", Action::Quit),
+];
+
+impl FromStr for Action {
+    type Err = String;
+
+    /// Parses an action name from [`ACTION_NAMES`], or `scheme-` followed by
+    /// a color scheme name like `scheme-black-and-white`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Some(scheme) = name.strip_prefix("scheme-") {
+            return scheme.parse().map(Action::SelectScheme);
+        }
+        ACTION_NAMES
+            .iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|&(_, action)| action)
+            .ok_or_else(|| format!("unknown action \"{name}\""))
+    }
+}
+
+/// Which key triggers each [`Action`]. The defaults are the bindings listed
+/// in [`KeyMap::default`]; a config file only needs the ones it changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyMap {
+    keys: HashMap<Action, VirtualKeyCode>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        use VirtualKeyCode as Key;
+
+        let mut keys = HashMap::from([
+            (Action::PanLeft, Key::Left),
+            (Action::PanRight, Key::Right),
+            (Action::PanUp, Key::Up),
+            (Action::PanDown, Key::Down),
+            (Action::Grab, Key::Space),
+            (Action::ZoomIn, Key::PageUp),
+            (Action::ZoomOut, Key::PageDown),
+            (Action::ToggleJulia, Key::J),
+            (Action::NextFractal, Key::F),
+            (Action::RaisePower, Key::RBracket),
+            (Action::LowerPower, Key::LBracket),
+            (Action::FewerIterations, Key::Minus),
+            (Action::MoreIterations, Key::Equals),
+            (Action::Compare, Key::C),
+            (Action::OrbitTrap, Key::T),
+            (Action::ShrinkTrap, Key::Semicolon),
+            (Action::GrowTrap, Key::Apostrophe),
+            (Action::SmoothColoring, Key::Q),
+            (Action::Jitter, Key::N),
+            (Action::Supersampling, Key::A),
+            (Action::TemporalAa, Key::Insert),
+            (Action::Glow, Key::F3),
+            (Action::GammaCorrection, Key::Grave),
+            (Action::InvertPalette, Key::I),
+            (Action::DepthCoupling, Key::K),
+            (Action::Tonemap, Key::H),
+            (Action::PotentialContours, Key::Backslash),
+            (Action::LowerExponent, Key::Comma),
+            (Action::RaiseExponent, Key::Period),
+            (Action::FocusDetail, Key::D),
+            (Action::MagnificationPrompt, Key::M),
+            (Action::AutoZoom, Key::Z),
+            (Action::AutoIterations, Key::U),
+            (Action::PaletteCycling, Key::Y),
+            (Action::ScaleBar, Key::B),
+            (Action::Hud, Key::Tab),
+            (Action::Loupe, Key::L),
+            (Action::Orbit, Key::O),
+            (Action::SaveView, Key::W),
+            (Action::LoadView, Key::R),
+            (Action::Reset, Key::Home),
+            (Action::ExportPng16, Key::P),
+            (Action::Screenshot, Key::S),
+            (Action::PaletteStrip, Key::F2),
+            (Action::SyncViews, Key::V),
+            (Action::Quit, Key::Escape),
+        ]);
+        for (key, scheme) in [
+            (Key::Key1, ColorScheme::Smooth),
+            (Key::Key2, ColorScheme::Zebra),
+            (Key::Key3, ColorScheme::Red),
+            (Key::Key4, ColorScheme::Blue),
+            (Key::Key5, ColorScheme::BlackAndWhite),
+            (Key::Key6, ColorScheme::Rainbow),
+            (Key::Key7, ColorScheme::Psychedelic),
+            (Key::Key8, ColorScheme::GreenGradient),
+            (Key::Key9, ColorScheme::Electric),
+            (Key::Key0, ColorScheme::EscapeVelocity),
+            (Key::E, ColorScheme::Histogram),
+            (Key::G, ColorScheme::Gradient),
+            (Key::X, ColorScheme::DistanceEstimate),
+            (Key::Slash, ColorScheme::Potential),
+            (Key::End, ColorScheme::Angle),
+        ] {
+            keys.insert(Action::SelectScheme(scheme), key);
+        }
+        Self { keys }
+    }
+}
+
+impl KeyMap {
+    /// Parses key bindings on top of the defaults, one `action = Key` line
+    /// per binding. Actions are named like `zoom-in` or `scheme-smooth`, see
+    /// [`Action::from_str`], and keys like winit's [`VirtualKeyCode`]s, such
+    /// as `PageUp`, `Key1` or `A`. A key of `none` unbinds the action.
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((action, key)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `action = Key`", index + 1));
+            };
+            let action: Action = action
+                .trim()
+                .parse()
+                .map_err(|err| format!("line {}: {err}", index + 1))?;
+            match key.trim() {
+                "none" => {
+                    keymap.keys.remove(&action);
+                }
+                key => {
+                    let key = parse_key(key)
+                        .ok_or_else(|| format!("line {}: unknown key \"{key}\"", index + 1))?;
+                    keymap.keys.insert(action, key);
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Reads and parses a key binding file, see [`KeyMap::parse`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// The key bound to `action`, if any.
+    pub fn key(&self, action: Action) -> Option<VirtualKeyCode> {
+        self.keys.get(&action).copied()
+    }
+
+    /// Binds `action` to `key`, replacing its previous key.
+    pub fn bind(&mut self, action: Action, key: VirtualKeyCode) {
+        self.keys.insert(action, key);
+    }
+
+    /// Whether the key of `action` went down this step.
+    pub fn pressed(&self, input: &WinitInputHelper, action: Action) -> bool {
+        self.key(action).is_some_and(|key| input.key_pressed(key))
+    }
+
+    /// Whether the key of `action` is down.
+    pub fn held(&self, input: &WinitInputHelper, action: Action) -> bool {
+        self.key(action).is_some_and(|key| input.key_held(key))
+    }
+}
+
+/// Looks a key up by its [`VirtualKeyCode`] name.
+fn parse_key(name: &str) -> Option<VirtualKeyCode> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides_defaults() {
+        let keymap = KeyMap::parse(
+            "# Vim style panning\npan-left = H\n  pan-right=L\n\nscheme-black-and-white = F5\nquit = none\n",
+        )
+        .unwrap();
+
+        assert_eq!(keymap.key(Action::PanLeft), Some(VirtualKeyCode::H));
+        assert_eq!(keymap.key(Action::PanRight), Some(VirtualKeyCode::L));
+        assert_eq!(
+            keymap.key(Action::SelectScheme(ColorScheme::BlackAndWhite)),
+            Some(VirtualKeyCode::F5)
+        );
+        assert_eq!(keymap.key(Action::Quit), None);
+        // Everything else keeps its default
+        assert_eq!(keymap.key(Action::ZoomIn), Some(VirtualKeyCode::PageUp));
+        assert_eq!(
+            keymap.key(Action::SelectScheme(ColorScheme::Smooth)),
+            Some(VirtualKeyCode::Key1)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lines() {
+        for (text, line) in [
+            ("zoom-in PageUp", 1),
+            ("# comment\nzoom-sideways = A", 2),
+            ("zoom-in = NoSuchKey", 1),
+            ("scheme-plaid = A", 1),
+        ] {
+            let err = KeyMap::parse(text).unwrap_err();
+            assert!(err.starts_with(&format!("line {line}:")), "{text:?}: {err}");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! The fractal math in [`fractals`] only depends on `core`, so it can be used
+//! without `std` by disabling default features and enabling `libm`. Everything
+//! that needs a window or threads lives behind the `std` feature.
+//!
+//! [`render_image`] renders a view to RGBA bytes without opening a window.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("frustal needs either the `std` or the `libm` feature for float math");
+
+#[cfg(feature = "std")]
+pub mod args;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "deep-zoom")]
+pub mod deep;
+#[cfg(feature = "std")]
+pub mod export;
+pub mod fractals;
+#[cfg(feature = "std")]
+pub mod gpu;
+#[cfg(feature = "std")]
+pub mod keymap;
+#[cfg(feature = "std")]
+pub mod overlay;
+#[cfg(feature = "std")]
+pub mod palette;
+#[cfg(feature = "std")]
+pub mod renderer;
+#[cfg(feature = "std")]
+pub mod stats;
+pub mod viewport;
+
+#[cfg(feature = "std")]
+use num_complex::Complex64;
+
+/// Renders the view of `scale` units around `center` without a window,
+/// returning its RGBA bytes, row by row, along with the width and height.
+/// The size, iteration limit, fractal and coloring come from `args`.
+#[cfg(feature = "std")]
+pub fn render_image(args: &args::Args, center: Complex64, scale: f64) -> (Vec<u8>, u32, u32) {
+    let mut renderer = renderer::Renderer::from_args(args);
+    renderer.set_view(center, scale);
+
+    let (width, height) = (args.get_width(), args.get_height());
+    (renderer.render_to_buffer(width, height), width, height)
+}
+
+/// A path in the temporary directory no other test or test run uses, for
+/// the tests that write files.
+#[cfg(all(test, feature = "std"))]
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("frustal_test_{}_{name}", std::process::id()))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::args::{Args, ColorScheme};
+
+    #[test]
+    fn test_render_image_honors_args() {
+        let args = Args::default()
+            .with_size(40, 30)
+            .with_max_iterations(50)
+            .with_color_scheme(ColorScheme::BlackAndWhite);
+        let (pixels, width, height) = render_image(&args, Complex64::new(-0.5, 0.0), 3.0);
+
+        assert_eq!((width, height), (40, 30));
+        assert_eq!(pixels.len(), 40 * 30 * 4);
+        // The center lies in the set, the corners far outside it
+        let at = |x: usize, y: usize| &pixels[(y * 40 + x) * 4..][..4];
+        assert_eq!(at(20, 15), [0, 0, 0, 255]);
+        assert_ne!(at(0, 0), at(20, 15));
+    }
+}
@@ -1,15 +1,105 @@
-mod args;
-mod fractals;
-mod renderer;
+use clap::Parser;
+use frustal::args::{Args, Cli, ColorScheme, Command};
+use frustal::export::{ExportTask, PGM_MAX_VALUE};
+use frustal::renderer::{Renderer, RendererRunner};
+use num_complex::Complex64;
+use std::path::PathBuf;
 
-use args::Args;
-use renderer::RendererRunner;
+/// Center and scale `bench` renders, a busy part of the seahorse valley.
+const BENCH_VIEW: (f64, f64, f64) = (-0.743643887037151, 0.131825904205330, 0.01);
+const BENCH_SIZES: [(u32, u32); 3] = [(320, 240), (800, 600), (1920, 1080)];
 
 fn main() -> Result<(), pixels::Error> {
     let args = Args::default()
         .with_size(800, 600)
-        .with_max_iterations(200);
+        .with_max_iterations(200)
+        .with_color_scheme(ColorScheme::Smooth);
+    // Environment variables override the built-in defaults
+    let args = match args.with_env() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+    // Command line flags take precedence over both
+    let cli = Cli::parse();
+    let command = cli.command();
+    let dump_path = cli.dump_iterations();
+    let output_path = cli.output();
+    let args = cli.apply(args);
+
+    if let Some(Command::Bench { frames }) = command {
+        bench(args, frames);
+        return Ok(());
+    }
+    if output_path.is_some() || dump_path.is_some() {
+        if let Some(path) = output_path {
+            render_output(&args, path);
+        }
+        if let Some(path) = dump_path {
+            dump_iterations(args, path);
+        }
+        return Ok(());
+    }
+
     let runner = RendererRunner::new()?;
     runner.with_args(args).run()?;
     Ok(())
 }
+
+/// Renders the view `args` describes once and saves it as a PNG at `path`,
+/// exiting with an error if that fails.
+fn render_output(args: &Args, path: PathBuf) {
+    let renderer = Renderer::from_args(args);
+    match renderer.render_to_file(&path, false) {
+        Ok(stats) => println!(
+            "Rendered {}x{} in {:.0} ms to {}",
+            args.get_width(),
+            args.get_height(),
+            stats.render_time_ms,
+            path.display()
+        ),
+        Err(err) => {
+            eprintln!("Failed to write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Writes the iteration counts of the view `args` describes to a PGM at
+/// `path`, exiting with an error if that fails.
+fn dump_iterations(args: Args, path: PathBuf) {
+    if args.get_max_iterations() > PGM_MAX_VALUE {
+        eprintln!("Counts above {PGM_MAX_VALUE} don't fit in a 16 bit PGM and are clamped");
+    }
+
+    let renderer = Renderer::from_args(&args);
+    match ExportTask::spawn_iterations(renderer, path.clone()).wait() {
+        Ok(()) => println!("Wrote the iteration counts to {}", path.display()),
+        Err(err) => {
+            eprintln!("Failed to write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints how long full renders of [`BENCH_VIEW`] take at each of
+/// [`BENCH_SIZES`], using the iterations and coloring of `args`.
+fn bench(args: Args, frames: u32) {
+    let (x, y, scale) = BENCH_VIEW;
+    let mut args = args.with_center(Complex64::new(x, y)).with_scale(scale);
+
+    for (width, height) in BENCH_SIZES {
+        args = args.with_size(width, height);
+        let renderer = Renderer::from_args(&args);
+        let elapsed = renderer.bench(frames as usize);
+
+        let frame_ms = elapsed.as_secs_f64() * 1000.0 / frames as f64;
+        let megapixels = width as f64 * height as f64 * frames as f64 / 1e6;
+        println!(
+            "{width}x{height}: {frame_ms:.2} ms/frame, {:.2} MP/s",
+            megapixels / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Minimal drawing helpers for annotating an RGBA frame: filled rectangles,
+//! lines and text in a built-in 5x7 bitmap font.
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+/// Horizontal distance between the starts of two characters, at scale 1
+const ADVANCE: usize = GLYPH_WIDTH + 1;
+
+/// Rows of a glyph, top to bottom, using the low five bits of each byte.
+/// Lowercase letters are drawn with the uppercase glyphs.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        ' ' => [0x00; GLYPH_HEIGHT],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// Width in pixels of `text` drawn at `scale`.
+pub fn text_width(text: &str, scale: usize) -> usize {
+    text.chars().count() * ADVANCE * scale
+}
+
+/// Height in pixels of a line of text drawn at `scale`.
+pub fn text_height(scale: usize) -> usize {
+    GLYPH_HEIGHT * scale
+}
+
+fn set_pixel(frame: &mut [u8], frame_width: usize, x: usize, y: usize, color: [u8; 3]) {
+    let index = (y * frame_width + x) * 4;
+    if x < frame_width && index + 4 <= frame.len() {
+        frame[index..index + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+    }
+}
+
+/// Fills `x0..x1` by `y0..y1`, clipped to the frame.
+pub fn fill_rect(
+    frame: &mut [u8],
+    frame_width: usize,
+    (x0, y0): (usize, usize),
+    (x1, y1): (usize, usize),
+    color: [u8; 3],
+) {
+    for y in y0..y1 {
+        for x in x0..x1 {
+            set_pixel(frame, frame_width, x, y, color);
+        }
+    }
+}
+
+/// Draws a line between two points, which may lie outside the frame. Only
+/// the part inside the frame is walked, so far-off points stay cheap.
+pub fn draw_line(
+    frame: &mut [u8],
+    frame_width: usize,
+    from: (f64, f64),
+    to: (f64, f64),
+    color: [u8; 3],
+) {
+    if frame_width == 0 || !(from.0.is_finite() && from.1.is_finite()) {
+        return;
+    }
+    if !(to.0.is_finite() && to.1.is_finite()) {
+        return;
+    }
+    let frame_height = frame.len() / 4 / frame_width;
+    let max_x = frame_width as f64 - 1.0;
+    let max_y = frame_height as f64 - 1.0;
+
+    // Liang-Barsky: narrow t in 0..1 down to the part inside the frame
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+    for (p, q) in [
+        (-dx, from.0),
+        (dx, max_x - from.0),
+        (-dy, from.1),
+        (dy, max_y - from.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 > t1 {
+        return;
+    }
+
+    let (x0, y0) = (from.0 + t0 * dx, from.1 + t0 * dy);
+    let (x1, y1) = (from.0 + t1 * dx, from.1 + t1 * dy);
+    let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let x = (x0 + (x1 - x0) * t).round();
+        let y = (y0 + (y1 - y0) * t).round();
+        set_pixel(frame, frame_width, x as usize, y as usize, color);
+    }
+}
+
+/// Draws `text` with its top-left corner at `(x, y)`, each font pixel
+/// becoming a `scale`x`scale` block. Anything outside the frame is clipped.
+pub fn draw_text(
+    frame: &mut [u8],
+    frame_width: usize,
+    (x, y): (usize, usize),
+    text: &str,
+    color: [u8; 3],
+    scale: usize,
+) {
+    for (index, c) in text.chars().enumerate() {
+        let origin_x = x + index * ADVANCE * scale;
+
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+
+                let block_x = origin_x + column * scale;
+                let block_y = y + row * scale;
+                fill_rect(
+                    frame,
+                    frame_width,
+                    (block_x, block_y),
+                    (block_x + scale, block_y + scale),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_text_clips_to_frame() {
+        let width = 10;
+        let mut frame = vec![0; width * 8 * 4];
+
+        // Runs off the right and bottom edges without panicking
+        draw_text(&mut frame, width, (6, 4), "1E-7", [255, 255, 255], 1);
+
+        // The top row of the "1" glyph has a single lit pixel, in column 2
+        assert_eq!(&frame[(4 * width + 8) * 4..(4 * width + 9) * 4], &[255; 4]);
+        assert_eq!(&frame[(4 * width + 7) * 4..(4 * width + 8) * 4], &[0; 4]);
+    }
+
+    #[test]
+    fn test_draw_line_clips_far_points() {
+        let width = 8;
+        let mut frame = vec![0; width * 8 * 4];
+
+        // A horizontal line through row 3 reaching far past both sides
+        draw_line(&mut frame, width, (-1e12, 3.0), (1e12, 3.0), [255, 0, 0]);
+        // Entirely outside, nothing to draw
+        draw_line(&mut frame, width, (-5.0, -5.0), (-1.0, 20.0), [0, 255, 0]);
+
+        for y in 0..8 {
+            for x in 0..width {
+                let lit = frame[(y * width + x) * 4] == 255;
+                assert_eq!(lit, y == 3, "({x}, {y})");
+            }
+        }
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[1] == 0));
+    }
+}
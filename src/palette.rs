@@ -0,0 +1,170 @@
+//! Gradient palettes for [`ColorScheme::Gradient`](crate::args::ColorScheme),
+//! read from GIMP `.gpl` files or plain text files of color stops.
+use crate::fractals::{GradientStop, DEFAULT_GRADIENT};
+use std::io;
+use std::path::Path;
+
+/// The color stops of a gradient, sorted by position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    stops: Vec<GradientStop>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            stops: DEFAULT_GRADIENT.to_vec(),
+        }
+    }
+}
+
+impl Palette {
+    /// Sorts `stops` by position. Fails if there are none or a position
+    /// lies outside `0.0..=1.0`.
+    pub fn new(mut stops: Vec<GradientStop>) -> Result<Self, String> {
+        if stops.is_empty() {
+            return Err("a palette needs at least one color".into());
+        }
+        if let Some(stop) = stops
+            .iter()
+            .find(|stop| !(0.0..=1.0).contains(&stop.position))
+        {
+            return Err(format!("position {} is outside 0.0 to 1.0", stop.position));
+        }
+
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Ok(Self { stops })
+    }
+
+    /// Parses a palette file. Two layouts are understood:
+    ///
+    /// - GIMP palettes, starting with a `GIMP Palette` line, with one
+    ///   `r g b name` line per color. The colors are spread evenly.
+    /// - Plain text with one `position r g b` line per stop, or `r g b` lines
+    ///   to spread the colors evenly.
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().peekable();
+        let gimp = lines
+            .peek()
+            .is_some_and(|(_, line)| line.trim() == "GIMP Palette");
+        if gimp {
+            lines.next();
+        }
+
+        let mut colors = Vec::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // GIMP headers like `Name: Fire`
+            if gimp && line.contains(':') {
+                continue;
+            }
+
+            let numbers: Vec<f64> = line
+                .split_whitespace()
+                .map_while(|token| token.parse().ok())
+                .collect();
+            let (position, rgb) = match numbers.as_slice() {
+                [r, g, b, ..] if gimp => (None, [*r, *g, *b]),
+                [position, r, g, b] => (Some(*position), [*r, *g, *b]),
+                [r, g, b] => (None, [*r, *g, *b]),
+                _ => return Err(format!("line {}: expected a color", index + 1)),
+            };
+            let Some(color) = to_color(rgb) else {
+                return Err(format!(
+                    "line {}: channels must be whole numbers from 0 to 255",
+                    index + 1
+                ));
+            };
+            colors.push((position, color));
+        }
+
+        let positioned = colors
+            .iter()
+            .filter(|(position, _)| position.is_some())
+            .count();
+        if positioned != 0 && positioned != colors.len() {
+            return Err("either every color or none needs a position".into());
+        }
+
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+        let stops = colors
+            .into_iter()
+            .enumerate()
+            .map(|(index, (position, color))| GradientStop {
+                position: position.unwrap_or(index as f64 / last),
+                color,
+            })
+            .collect();
+        Self::new(stops)
+    }
+
+    /// Reads and parses a palette file, see [`Palette::parse`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+}
+
+fn to_color(rgb: [f64; 3]) -> Option<[u8; 3]> {
+    let valid = rgb
+        .iter()
+        .all(|channel| channel.fract() == 0.0 && (0.0..=255.0).contains(channel));
+    valid.then(|| rgb.map(|channel| channel as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gimp_palette() {
+        let palette = Palette::parse(
+            "GIMP Palette\nName: Fire\nColumns: 3\n#\n  0   0   0\tBlack\n255 128   0\tOrange\n255 255 255\tWhite\n",
+        )
+        .unwrap();
+
+        let stops = palette.stops();
+        assert_eq!(stops.len(), 3);
+        assert_eq!(stops[0].position, 0.0);
+        assert_eq!(stops[1].position, 0.5);
+        assert_eq!(stops[1].color, [255, 128, 0]);
+        assert_eq!(stops[2].position, 1.0);
+    }
+
+    #[test]
+    fn test_parse_positioned_stops() {
+        let palette = Palette::parse("# position r g b\n1.0 255 0 0\n0.2 0 0 255\n").unwrap();
+        assert_eq!(
+            palette.stops(),
+            [
+                GradientStop {
+                    position: 0.2,
+                    color: [0, 0, 255],
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: [255, 0, 0],
+                },
+            ]
+        );
+
+        for text in [
+            "",
+            "0.5 255 0\n",
+            "0 0 256\n",
+            "1.5 0 0 0\n",
+            "0.0 0 0 0\n255 255 255\n",
+        ] {
+            assert!(Palette::parse(text).is_err(), "{text:?}");
+        }
+    }
+}
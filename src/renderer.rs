@@ -1,412 +1,6836 @@
 use crate::args::{Args, ColorScheme, ScanConfig};
-use crate::fractals::{color_map, mandelbrot, ColorMode};
+use crate::backend::RenderBackend;
+use crate::export::{color_map_strip, save_png, ExportError, ExportTask};
+use crate::fractals::{
+    angle_color_channels, color_map_value, distance_color_channels, gradient_color,
+    gradient_color_oklab, newton_color_channels, smooth_color, stripe_color_channels,
+    trap_color_channels, ColorMode, EscapeResult, Fractal, FractalKind, ToneMap, TrapShape,
+    DEFAULT_BAILOUT, DEFAULT_SINGLE_HUE, DEFAULT_STRIPE_FREQUENCY,
+};
+use crate::gpu::GpuRenderer;
+use crate::keymap::{Action, KeyMap};
+use crate::overlay;
+use crate::palette::Palette;
+use crate::stats::{LocationError, RenderStats, ViewState, ViewStats, STATS_VERSION};
+use crate::viewport::{buffer_len, Viewport};
+use num_complex::Complex64;
 use pixels::{Error, Pixels, SurfaceTexture};
 use rayon::prelude::*;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use winit::{
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalSize},
     event::{Event, VirtualKeyCode},
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+    window::{Fullscreen, WindowBuilder},
 };
-use winit_input_helper::WinitInputHelper;
+use winit_input_helper::{TextChar, WinitInputHelper};
+
+const WINDOW_TITLE: &str = "Fractal Renderer";
+const DEFAULT_SCALE: f64 = 2.5;
+/// Widest view zooming out goes to, unless set with
+/// [`Renderer::set_max_scale`].
+const MAX_SCALE: f64 = 10.0;
+const DEFAULT_ESCAPE_VELOCITY_EXPONENT: f64 = 4.0;
+const LOUPE_SIZE: u32 = 160;
+const LOUPE_ZOOM: f64 = 8.0;
+const DEFAULT_COLOR_JITTER: f64 = 1.0;
+const DEFAULT_PALETTE_DEPTH_COUPLING: f64 = 0.1;
+const SCALE_BAR_PIXELS: usize = 100;
+const SCALE_BAR_MARGIN: usize = 8;
+const HUD_MARGIN: usize = 8;
+/// Pixels between two lines of HUD text.
+const HUD_LINE_SPACING: usize = 3;
+/// Most orbit points drawn, however high the iteration limit.
+const ORBIT_MAX_POINTS: u32 = 1000;
+/// Side of the tiles full renders are split into, in pixels, see
+/// [`Renderer::render_tiles`].
+const RENDER_TILE: usize = 32;
+/// Side of the tiles border tracing starts from, in pixels.
+const TRACE_TILE: usize = 64;
+/// Tiles this narrow are computed in full rather than split any further.
+const TRACE_MIN_TILE: usize = 8;
+/// Rows each slice of a fine scan pass covers, whole trace tiles so the final
+/// pass traces the same tiles as a full render
+const SCAN_SLICE_ROWS: usize = TRACE_TILE;
+/// How long one call of [`Renderer::render`] keeps refining before it hands
+/// control back for input
+const SCAN_SLICE_BUDGET: Duration = Duration::from_millis(12);
+const DETAIL_TILES: usize = 8;
+const DETAIL_DOWNSAMPLE: u32 = 4;
+const DETAIL_ZOOM: f64 = 0.5;
+const CLICK_ZOOM: f64 = 0.5;
+/// How far, in window pixels, the mouse has to move with the left button
+/// down before a click turns into a drag.
+const DRAG_THRESHOLD: f32 = 4.0;
+/// Factor the scale changes by per notch of the mouse wheel, unless set
+/// with [`Renderer::set_zoom_step`].
+const WHEEL_ZOOM: f64 = 0.8;
+/// Fraction of the view a full step of [`Renderer::pan`] moves by, unless
+/// set with [`Renderer::set_pan_speed`].
+const PAN_SPEED: f64 = 0.3;
+const VIEW_STATE_PATH: &str = "view.json";
+/// Width and height of the strips [`Action::PaletteStrip`] saves, one column
+/// per iteration.
+const PALETTE_STRIP_SIZE: (u32, u32) = (256, 32);
+/// Gamma supersampled colors are blended with, unless set with
+/// [`Renderer::set_gamma`]. The usual approximation of sRGB.
+const DEFAULT_GAMMA: f64 = 2.2;
+/// Supersampling grid sizes cycled through, one sample per pixel first.
+const AA_LEVELS: [u32; 3] = [1, 2, 4];
+/// Samples per pixel temporal supersampling stops adding at, see
+/// [`Renderer::set_temporal_aa`].
+const TEMPORAL_AA_SAMPLES: u32 = 64;
+/// Radius, in pixels, and strength of the glow, unless set with
+/// [`Renderer::set_glow`].
+const DEFAULT_GLOW: (usize, f64) = (6, 0.5);
+/// Share of the pixels that have to escape between two iteration limits for
+/// [`Renderer::auto_tune_iterations`] to try the higher one.
+const AUTO_TUNE_THRESHOLD: f64 = 0.001;
+/// Longer side of the scratch view [`Renderer::auto_tune_iterations`]
+/// renders, in pixels.
+const AUTO_TUNE_SIZE: u32 = 256;
+/// Lowest and highest iteration limits [`Renderer::auto_tune_iterations`]
+/// picks.
+const AUTO_TUNE_LIMITS: (u32, u32) = (64, 1 << 16);
+/// Factor the `-` and `=` keys change the iteration limit by.
+const ITERATION_STEP: f64 = 1.25;
+/// Iterations a pixel's escape can differ from its neighbours' by before
+/// adaptive supersampling treats it as an edge.
+const ADAPTIVE_AA_THRESHOLD: u32 = 1;
+/// Fraction of the scale left after one second of auto-zoom.
+const AUTO_ZOOM_RATE: f64 = 0.5;
+/// Longest time step taken in one frame, so a stall doesn't cause a jump.
+const AUTO_ZOOM_MAX_STEP: f64 = 0.1;
+/// Iterations added for every halving of the scale, by the auto-zoom and
+/// by auto iterations.
+const ITERATIONS_PER_OCTAVE: f64 = 24.0;
+/// Factor the scale is headed for changes by per second while a zoom key is
+/// held.
+const HELD_ZOOM_RATE: f64 = 0.25;
+/// How quickly the scale catches up with where it is headed, per second.
+const ZOOM_EASING: f64 = 12.0;
+/// Speed the inertial pan builds up to while a pan key is held, in units of
+/// [`Renderer::pan`] per second, and the most it ever reaches.
+const HELD_PAN_RATE: f64 = 3.0;
+/// How quickly the inertial pan picks up speed while a pan key is held, and
+/// how quickly it slows down once they are all released, per second.
+const PAN_ACCELERATION: f64 = 10.0;
+const PAN_FRICTION: f64 = 4.0;
+/// Speed below which a gliding pan stops, in units of [`Renderer::pan`] per
+/// second.
+const PAN_STOP_SPEED: f64 = 0.01;
+/// Fraction of the palette the colors move through per second while cycling.
+const PALETTE_CYCLE_SPEED: f64 = 0.2;
+/// How often an idle window wakes to see whether an export has finished
+const EXPORT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Well-known deep zoom locations the auto-zoom cycles through.
+const AUTO_ZOOM_TARGETS: [(f64, f64); 4] = [
+    (-0.743_643_887_037_151, 0.131_825_904_205_330),
+    (-1.749_759_145_130_366_5, 0.0),
+    (0.360_240_443_437_614_4, -0.641_313_061_064_803_2),
+    (-0.101_096_363_845_622_3, 0.956_286_510_809_142_4),
+];
+
+/// State of a running auto-zoom.
+#[derive(Clone)]
+struct AutoZoom {
+    target: Complex64,
+    base_iterations: u32,
+}
+
+/// The escapes of every pixel of a full render, and what they were computed
+/// for. Panning, zooming, resizing or changing the iteration limit, bailout
+/// or fractal makes it stale, see [`Renderer::cached_escapes`].
+#[derive(Clone)]
+struct EscapeCache {
+    viewport: Viewport,
+    fractal: FractalKind,
+    max_iterations: u32,
+    bailout: f64,
+    /// The frequency the stripe averages were taken with, if they were
+    stripe_frequency: Option<f64>,
+    escapes: Vec<EscapeResult>,
+}
+
+/// The escapes of one tile of a border-traced region, filled in as they are
+/// needed. Coordinates are relative to the tile.
+struct TracedTile<'a> {
+    reals: &'a [f64],
+    imags: &'a [f64],
+    width: usize,
+    escapes: Vec<Option<EscapeResult>>,
+}
+
+/// The colors of every iteration count below the limit in one of the plain
+/// gradient modes, see [`Renderer::update_gradient_lut`].
+#[derive(Clone)]
+struct GradientLut {
+    mode: ColorMode,
+    max_iterations: u32,
+    colors: Vec<[f64; 3]>,
+}
 
+/// A smooth zoom around the center, see [`Renderer::step_animation`].
+#[derive(Clone)]
+struct AnimationState {
+    /// The scale being eased towards
+    target_scale: f64,
+    /// What the target is multiplied by per second while the zoom key is
+    /// held, `None` once it is released
+    held_rate: Option<f64>,
+}
+
+#[derive(Clone)]
 pub struct Renderer {
-    width: u32,
-    height: u32,
-    center_x: f64,
-    center_y: f64,
-    scale: f64,
+    viewport: Viewport,
     max_iterations: u32,
+    /// Whether `max_iterations` follows the zoom depth, see
+    /// [`Self::toggle_auto_iterations`]
+    auto_iterations: bool,
+    /// The iteration limit at the default scale while `auto_iterations` is on
+    base_iterations: u32,
+    bailout: f64,
+    fractal: FractalKind,
+    /// Computes the escapes in place of `fractal`, see
+    /// [`Self::set_custom_fractal`]
+    custom_fractal: Option<Arc<dyn Fractal>>,
     color_scheme: ColorScheme,
+    pan_speed: f64,
+    zoom_step: f64,
+    /// Widest view zooming out goes to
+    max_scale: f64,
+    /// The color scheme and iteration limit [`Self::restore_settings`] goes
+    /// back to
+    home_settings: (ColorScheme, u32),
+    escape_velocity_exponent: f64,
+    /// Whether the potential scheme draws equipotential lines
+    potential_contours: bool,
+    /// See [`Self::set_single_hue`]
+    single_hue: [u8; 3],
+    /// See [`Self::set_stripe_frequency`]
+    stripe_frequency: f64,
+    /// See [`Self::set_perceptual`]
+    perceptual: bool,
+    orbit_trap: TrapShape,
+    /// Whether every scheme colors by the fractional escape value, like
+    /// the smooth scheme always does
+    smooth_coloring: bool,
+    /// Color of the points that never escape
+    interior_color: [u8; 3],
+    /// Whether the interior is lightened by where each orbit settles, see
+    /// [`Self::set_interior_shading`]
+    interior_shading: bool,
+    /// See [`Self::set_keymap`]
+    keymap: KeyMap,
+    color_jitter: f64,
+    jitter_seed: u64,
+    tonemap: ToneMap,
+    invert_palette: bool,
+    palette_depth_coupling: f64,
+    aa_samples: u32,
+    /// Whether supersampling is limited to edges, see
+    /// [`Self::render_adaptive_aa`]
+    adaptive_aa: bool,
+    /// See [`Self::set_temporal_aa`]
+    temporal_aa: bool,
+    /// Sums of the linear colors of every temporal sample of each pixel
+    accumulator: Vec<[f32; 3]>,
+    /// Temporal samples in `accumulator`, 0 after every change to the view
+    accumulated_samples: u32,
+    /// Set to stop the render in progress, see [`Self::cancel_handle`]
+    cancel: Arc<AtomicBool>,
+    /// Whether [`Self::render`] is running, the only time `cancel` is heeded
+    rendering: bool,
+    /// Whether `cancel` cut the last render short
+    cancelled: bool,
+    /// Whether [`Self::render`] adds a glow, see [`Self::set_glow`]
+    glow_enabled: bool,
+    glow_radius: usize,
+    glow_strength: f64,
+    /// See [`Self::set_gamma`]
+    gamma: f64,
+    /// Whether samples are blended in linear light, see
+    /// [`Self::toggle_gamma_correction`]
+    gamma_correction: bool,
+    /// Stops of the gradient color scheme
+    palette: Palette,
     scan_level: u32,
+    /// First row of the current fine pass that isn't drawn yet, see
+    /// [`Self::render`]
+    scan_row: usize,
+    /// Escapes of the rows the final pass has traced so far, which become
+    /// the escape cache once it reaches the bottom
+    scan_escapes: Option<EscapeCache>,
     scan_config: ScanConfig,
+    /// The frame a double buffered scan draws into, see
+    /// [`Self::render_buffered`]. Emptied whenever the frame is drawn to
+    /// directly, so it gets copied again.
+    back_buffer: Vec<u8>,
+    /// When the frame was last handed over from `back_buffer`
+    last_present: Instant,
+    loupe_enabled: bool,
+    cursor: Option<(f64, f64)>,
+    /// Corners of the rubber band being dragged out, see [`Self::select`]
+    selection: Option<((f64, f64), (f64, f64))>,
+    orbit_enabled: bool,
+    scale_bar_enabled: bool,
+    hud_enabled: bool,
+    compare_scheme: Option<ColorScheme>,
+    auto_zoom: Option<AutoZoom>,
+    next_auto_zoom_target: usize,
+    animation: Option<AnimationState>,
+    /// Velocity of the inertial pan, in units of [`Self::pan`] per second,
+    /// see [`Self::hold_pan`]
+    pan_velocity_x: f64,
+    pan_velocity_y: f64,
+    /// Direction the pan keys are held in, see [`Self::hold_pan`]
+    held_pan: (f64, f64),
+    /// Pixels [`Self::pan`] or the inertial pan moved by that didn't add up
+    /// to a whole one yet
+    pan_remainder: (f64, f64),
+    /// See [`Self::set_pan_inertia`]
+    pan_inertia: bool,
+    /// Fraction of the palette the colors are shifted by
+    color_offset: f64,
+    palette_cycling: bool,
+    escape_cache: Option<EscapeCache>,
+    gradient_lut: Option<GradientLut>,
+    backend: RenderBackend,
+    /// Draws the views it can instead of the CPU, see [`Self::render_gpu`]
+    gpu: Option<Arc<GpuRenderer>>,
+    /// Whether the frame on screen came from `gpu`, leaving the frame
+    /// buffer behind
+    frame_on_gpu: bool,
+    /// See [`Self::render_time`]
+    render_time: Duration,
 }
 
 impl Renderer {
     pub fn new() -> Self {
+        let fractal = FractalKind::Mandelbrot;
+        let (center, scale) = fractal.default_view();
+
         Self {
-            width: 800,
-            height: 600,
-            center_x: -0.5,
-            center_y: 0.0,
-            scale: 2.5,
+            viewport: Viewport::new(center, scale, 800, 600),
             max_iterations: 200,
+            auto_iterations: false,
+            base_iterations: 200,
+            bailout: DEFAULT_BAILOUT,
+            fractal,
+            custom_fractal: None,
             color_scheme: ColorScheme::Smooth,
+            pan_speed: PAN_SPEED,
+            zoom_step: WHEEL_ZOOM,
+            max_scale: MAX_SCALE,
+            home_settings: (ColorScheme::Smooth, 200),
+            escape_velocity_exponent: DEFAULT_ESCAPE_VELOCITY_EXPONENT,
+            potential_contours: false,
+            single_hue: DEFAULT_SINGLE_HUE,
+            stripe_frequency: DEFAULT_STRIPE_FREQUENCY,
+            perceptual: false,
+            orbit_trap: TrapShape::DEFAULTS[0],
+            smooth_coloring: false,
+            interior_color: [0, 0, 0],
+            interior_shading: false,
+            keymap: KeyMap::default(),
+            color_jitter: 0.0,
+            jitter_seed: 0,
+            tonemap: ToneMap::None,
+            invert_palette: false,
+            palette_depth_coupling: 0.0,
+            aa_samples: 1,
+            adaptive_aa: false,
+            temporal_aa: false,
+            accumulator: Vec::new(),
+            accumulated_samples: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+            rendering: false,
+            cancelled: false,
+            glow_enabled: false,
+            glow_radius: DEFAULT_GLOW.0,
+            glow_strength: DEFAULT_GLOW.1,
+            gamma: DEFAULT_GAMMA,
+            gamma_correction: true,
+            palette: Palette::default(),
             scan_level: 0,
+            scan_row: 0,
+            scan_escapes: None,
             scan_config: ScanConfig::default(),
+            back_buffer: Vec::new(),
+            last_present: Instant::now(),
+            loupe_enabled: false,
+            cursor: None,
+            selection: None,
+            orbit_enabled: false,
+            scale_bar_enabled: false,
+            hud_enabled: false,
+            compare_scheme: None,
+            auto_zoom: None,
+            next_auto_zoom_target: 0,
+            animation: None,
+            pan_velocity_x: 0.0,
+            pan_velocity_y: 0.0,
+            held_pan: (0.0, 0.0),
+            pan_remainder: (0.0, 0.0),
+            pan_inertia: true,
+            color_offset: 0.0,
+            palette_cycling: false,
+            escape_cache: None,
+            gradient_lut: None,
+            backend: RenderBackend::detect(),
+            gpu: None,
+            frame_on_gpu: false,
+            render_time: Duration::ZERO,
         }
     }
 
-    pub fn pan(&mut self, dx: f64, dy: f64) {
-        self.center_x += dx * self.scale * 0.3;
-        self.center_y += dy * self.scale * 0.3;
-        if self.scan_config.enabled {
-            self.scan_level = 0;
+    /// Builds a renderer for the given arguments without needing a window,
+    /// e.g. for headless renders.
+    pub fn from_args(args: &Args) -> Self {
+        let mut renderer = Self::new();
+        renderer.viewport.width = args.get_width();
+        renderer.viewport.height = args.get_height();
+        renderer.max_iterations = args.get_max_iterations();
+        renderer.bailout = args.get_bailout();
+        renderer.color_scheme = args.get_color_scheme();
+        renderer.home_settings = (renderer.color_scheme, renderer.max_iterations);
+        renderer.set_fractal_kind(args.get_fractal_kind());
+        renderer.apply_view_args(args);
+        renderer.scan_config = args.get_scan_config();
+        renderer.orbit_trap = args.get_orbit_trap();
+        renderer.color_jitter = args.get_color_jitter();
+        renderer.tonemap = args.get_tonemap();
+        renderer.invert_palette = args.get_invert_palette();
+        renderer.interior_color = args.get_interior_color();
+        renderer.interior_shading = args.get_interior_shading();
+        renderer.single_hue = args.get_single_hue();
+        renderer.stripe_frequency = args.get_stripe_frequency();
+        renderer.perceptual = args.get_perceptual();
+        if let Some(keymap) = args.get_keymap() {
+            renderer.keymap = keymap.clone();
+        }
+        renderer.palette_depth_coupling = args.get_palette_depth_coupling();
+        renderer.aa_samples = args.get_aa_samples();
+        renderer.temporal_aa = args.get_temporal_aa();
+        renderer.glow_enabled = args.get_glow().is_some();
+        renderer.glow_strength = args.get_glow().unwrap_or(DEFAULT_GLOW.1);
+        renderer.glow_radius = args.get_glow_radius().unwrap_or(DEFAULT_GLOW.0);
+        if let Some(palette) = args.get_palette() {
+            renderer.palette = palette.clone();
+        }
+        if let Some(backend) = args.get_render_backend() {
+            renderer.set_backend(backend);
         }
+        renderer.gamma = args.get_gamma().unwrap_or(DEFAULT_GAMMA);
+        renderer.pan_speed = args.get_pan_speed().unwrap_or(PAN_SPEED);
+        renderer.pan_inertia = args.get_pan_inertia();
+        renderer.zoom_step = args.get_zoom_step().unwrap_or(WHEEL_ZOOM);
+        renderer.max_scale = args.get_max_scale().unwrap_or(MAX_SCALE);
+        renderer.update_gradient_lut();
+        renderer
     }
 
-    pub fn zoom(&mut self, factor: f64) {
-        let new_scale = self.scale * factor;
-        if new_scale <= 10.0 {
-            self.scale = new_scale;
-
-            if self.scan_config.enabled {
-                self.scan_level = 0;
-            }
+    /// Takes the center and scale from the arguments, where given, fitting
+    /// the region between the corners of [`Args::get_bounds`] to the frame
+    /// first.
+    fn apply_view_args(&mut self, args: &Args) {
+        if let Some((upper_left, lower_right)) = args.get_bounds() {
+            // The size asked for, which the frame may not be resized to yet
+            let (width, height) = (args.get_width(), args.get_height());
+            let fitted = Viewport::from_corners(upper_left, lower_right, width, height);
+            self.viewport.center_x = fitted.center_x;
+            self.viewport.center_y = fitted.center_y;
+            self.viewport.scale = fitted.scale;
+        }
+        if let Some(center) = args.get_center() {
+            self.viewport.center_x = center.re;
+            self.viewport.center_y = center.im;
+        }
+        if let Some(scale) = args.get_scale() {
+            self.viewport.scale = scale;
         }
     }
 
-    pub fn render(&mut self, frame: &mut [u8]) {
-        if !self.scan_config.enabled {
-            // Regular rendering without scanning
-            self.render_full(frame);
-            return;
+    /// The current view, for saving it to come back to later.
+    pub fn export_state(&self) -> ViewState {
+        ViewState {
+            center_x: self.viewport.center_x,
+            center_y: self.viewport.center_y,
+            scale: self.viewport.scale,
+            max_iterations: self.max_iterations,
+            color_scheme: self.color_scheme,
         }
+    }
 
-        // Calculate stride based on current scan level
-        let stride = if self.scan_level == 0 {
-            self.scan_config.initial_stride
-        } else {
-            self.scan_config.initial_stride >> self.scan_level
-        };
+    /// The current view as a token to share, see
+    /// [`ViewState::to_location_string`].
+    pub fn to_location_string(&self) -> String {
+        self.export_state().to_location_string()
+    }
 
-        if stride < 1 {
-             // All passes completed
-            return;
-        }
+    /// Goes back to a view saved with [`Self::export_state`], stopping any
+    /// auto-zoom and auto iterations and restarting the progressive render.
+    /// A view that fails [`ViewState::validate`] is left unapplied.
+    pub fn load_state(&mut self, state: ViewState) -> Result<(), LocationError> {
+        state.validate()?;
+        self.stop_auto_zoom();
+        // The saved limit is used as is, rather than scaled again
+        self.auto_iterations = false;
+        self.viewport.center_x = state.center_x;
+        self.viewport.center_y = state.center_y;
+        self.viewport.scale = state.scale;
+        self.max_iterations = state.max_iterations;
+        self.color_scheme = state.color_scheme;
+        self.scan_level = 0;
+        Ok(())
+    }
 
-        self.render_with_stride(frame, stride);
-        self.scan_level += 1;
+    pub fn fractal_kind(&self) -> FractalKind {
+        self.fractal
     }
 
-    fn render_full(&self, frame: &mut [u8]) {
-        let width = self.width as usize;
-        let height = self.height as usize;
-        let chunk_size = (width * height / rayon::current_num_threads()).max(1);
+    /// Switches to another fractal, moving to the view that shows all of it.
+    pub fn set_fractal_kind(&mut self, fractal: FractalKind) {
+        self.fractal = fractal;
+        let (center, scale) = fractal.default_view();
+        self.set_view(center, scale);
+    }
 
-        frame
-            .par_chunks_exact_mut(4 * chunk_size)
-            .enumerate()
-            .for_each(|(chunk_index, chunk)| {
-                let start = chunk_index * chunk_size;
-                let end = (start + chunk_size).min(width * height);
+    /// Draws `fractal` instead of the built-in fractal kind, or goes back to
+    /// it for `None`. The view stays where it is, and the fractal is iterated
+    /// with [`Self::bailout`]. Custom fractals only give escapes, so the
+    /// schemes that follow the whole orbit fall back to smooth coloring and
+    /// the orbit overlay is hidden.
+    pub fn set_custom_fractal(&mut self, fractal: Option<Arc<dyn Fractal>>) {
+        self.custom_fractal = fractal;
+        self.escape_cache = None;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
 
-                for index in start..end {
-                    let x = index % width;
-                    let y = index / width;
+    /// The power of the multibrot set on screen, 2 for the Mandelbrot set.
+    /// `None` for the other fractals.
+    pub fn power(&self) -> Option<f64> {
+        match self.fractal {
+            FractalKind::Mandelbrot | FractalKind::Multibrot { .. } => self.fractal.power(),
+            _ => None,
+        }
+    }
 
-                    let real =
-                        self.center_x + (x as f64 - width as f64 / 2.0) * self.scale / width as f64;
-                    let imag = self.center_y
-                        + (y as f64 - height as f64 / 2.0) * self.scale / height as f64;
+    /// Switches between multibrot sets of different powers, keeping the
+    /// view. Does nothing unless a multibrot or the Mandelbrot set is shown.
+    pub fn set_power(&mut self, power: f64) {
+        if self.power().is_none() || !(power.is_finite() && power > 1.0) {
+            return;
+        }
 
-                    let iterations = mandelbrot(real, imag, self.max_iterations);
-                    let color = self.get_color(iterations);
+        self.fractal = FractalKind::multibrot(power);
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
 
-                    let pixel_index = (index - start) * 4;
-                    chunk[pixel_index..pixel_index + 4]
-                        .copy_from_slice(&[color[0], color[1], color[2], 255]);
-                }
-            });
+    /// Goes back to the view the fractal starts out with, stopping any
+    /// auto-zoom or zoom animation.
+    pub fn reset(&mut self) {
+        self.stop_auto_zoom();
+        self.animation = None;
+        let (center, scale) = self.fractal.default_view();
+        self.set_view(center, scale);
     }
 
-    fn render_with_stride(&self, frame: &mut [u8], stride: u32) {
-        let width = self.width as usize;
-        let height = self.height as usize;
-        let chunk_size = (width * height / rayon::current_num_threads()).max(1);
+    /// Goes back to the color scheme and iteration limit the renderer
+    /// started with.
+    pub fn restore_settings(&mut self) {
+        let (color_scheme, max_iterations) = self.home_settings;
+        self.color_scheme = color_scheme;
+        self.max_iterations = max_iterations;
+        self.base_iterations = max_iterations;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
 
-        frame
-            .par_chunks_exact_mut(4 * chunk_size)
-            .enumerate()
-            .for_each(|(chunk_index, chunk)| {
-                let start = chunk_index * chunk_size;
-                let end = (start + chunk_size).min(width * height);
-
-                for index in start..end {
-                    let x = index % width;
-                    let y = index / width;
-
-                    if (x % stride as usize == 0) && (y % stride as usize == 0) {
-                        let real = self.center_x
-                            + (x as f64 - width as f64 / 2.0) * self.scale / width as f64;
-                        let imag = self.center_y
-                            + (y as f64 - height as f64 / 2.0) * self.scale / height as f64;
-
-                        let iterations = mandelbrot(real, imag, self.max_iterations);
-                        let color = self.get_color(iterations);
-
-                        // Fill the block of pixels for the current stride
-                        for dy in 0..stride as usize {
-                            for dx in 0..stride as usize {
-                                let fill_x = x + dx;
-                                let fill_y = y + dy;
-                                if fill_x < width && fill_y < height {
-                                    let fill_index = (fill_y * width + fill_x - start) * 4;
-                                    if fill_index + 3 < chunk.len() {
-                                        chunk[fill_index..fill_index + 4]
-                                            .copy_from_slice(&[color[0], color[1], color[2], 255]);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            });
+    /// Moves the view to `center`, spanning `scale` units across the smaller side of the frame.
+    pub fn set_view(&mut self, center: Complex64, scale: f64) {
+        self.viewport.center_x = center.re;
+        self.viewport.center_y = center.im;
+        self.viewport.scale = scale;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
     }
 
-    fn get_color(&self, iterations: u32) -> [u8; 3] {
-        match self.color_scheme {
-            ColorScheme::Smooth => color_map(iterations, self.max_iterations, ColorMode::Smooth),
-            ColorScheme::Zebra => color_map(iterations, self.max_iterations, ColorMode::Zebra),
-            ColorScheme::Red => color_map(iterations, self.max_iterations, ColorMode::Red),
-            ColorScheme::Blue => color_map(iterations, self.max_iterations, ColorMode::Blue),
-            ColorScheme::BlackAndWhite => {
-                color_map(iterations, self.max_iterations, ColorMode::BlackAndWhite)
-            }
-            ColorScheme::Rainbow => color_map(iterations, self.max_iterations, ColorMode::Rainbow),
-            ColorScheme::Psychedelic => {
-                color_map(iterations, self.max_iterations, ColorMode::Psychedelic)
-            }
-            ColorScheme::GreenGradient => {
-                color_map(iterations, self.max_iterations, ColorMode::GreenGradient)
-            }
-            ColorScheme::Electric => {
-                color_map(iterations, self.max_iterations, ColorMode::Electric)
-            }
+    /// Moves the view by `dx`/`dy` times the pan speed of its size, rounded
+    /// to whole pixels so the escapes already computed can be shifted along.
+    /// The part of a pixel left over is carried into the next pan, so many
+    /// small pans add up rather than being dropped.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let pixels = self.viewport.scale * self.pan_speed / self.viewport.pixel_size();
+        let (remainder_x, remainder_y) = self.pan_remainder;
+        let (x, y) = (remainder_x - dx * pixels, remainder_y - dy * pixels);
+        self.pan_remainder = (x - x.round(), y - y.round());
+        if x.round() != 0.0 || y.round() != 0.0 {
+            self.pan_pixels(x.round(), y.round());
         }
     }
 
-    pub fn change_color_scheme(&mut self, scheme: ColorScheme) {
-        self.color_scheme = scheme;
+    /// Moves the view so the content follows a drag of `dx`/`dy` pixels.
+    pub fn pan_pixels(&mut self, dx: f64, dy: f64) {
+        let pixel_size = self.viewport.pixel_size();
+        self.viewport.center_x -= dx * pixel_size;
+        self.viewport.center_y -= dy * pixel_size;
         if self.scan_config.enabled {
             self.scan_level = 0;
         }
     }
 
-    pub fn is_scanning(&self) -> bool {
-        if !self.scan_config.enabled {
-            return false;
+    /// Pans by a whole number of pixels, reusing what is already in the frame:
+    /// the existing image is shifted and only the newly exposed edges are
+    /// rendered, so dragging gives instant feedback.
+    pub fn drag(&mut self, frame: &mut [u8], dx: isize, dy: isize) {
+        self.back_buffer.clear();
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+
+        if self.compare_scheme.is_some()
+            || matches!(self.color_scheme, ColorScheme::Histogram)
+            // The glow would be missing from the exposed edges
+            || self.glow_enabled
+            || dx.unsigned_abs() >= width
+            || dy.unsigned_abs() >= height
+        {
+            // Nothing (reliable) to reuse, render from scratch
+            self.pan_pixels(dx as f64, dy as f64);
+            self.render(frame);
+            return;
+        }
+
+        // Overlays are drawn into the frame, so they get shifted along with it
+        let overlays = self.overlay_rects();
+
+        let scan_level = self.scan_level;
+        self.pan_pixels(dx as f64, dy as f64);
+        // The shifted pixels stay valid, so keep refining rather than restarting
+        self.scan_level = scan_level;
+        // The samples don't shift along with them
+        self.accumulated_samples = 0;
+
+        let row_bytes = width * 4;
+        let shift_x = dx.unsigned_abs();
+        let shift_y = dy.unsigned_abs();
+
+        if dy > 0 {
+            frame.copy_within(0..(height - shift_y) * row_bytes, shift_y * row_bytes);
+        } else if dy < 0 {
+            frame.copy_within(shift_y * row_bytes..height * row_bytes, 0);
         }
-        let stride = if self.scan_level == 0 {
-            self.scan_config.initial_stride
+
+        if dx != 0 {
+            frame.par_chunks_exact_mut(row_bytes).for_each(|row| {
+                if dx > 0 {
+                    row.copy_within(0..(width - shift_x) * 4, shift_x * 4);
+                } else {
+                    row.copy_within(shift_x * 4..row_bytes, 0);
+                }
+            });
+        }
+
+        // Newly exposed rows span the full width, exposed columns the rest
+        let (rows_start, rows_end) = if dy > 0 {
+            (0, shift_y)
+        } else {
+            (height - shift_y, height)
+        };
+        let (columns_start, columns_end) = if dx > 0 {
+            (0, shift_x)
+        } else {
+            (width - shift_x, width)
+        };
+        let (kept_start, kept_end) = if dy > 0 {
+            (shift_y, height)
         } else {
-            self.scan_config.initial_stride >> self.scan_level
+            (0, height - shift_y)
         };
-        stride >= 1
+
+        self.render_rect(frame, 0, rows_start, width, rows_end);
+        self.render_rect(frame, columns_start, kept_start, columns_end, kept_end);
+
+        // Replace the shifted copies of the overlays with fractal, then redraw them
+        for (x0, y0, x1, y1) in overlays {
+            let shift = |value: usize, delta: isize, limit: usize| {
+                (value as isize + delta).clamp(0, limit as isize) as usize
+            };
+            self.render_rect(
+                frame,
+                shift(x0, dx, width),
+                shift(y0, dy, height),
+                shift(x1, dx, width),
+                shift(y1, dy, height),
+            );
+        }
+        self.draw_overlays(frame);
     }
-}
 
-pub struct RendererRunner {
-    event_loop: EventLoop<()>,
-    window: winit::window::Window,
-    pixels: Pixels,
-    renderer: Renderer,
-    input: WinitInputHelper,
-    args: Args,
-}
+    /// Scales the view by `factor`, keeping the center. Zooms that would go
+    /// wider than [`Self::max_scale`] or deeper than [`Self::min_scale`]
+    /// are refused. Nothing is printed, as a held key asks again and again;
+    /// [`Self::is_at_precision_limit`] says when zooming in would be refused.
+    pub fn zoom(&mut self, factor: f64) {
+        let new_scale = self.viewport.scale * factor;
+        if factor < 1.0 && new_scale < self.min_scale() {
+            return;
+        }
+        if new_scale <= self.max_scale {
+            self.viewport.scale = new_scale;
 
-impl RendererRunner {
-    pub fn new() -> Result<Self, Error> {
-        let event_loop = EventLoop::new();
-        let input = WinitInputHelper::new();
-        let window = Self::create_window(&event_loop);
-        let args = Args::default();
-        let pixels = Self::create_pixels(&window, &args)?;
-        let renderer = Renderer::new();
+            if self.scan_config.enabled {
+                self.scan_level = 0;
+            }
+        }
+    }
 
-        Ok(Self {
-            event_loop,
-            window,
-            pixels,
-            renderer,
-            input,
-            args,
-        })
+    pub fn max_scale(&self) -> f64 {
+        self.max_scale
     }
 
-    fn create_window(event_loop: &EventLoop<()>) -> winit::window::Window {
-        let size = LogicalSize::new(800.0, 600.0);
-        WindowBuilder::new()
-            .with_title("Fractal Renderer")
-            .with_inner_size(size)
-            .with_min_inner_size(size)
-            .build(event_loop)
-            .unwrap()
+    /// Sets the widest view zooming out goes to, in units across the
+    /// smaller side of the frame.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` isn't finite and greater than 0.
+    pub fn set_max_scale(&mut self, scale: f64) {
+        if !(scale.is_finite() && scale > 0.0) {
+            panic!("Max scale must be greater than 0");
+        }
+        self.max_scale = scale;
     }
 
-    fn create_pixels(window: &winit::window::Window, args: &Args) -> Result<Pixels, Error> {
-        let window_size = window.inner_size();
-        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, window);
-        Pixels::new(args.get_width(), args.get_height(), surface_texture)
+    /// The deepest scale zooming in goes to around the current center,
+    /// before neighbouring pixels blur together in `f64`.
+    pub fn min_scale(&self) -> f64 {
+        let magnitude = self
+            .viewport
+            .center_x
+            .abs()
+            .max(self.viewport.center_y.abs())
+            .max(1.0);
+        let pixels = self.viewport.width.min(self.viewport.height).max(1) as f64;
+        magnitude * f64::EPSILON * 4.0 * pixels
     }
 
-    pub fn with_args(mut self, args: Args) -> Self {
-        // Update renderer configuration
-        self.renderer.max_iterations = args.get_max_iterations();
-        self.renderer.scan_config = args.get_scan_config();
+    pub fn pan_speed(&self) -> f64 {
+        self.pan_speed
+    }
 
-        // Check if window size needs to be updated
-        let current_size = self.window.inner_size();
-        let new_width = args.get_width();
-        let new_height = args.get_height();
+    /// Sets the fraction of the view [`Self::pan`] moves by per unit.
+    ///
+    /// # Panics
+    ///
+    /// If `speed` isn't finite and greater than 0.
+    pub fn set_pan_speed(&mut self, speed: f64) {
+        if !(speed.is_finite() && speed > 0.0) {
+            panic!("Pan speed must be greater than 0");
+        }
+        self.pan_speed = speed;
+    }
 
-        if current_size.width != new_width || current_size.height != new_height {
-            // Resize the window
-            self.window
-                .set_inner_size(LogicalSize::new(new_width as f64, new_height as f64));
+    pub fn zoom_step(&self) -> f64 {
+        self.zoom_step
+    }
 
-            // Recreate pixels with new dimensions
-            self.pixels = Self::create_pixels(&self.window, &args)
-                .expect("Failed to create pixels with new dimensions");
+    /// Sets the factor the scale changes by per notch of the mouse wheel.
+    /// Held zoom keys speed up or slow down in proportion.
+    ///
+    /// # Panics
+    ///
+    /// If `step` isn't between 0 and 1, exclusive, which would zoom the
+    /// wrong way or not at all.
+    pub fn set_zoom_step(&mut self, step: f64) {
+        if !(step > 0.0 && step < 1.0) {
+            panic!("Zoom step must be between 0 and 1");
+        }
+        self.zoom_step = step;
+    }
 
-            self.renderer.width = new_width;
-            self.renderer.height = new_height;
+    /// Centers the view on the point under the pixel `(px, py)`, then zooms
+    /// by `factor` like [`Self::zoom`].
+    pub fn zoom_at(&mut self, px: f64, py: f64, factor: f64) {
+        let target = self.pixel_to_complex(px, py);
+        self.viewport.center_x = target.re;
+        self.viewport.center_y = target.im;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
         }
+        self.zoom(factor);
+    }
 
-        // Update stored args
-        self.args = args;
+    /// Zooms by `factor` like [`Self::zoom`], keeping the point under the
+    /// pixel `(px, py)` where it is on screen.
+    pub fn zoom_towards(&mut self, px: f64, py: f64, factor: f64) {
+        let target = self.pixel_to_complex(px, py);
+        let scale = self.viewport.scale;
+        self.zoom(factor);
 
-        self
+        let center = target + (self.viewport.center() - target) * (self.viewport.scale / scale);
+        self.viewport.center_x = center.re;
+        self.viewport.center_y = center.im;
     }
 
-    pub fn run(self) -> Result<(), Error> {
-        let RendererRunner {
-            event_loop,
-            window,
-            mut pixels,
-            mut renderer,
-            mut input,
-            args: _
-        } = self;
+    /// Zooms so the box between the frame pixels `(px0, py0)` and
+    /// `(px1, py1)`, in either order, fills the view: its center becomes the
+    /// view's, and it just fits along its longer side relative to the frame.
+    /// Boxes less than a pixel across are ignored.
+    pub fn zoom_to_rect(&mut self, px0: f64, py0: f64, px1: f64, py1: f64) {
+        let box_width = (px1 - px0).abs();
+        let box_height = (py1 - py0).abs();
+        if box_width < 1.0 || box_height < 1.0 {
+            return;
+        }
 
-        // Initial render
-        renderer.render(pixels.frame_mut());
-        pixels.render()?;
+        let center = self.pixel_to_complex((px0 + px1) / 2.0, (py0 + py1) / 2.0);
+        self.viewport.center_x = center.re;
+        self.viewport.center_y = center.im;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+        self.zoom(
+            (box_width / self.viewport.width as f64).max(box_height / self.viewport.height as f64),
+        );
+    }
 
-        event_loop.run(move |event, _, control_flow| {
-            input.update(&event);
+    pub fn width(&self) -> u32 {
+        self.viewport.width
+    }
 
-            if input.key_pressed(VirtualKeyCode::Escape) {
-                *control_flow = ControlFlow::Exit;
-                return;
-            }
+    pub fn height(&self) -> u32 {
+        self.viewport.height
+    }
 
-            Self::handle_input(&mut renderer, &input, &mut pixels, &window);
+    /// Renders `width` by `height` pixels from now on, keeping the center and
+    /// scale, and restarts the progressive render to fill the new size.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.viewport = self.viewport.with_size(width, height);
+        self.frame_on_gpu = false;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
 
-            // Handle window events
-            match event {
-                Event::WindowEvent { event, .. } => match event {
-                    winit::event::WindowEvent::CloseRequested => {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                    winit::event::WindowEvent::Resized(_) => {
-                        window.request_redraw();
-                    }
-                    _ => {}
-                },
-                Event::RedrawRequested(_) => {
-                    if renderer.is_scanning() {
-                        renderer.render(pixels.frame_mut());
-                        pixels.render().expect("pixels.render() failed");
-                        // Request another redraw if still scanning
-                        window.request_redraw();
-                    }
-                }
-                Event::MainEventsCleared => {
-                    // Request redraw during scanning
-                    if renderer.is_scanning() {
-                        window.request_redraw();
-                    }
-                }
-                Event::LoopDestroyed => {
-                    *control_flow = ControlFlow::Exit;
-                }
-                _ => {}
-            }
-        })
+    pub fn backend(&self) -> RenderBackend {
+        self.backend
     }
 
-    fn handle_input(
-        renderer: &mut Renderer,
-        input: &WinitInputHelper,
-        pixels: &mut Pixels,
-        window: &winit::window::Window,
-    ) {
-        let mut needs_update = false;
+    /// Forces a specific kernel, e.g. to benchmark it against the others.
+    /// Returns `false` and keeps the current one if the CPU can't run it.
+    pub fn set_backend(&mut self, backend: RenderBackend) -> bool {
+        if !backend.is_supported() {
+            return false;
+        }
 
-        // Handle panning
-        let mut pan_x = 0.0;
-        let mut pan_y = 0.0;
-        if input.key_held(VirtualKeyCode::Left) {
-            pan_x -= 0.05;
+        self.backend = backend;
+        true
+    }
+
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    /// Sets the iteration limit, at least 1. With auto iterations on, the
+    /// limit at the default scale moves along with it.
+    pub fn set_max_iterations(&mut self, max_iterations: u32) {
+        let max_iterations = max_iterations.max(1);
+        if self.auto_iterations {
+            let extra = self.max_iterations.saturating_sub(self.base_iterations);
+            self.base_iterations = max_iterations.saturating_sub(extra).max(1);
+        } else {
+            self.base_iterations = max_iterations;
         }
-        if input.key_held(VirtualKeyCode::Right) {
-            pan_x += 0.05;
+        self.max_iterations = max_iterations;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
         }
-        if input.key_held(VirtualKeyCode::Up) {
-            pan_y -= 0.05;
+    }
+
+    /// Switches between a fixed iteration limit and one that grows by
+    /// `ITERATIONS_PER_OCTAVE` for every halving of the scale, starting from
+    /// the current limit at the default scale. Turning it off goes back to
+    /// that starting limit.
+    pub fn toggle_auto_iterations(&mut self) {
+        self.auto_iterations = !self.auto_iterations;
+        if self.auto_iterations {
+            self.base_iterations = self.max_iterations;
+            self.update_auto_iterations();
+        } else if self.max_iterations != self.base_iterations {
+            self.max_iterations = self.base_iterations;
+            if self.scan_config.enabled {
+                self.scan_level = 0;
+            }
         }
-        if input.key_held(VirtualKeyCode::Down) {
-            pan_y += 0.05;
+    }
+
+    pub fn is_auto_iterations(&self) -> bool {
+        self.auto_iterations
+    }
+
+    /// Sets the iteration limit to just enough for the current view, and
+    /// returns it. Starting from the lowest of [`AUTO_TUNE_LIMITS`], the
+    /// limit is doubled for as long as no pixels escape within it, or
+    /// doubling it lets more than [`AUTO_TUNE_THRESHOLD`] of the pixels
+    /// escape that didn't before.
+    /// The trial renders are of a copy of the view at most
+    /// [`AUTO_TUNE_SIZE`] pixels across, so this takes far less than a full
+    /// render at the final limit.
+    pub fn auto_tune_iterations(&mut self) -> u32 {
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let shrink = (AUTO_TUNE_SIZE as f64 / width.max(height) as f64).min(1.0);
+        let size = |side: u32| ((side as f64 * shrink).round() as u32).max(1);
+        let view = self.viewport.with_size(size(width), size(height));
+        let pixels = (view.width * view.height) as f64;
+
+        let current = self.max_iterations;
+        let (mut limit, highest) = AUTO_TUNE_LIMITS;
+        while limit < highest {
+            // The trial at twice the limit also tells which pixels escape within it
+            let next = limit * 2;
+            self.max_iterations = next;
+            let escapes = self.trace_region(&view);
+            let escaped = escapes.iter().filter(|escape| escape.escaped);
+            let (early, late) = escaped.fold((0, 0), |(early, late), escape| {
+                match escape.iterations < limit {
+                    true => (early + 1, late),
+                    false => (early, late + 1),
+                }
+            });
+            // Deep in, nothing may escape at all yet, which resolves nothing
+            if early > 0 && (late as f64) < AUTO_TUNE_THRESHOLD * pixels {
+                break;
+            }
+            limit = next;
         }
+        self.max_iterations = current;
 
-        if pan_x != 0.0 || pan_y != 0.0 {
-            renderer.pan(pan_x, pan_y);
-            needs_update = true;
+        self.set_max_iterations(limit);
+        limit
+    }
+
+    /// Brings the iteration limit in line with the zoom depth when auto
+    /// iterations are on. A changed limit changes the colors of pixels that
+    /// are already drawn, so the progressive render starts over.
+    fn update_auto_iterations(&mut self) {
+        // The auto-zoom scales the iterations itself
+        if !self.auto_iterations || self.auto_zoom.is_some() {
+            return;
         }
 
-        // Handle zooming
-        if input.key_held(VirtualKeyCode::PageUp) {
-            renderer.zoom(0.9);
-            needs_update = true;
+        let octaves = self.magnification().log2().max(0.0);
+        let max_iterations = self.base_iterations + (octaves * ITERATIONS_PER_OCTAVE) as u32;
+        if max_iterations != self.max_iterations {
+            self.max_iterations = max_iterations;
+            if self.scan_config.enabled {
+                self.scan_level = 0;
+            }
         }
-        if input.key_held(VirtualKeyCode::PageDown) {
-            renderer.zoom(1.1);
-            needs_update = true;
+    }
+
+    /// How far the view is zoomed in relative to the default scale.
+    pub fn magnification(&self) -> f64 {
+        DEFAULT_SCALE / self.viewport.scale
+    }
+
+    /// Jumps straight to the given magnification, keeping the center fixed.
+    pub fn set_magnification(&mut self, magnification: f64) {
+        if !magnification.is_finite() || magnification <= 0.0 {
+            eprintln!("Ignoring invalid magnification {magnification}");
+            return;
         }
 
-        // Handle color scheme changes
-        if input.key_pressed(VirtualKeyCode::Key1) {
-            renderer.change_color_scheme(ColorScheme::Smooth);
-            needs_update = true;
+        self.viewport.scale = DEFAULT_SCALE / magnification;
+        if self.is_precision_exhausted() {
+            eprintln!(
+                "Warning: magnification {magnification:e} exceeds f64 precision, the image will break up"
+            );
         }
-        if input.key_pressed(VirtualKeyCode::Key2) {
-            renderer.change_color_scheme(ColorScheme::Zebra);
-            needs_update = true;
+
+        if self.scan_config.enabled {
+            self.scan_level = 0;
         }
-        if input.key_pressed(VirtualKeyCode::Key3) {
-            renderer.change_color_scheme(ColorScheme::Red);
+    }
+
+    /// Whether neighbouring pixels are closer together than `f64` can resolve
+    /// around the current center.
+    fn is_precision_exhausted(&self) -> bool {
+        self.viewport.scale < self.min_scale()
+    }
+
+    /// Whether zooming in by another step would go past [`Self::min_scale`],
+    /// so [`Self::zoom`] refuses to. The HUD and the window title say so, as
+    /// the view can't get any sharper from here.
+    pub fn is_at_precision_limit(&self) -> bool {
+        self.viewport.scale * self.zoom_step < self.min_scale()
+    }
+
+    /// Draws the view into `frame`, or the next part of it while scanning.
+    /// Setting the flag of [`Self::cancel_handle`] meanwhile stops it early.
+    /// The rows or tiles drawn so far are left in the frame over the previous
+    /// view, and the next render starts over.
+    pub fn render(&mut self, frame: &mut [u8]) {
+        self.rendering = true;
+        self.render_view(frame);
+        self.rendering = false;
+
+        self.cancelled = self.cancel.swap(false, Ordering::Relaxed);
+        if self.cancelled && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// A flag that stops the render in progress, or the next one if none is,
+    /// once set. It's cleared again when that render returns. The window
+    /// renders full frames on a thread of their own, and sets it as soon as
+    /// the view is panned or zoomed away from the one being rendered.
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel)
+    }
+
+    /// A copy to render the view on another thread, with a cancel flag of
+    /// its own so stopping it leaves this renderer's alone.
+    fn detached(&self) -> Self {
+        Self {
+            cancel: Arc::new(AtomicBool::new(false)),
+            ..self.clone()
+        }
+    }
+
+    /// Takes over what rendering on `done`, a [`Self::detached`] copy,
+    /// worked out about the view, as if it had rendered here.
+    fn adopt_render(&mut self, done: Renderer) {
+        self.max_iterations = done.max_iterations;
+        self.gradient_lut = done.gradient_lut;
+        self.escape_cache = done.escape_cache;
+        self.accumulator = done.accumulator;
+        self.accumulated_samples = done.accumulated_samples;
+        self.cancelled = done.cancelled;
+        self.render_time = done.render_time;
+        self.back_buffer.clear();
+        self.frame_on_gpu = false;
+    }
+
+    /// Whether the last [`Self::render`] was stopped through
+    /// [`Self::cancel_handle`].
+    pub fn was_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Whether the render in progress should stop, checked between rows and
+    /// tiles.
+    fn is_cancel_requested(&self) -> bool {
+        self.rendering && self.cancel.load(Ordering::Relaxed)
+    }
+
+    fn render_view(&mut self, frame: &mut [u8]) {
+        self.update_auto_iterations();
+        self.update_gradient_lut();
+
+        if let Some(compare_scheme) = self.compare_scheme {
+            self.render_split(frame, compare_scheme);
+            self.add_glow(frame);
+            self.draw_overlays(frame);
+            return;
+        }
+        if self.scan_level == 0 {
+            self.accumulated_samples = 0;
+        }
+
+        // Changes that only affect the colors, like switching the scheme,
+        // are redrawn from the cached escapes at once rather than rescanned,
+        // and so are pans, which only need the exposed edges iterated
+        if self.scan_level == 0 && self.uses_escape_cache() {
+            self.shift_escape_cache();
+        }
+        if self.scan_level == 0 && self.recolor(frame) {
+            self.finish_scan();
+            self.add_glow(frame);
+            self.draw_overlays(frame);
+            return;
+        }
+
+        // Every auto-zoom frame moves the view, so there is no time to refine
+        if !self.scan_config.enabled || self.auto_zoom.is_some() {
+            // Regular rendering without scanning
+            self.render_full(frame);
+            self.add_glow(frame);
+            self.draw_overlays(frame);
+            return;
+        }
+
+        let stride = self.scan_stride();
+        if stride < 1 {
+             // All passes completed, so any further frames add temporal samples
+            if self.is_accumulating() {
+                self.accumulate(frame);
+                self.add_glow(frame);
+                self.draw_overlays(frame);
+            }
+            return;
+        }
+
+        // Level 0 is the coarse pass, drawn whole in one call so that every
+        // change shows its new geometry at once. The fine passes after it
+        // are drawn a slice of rows at a time from `scan_row` on, for at
+        // most `SCAN_SLICE_BUDGET` per call, so input is handled between
+        // slices. Input that changes the view goes back to level 0, which
+        // drops the unfinished fine pass but never the coarse one. The glow
+        // is only added to whole passes, as every pixel of it has to be
+        // drawn anew for the glow not to pile up.
+        if self.scan_level == 0 {
+            self.scan_row = 0;
+            self.scan_escapes = None;
+            if stride == 1 {
+                // The only pass covers every pixel, so it can use the row kernels
+                self.render_full(frame);
+            } else {
+                let height = self.viewport.height as usize;
+                self.render_with_stride(frame, stride, 0..height);
+            }
+            self.scan_level += 1;
+            self.add_glow(frame);
+        } else if self.refine(frame, stride) {
+            self.scan_level += 1;
+            self.scan_row = 0;
+            self.add_glow(frame);
+        }
+        self.draw_overlays(frame);
+    }
+
+    /// Draws slices of the fine scan pass with `stride` until it's done,
+    /// returning `true`, or [`SCAN_SLICE_BUDGET`] is spent. Slices of the
+    /// final pass are traced into `scan_escapes` rather than drawn when the
+    /// escapes are cached, so the previous pass stays on screen until the
+    /// whole frame can be colored from them.
+    fn refine(&mut self, frame: &mut [u8], stride: u32) -> bool {
+        let start = Instant::now();
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+        let cached = stride == 1 && self.uses_escape_cache();
+        if stride == 1 && !cached && self.adaptive_aa && self.aa_samples > 1 {
+            // Finding the edges needs the whole frame
+            self.render_full(frame);
+            return true;
+        }
+        if cached
+            && !self
+                .scan_escapes
+                .as_ref()
+                .is_some_and(|escapes| self.is_current(escapes))
+        {
+            // Starts tracing from the top, also when the view changed
+            // without restarting the scan, as a drag does
+            self.scan_row = 0;
+            self.scan_escapes = Some(EscapeCache {
+                viewport: self.viewport,
+                fractal: self.fractal,
+                max_iterations: self.max_iterations,
+                bailout: self.bailout,
+                stripe_frequency: self.stripe_frequency_in_use(),
+                escapes: Vec::with_capacity(width * height),
+            });
+        }
+
+        let slice_rows = SCAN_SLICE_ROWS.div_ceil(stride as usize) * stride as usize;
+        loop {
+            let rows = self.scan_row..(self.scan_row + slice_rows).min(height);
+            if cached {
+                let traced = self.trace_rows(&self.viewport, rows.clone());
+                if self.is_cancel_requested() {
+                    return false;
+                }
+                if let Some(escapes) = &mut self.scan_escapes {
+                    escapes.escapes.extend(traced);
+                }
+            } else if stride == 1 {
+                self.render_rect(frame, 0, rows.start, width, rows.end);
+            } else {
+                self.render_with_stride(frame, stride, rows.clone());
+            }
+            self.scan_row = rows.end;
+
+            if rows.end >= height {
+                if cached {
+                    self.escape_cache = self.scan_escapes.take();
+                    self.render_full(frame);
+                }
+                return true;
+            }
+            if start.elapsed() >= SCAN_SLICE_BUDGET {
+                return false;
+            }
+        }
+    }
+
+    /// Whether [`Self::render`] adds temporal samples once the scan is done,
+    /// see [`Self::set_temporal_aa`].
+    fn is_accumulating(&self) -> bool {
+        self.temporal_aa
+            && self.scan_config.enabled
+            && self.accumulated_samples < TEMPORAL_AA_SAMPLES
+            && self.aa_samples == 1
+            && self.compare_scheme.is_none()
+            && self.auto_zoom.is_none()
+            && !self.palette_cycling
+            // Equalized colors depend on the whole frame
+            && !matches!(self.color_scheme, ColorScheme::Histogram)
+    }
+
+    /// Renders one more sample of every pixel, each at the same offset from
+    /// the pixel's own point, and draws the average of all samples so far.
+    /// The offsets follow the R2 sequence, which starts at the pixel's point
+    /// and keeps filling the gaps between the earlier ones.
+    fn accumulate(&mut self, frame: &mut [u8]) {
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+        if self.accumulated_samples == 0 {
+            self.accumulator.clear();
+            self.accumulator.resize(width * height, [0.0; 3]);
+        }
+
+        let sample = self.accumulated_samples as f64;
+        let offset = |alpha: f64| (0.5 + sample * alpha).fract() - 0.5;
+        let pixel_size = self.viewport.pixel_size();
+        // Steps of the reciprocals of the plastic number and its square
+        let shift_x = offset(0.754_877_666_246_692_7) * pixel_size;
+        let shift_y = offset(0.569_840_290_998_053_2) * pixel_size;
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re + shift_x)
+            .collect();
+
+        // Averaged in linear light unless gamma correction is off
+        let gamma = if self.gamma_correction {
+            self.gamma
+        } else {
+            1.0
+        };
+        let samples = self.accumulated_samples + 1;
+        let mut accumulator = std::mem::take(&mut self.accumulator);
+        frame
+            .par_chunks_exact_mut(width * 4)
+            .zip(accumulator.par_chunks_exact_mut(width))
+            .enumerate()
+            .for_each(|(y, (row, sums))| {
+                let imag = self.pixel_to_complex(0.0, y as f64).im + shift_y;
+                let mut escapes = vec![EscapeResult::default(); width];
+                let mut channels = vec![[0.0; 3]; width];
+                self.channels_row(&reals, imag, &mut escapes, &mut channels);
+
+                for ((pixel, sum), channels) in row.chunks_exact_mut(4).zip(sums).zip(&channels) {
+                    for (sum, channel) in sum.iter_mut().zip(channels) {
+                        *sum += (channel / 255.0).powf(gamma) as f32;
+                    }
+                    let [r, g, b] = sum.map(|channel| {
+                        ((channel as f64 / samples as f64).powf(gamma.recip()) * 255.0 + 1e-6) as u8
+                    });
+                    pixel.copy_from_slice(&[r, g, b, 255]);
+                }
+            });
+        self.accumulator = accumulator;
+        self.accumulated_samples = samples;
+    }
+
+    /// Renders the view and presents it, on the GPU if [`Self::render_gpu`]
+    /// can. A scan with [`ScanConfig::double_buffered`] set is only
+    /// presented once a pass is whole, see [`Self::render_buffered`].
+    pub fn render_to(&mut self, pixels: &mut Pixels) -> Result<(), Error> {
+        let start = Instant::now();
+        if !self.render_gpu(pixels)? && self.render_buffered(pixels.frame_mut()) {
+            pixels.render()?;
+        }
+        self.render_time = start.elapsed();
+        Ok(())
+    }
+
+    /// Renders like [`Self::render`], but while double buffering a scan it
+    /// draws into the back buffer, and only copies it to `frame` once the
+    /// pass is whole or [`ScanConfig::present_interval`] has passed since
+    /// the last copy. Returns whether `frame` changed and is worth
+    /// presenting.
+    fn render_buffered(&mut self, frame: &mut [u8]) -> bool {
+        if !self.scan_config.double_buffered || !self.is_scanning() {
+            self.back_buffer.clear();
+            self.render(frame);
+            return true;
+        }
+
+        let mut back_buffer = std::mem::take(&mut self.back_buffer);
+        if back_buffer.len() != frame.len() {
+            back_buffer.clear();
+            back_buffer.extend_from_slice(frame);
+        }
+        self.render(&mut back_buffer);
+        // Every pass is finished before the next one starts at the top
+        let whole = self.scan_row == 0;
+        let present = whole || self.last_present.elapsed() >= self.scan_config.present_interval;
+        if present {
+            frame.copy_from_slice(&back_buffer);
+            self.last_present = Instant::now();
+        }
+        self.back_buffer = back_buffer;
+        present
+    }
+
+    /// How long the last [`Self::render_to`] took, presenting included. A
+    /// progressive render measures each pass, or slice of one, on its own.
+    pub fn render_time(&self) -> Duration {
+        self.render_time
+    }
+
+    /// Presents the frame buffer after an update like [`Self::drag`], or the
+    /// GPU's frame if [`Self::render_gpu`] can draw the view.
+    pub fn present(&mut self, pixels: &mut Pixels) -> Result<(), Error> {
+        if self.render_gpu(pixels)? {
+            return Ok(());
+        }
+        pixels.render()
+    }
+
+    /// Presents the view from the GPU set with [`Self::set_gpu`], if its
+    /// shader can draw it: the Mandelbrot set in the plain smooth scheme,
+    /// with no overlays, at a depth `f32` resolves. Otherwise returns
+    /// `Ok(false)` to leave presenting the frame buffer to the caller.
+    ///
+    /// Updates like [`Self::drag`] assume the frame buffer holds the
+    /// previous frame, which isn't the case after GPU frames, so the first
+    /// view the GPU can't draw is rendered and presented in full here.
+    pub fn render_gpu(&mut self, pixels: &mut Pixels) -> Result<bool, Error> {
+        if !self.gpu.as_ref().is_some_and(|gpu| gpu.fits(pixels)) {
+            return Ok(false);
+        }
+        if self.draw_gpu(pixels)? {
+            return Ok(true);
+        }
+
+        if !std::mem::take(&mut self.frame_on_gpu) {
+            return Ok(false);
+        }
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+        self.back_buffer.clear();
+        self.render(pixels.frame_mut());
+        pixels.render()?;
+        Ok(true)
+    }
+
+    /// Draws the view on the GPU if its shader can, without falling back to
+    /// the frame buffer like [`Self::render_gpu`]. Returns whether it did.
+    fn draw_gpu(&mut self, pixels: &mut Pixels) -> Result<bool, Error> {
+        let Some(gpu) = self.gpu.clone().filter(|gpu| gpu.fits(pixels)) else {
+            return Ok(false);
+        };
+        self.update_auto_iterations();
+
+        let plain_smooth = matches!(self.color_scheme, ColorScheme::Smooth)
+            && self.compare_scheme.is_none()
+            && self.aa_samples == 1
+            && self.color_jitter == 0.0
+            && self.tonemap == ToneMap::None
+            && !self.invert_palette
+            && !self.perceptual
+            && !self.glow_enabled
+            && self.interior_color == [0, 0, 0]
+            && !self.interior_shading
+            && self.palette_depth_coupling == 0.0
+            && self.color_offset == 0.0
+            && !self.palette_cycling;
+        if self.fractal == FractalKind::Mandelbrot
+            && self.custom_fractal.is_none()
+            && plain_smooth
+            && self.overlay_rects().is_empty()
+            && GpuRenderer::resolves(&self.viewport)
+        {
+            gpu.render(pixels, &self.viewport, self.max_iterations, self.bailout)?;
+            // A whole frame at once, so there is nothing to scan
+            self.finish_scan();
+            self.frame_on_gpu = true;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Hands the views the GPU can draw over to `gpu`, or takes them back
+    /// for `None`.
+    pub fn set_gpu(&mut self, gpu: Option<GpuRenderer>) {
+        self.gpu = gpu.map(Arc::new);
+        self.frame_on_gpu = false;
+    }
+
+    fn render_full(&mut self, frame: &mut [u8]) {
+        if self.uses_escape_cache() {
+            self.shift_escape_cache();
+            if self.cached_escapes().is_none() {
+                let escapes = self.trace_region(&self.viewport);
+                if self.is_cancel_requested() {
+                    // Leaves the previous frame rather than coloring a partial trace
+                    return;
+                }
+                self.escape_cache = Some(EscapeCache {
+                    viewport: self.viewport,
+                    fractal: self.fractal,
+                    max_iterations: self.max_iterations,
+                    bailout: self.bailout,
+                    stripe_frequency: self.stripe_frequency_in_use(),
+                    escapes,
+                });
+            }
+            if self.recolor(frame) {
+                return;
+            }
+        }
+        if self.adaptive_aa && self.aa_samples > 1 {
+            self.render_adaptive_aa(frame);
+            return;
+        }
+
+        self.render_tiles(frame, RENDER_TILE);
+    }
+
+    /// Marks the progressive render as done, for when the frame was drawn in
+    /// full some other way.
+    fn finish_scan(&mut self) {
+        if self.scan_config.enabled {
+            self.scan_level = self.scan_passes();
+        }
+    }
+
+    /// Passes of a whole progressive render, which is also the level at which
+    /// the stride reaches 0.
+    fn scan_passes(&self) -> u32 {
+        u32::BITS - self.scan_config.initial_stride.leading_zeros()
+    }
+
+    /// How far the progressive render has got, from 0 when it starts over to
+    /// 1 once the pass with stride 1 is done. Each pass counts the same, the
+    /// fine ones by the rows drawn so far. Always 1 with scanning off.
+    pub fn scan_progress(&self) -> f32 {
+        if !self.scan_config.enabled {
+            return 1.0;
+        }
+        // Level 0 is drawn in one go, `scan_row` only counts in the fine passes
+        let rows = match self.scan_level {
+            0 => 0.0,
+            _ => self.scan_row as f32 / self.viewport.height.max(1) as f32,
+        };
+        ((self.scan_level as f32 + rows) / self.scan_passes() as f32).min(1.0)
+    }
+
+    /// Whether the coloring only needs the escapes, so they go through the
+    /// cache and a later color change or pan can reuse them.
+    fn uses_escape_cache(&self) -> bool {
+        matches!(self.color_scheme, ColorScheme::Histogram)
+            || ((self.aa_samples == 1 || self.adaptive_aa)
+                && self.mode_for(self.color_scheme).is_some())
+    }
+
+    /// Moves the cached escapes along with a pan by whole pixels, iterating
+    /// only the edges the pan exposes. Leaves the cache alone unless it holds
+    /// the same view shifted by less than its size.
+    fn shift_escape_cache(&mut self) {
+        let view = self.viewport;
+        let Some(cache) = self.escape_cache.as_ref().filter(|cache| {
+            let old = cache.viewport;
+            old != view
+                && old.scale == view.scale
+                && (old.width, old.height) == (view.width, view.height)
+                && cache.fractal == self.fractal
+                && cache.max_iterations == self.max_iterations
+                && cache.bailout == self.bailout
+                && cache.stripe_frequency == self.stripe_frequency_in_use()
+        }) else {
+            return;
+        };
+
+        let width = view.width as usize;
+        let height = view.height as usize;
+        let pixel_size = view.pixel_size();
+        let dx = (cache.viewport.center_x - view.center_x) / pixel_size;
+        let dy = (cache.viewport.center_y - view.center_y) / pixel_size;
+        let (shift_x, shift_y) = (dx.round(), dy.round());
+        if (dx - shift_x).abs() > 1e-6
+            || (dy - shift_y).abs() > 1e-6
+            || shift_x.abs() >= width as f64
+            || shift_y.abs() >= height as f64
+        {
+            return;
+        }
+        let (shift_x, shift_y) = (shift_x as isize, shift_y as isize);
+
+        // Columns of each row the pan brings into view
+        let (x0, x1) = if shift_x >= 0 {
+            (0, shift_x as usize)
+        } else {
+            (width - shift_x.unsigned_abs(), width)
+        };
+        let reals: Vec<f64> = (0..width)
+            .map(|x| view.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+        let mut escapes = vec![EscapeResult::default(); width * height];
+        escapes
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let imag = view.pixel_to_complex(0.0, y as f64).im;
+                let source_y = y as isize - shift_y;
+                if source_y < 0 || source_y >= height as isize {
+                    self.iterations_row(&reals, imag, row);
+                    return;
+                }
+
+                let source = &cache.escapes[source_y as usize * width..][..width];
+                if shift_x >= 0 {
+                    row[x1..].copy_from_slice(&source[..width - x1]);
+                } else {
+                    row[..x0].copy_from_slice(&source[width - x0..]);
+                }
+                self.iterations_row(&reals[x0..x1], imag, &mut row[x0..x1]);
+            });
+
+        self.escape_cache = Some(EscapeCache {
+            viewport: view,
+            fractal: self.fractal,
+            max_iterations: self.max_iterations,
+            bailout: self.bailout,
+            stripe_frequency: self.stripe_frequency_in_use(),
+            escapes,
+        });
+    }
+
+    /// The escapes of the last full render, if it was of the current view
+    /// with the current fractal settings.
+    fn cached_escapes(&self) -> Option<&[EscapeResult]> {
+        self.escape_cache
+            .as_ref()
+            .filter(|cache| self.is_current(cache))
+            .map(|cache| cache.escapes.as_slice())
+    }
+
+    /// Whether escapes were computed for the current view with the current
+    /// fractal settings.
+    fn is_current(&self, cache: &EscapeCache) -> bool {
+        cache.viewport == self.viewport
+            && cache.fractal == self.fractal
+            && cache.max_iterations == self.max_iterations
+            && cache.bailout == self.bailout
+            && cache.stripe_frequency == self.stripe_frequency_in_use()
+    }
+
+    /// Colors the whole frame from the cached escapes without iterating any
+    /// points. Returns `false`, leaving the frame alone, when there is no
+    /// usable cache or the color scheme needs more than the escapes.
+    fn recolor(&self, frame: &mut [u8]) -> bool {
+        match self.cached_escapes() {
+            Some(escapes) => self.color_escapes(frame, escapes),
+            None => false,
+        }
+    }
+
+    /// Renders the current view like a full render, but skips iterating the
+    /// inside of tiles whose border lies entirely in the set, see
+    /// [`Self::trace_region`].
+    pub fn render_tiled(&self, frame: &mut [u8]) {
+        if !self.color_escapes(frame, &self.trace_region(&self.viewport)) {
+            self.render_rect(
+                frame,
+                0,
+                0,
+                self.viewport.width as usize,
+                self.viewport.height as usize,
+            );
+        }
+    }
+
+    /// Looks up the colors of the red, green, blue and grayscale gradients
+    /// by iteration count rather than dividing by the limit for every pixel.
+    /// Rebuilt here whenever the scheme or limit changed since, until then
+    /// the colors are computed as usual.
+    fn update_gradient_lut(&mut self) {
+        let mode = match self.color_mode() {
+            Some(
+                mode @ (ColorMode::Red
+                | ColorMode::Blue
+                | ColorMode::GreenGradient
+                | ColorMode::SingleHue(_)
+                | ColorMode::BlackAndWhite),
+            ) => mode,
+            _ => {
+                self.gradient_lut = None;
+                return;
+            }
+        };
+        if self
+            .gradient_lut
+            .as_ref()
+            .is_some_and(|lut| lut.mode == mode && lut.max_iterations == self.max_iterations)
+        {
+            return;
+        }
+
+        let colors = (0..self.max_iterations)
+            .map(|iterations| color_map_value(iterations as f64, self.max_iterations, mode))
+            .collect();
+        self.gradient_lut = Some(GradientLut {
+            mode,
+            max_iterations: self.max_iterations,
+            colors,
+        });
+    }
+
+    /// Colors the whole frame from the escapes of all of its pixels. Returns
+    /// `false`, leaving the frame alone, when the color scheme needs more
+    /// than the escapes.
+    fn color_escapes(&self, frame: &mut [u8], escapes: &[EscapeResult]) -> bool {
+        if self.compare_scheme.is_some() {
+            return false;
+        }
+        if matches!(self.color_scheme, ColorScheme::Histogram) {
+            self.color_histogram(frame, escapes);
+            return true;
+        }
+        let Some(mode) = self.mode_for(self.color_scheme) else {
+            return false;
+        };
+        if self.aa_samples > 1 {
+            return false;
+        }
+
+        self.colorize_frame(frame, escapes, mode);
+        true
+    }
+
+    /// Colors every pixel of the frame from its escape with `mode`.
+    fn colorize_frame(&self, frame: &mut [u8], escapes: &[EscapeResult], mode: ColorMode) {
+        let width = self.viewport.width as usize;
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        frame
+            .par_chunks_exact_mut(width * 4)
+            .zip(escapes.par_chunks(width))
+            .enumerate()
+            .for_each(|(y, (row, escapes))| {
+                let imag = self.pixel_to_complex(0.0, y as f64).im;
+                let mut channels = vec![[0.0; 3]; width];
+                self.colorize_row(mode, &reals, imag, escapes, &mut channels);
+
+                for (pixel, channels) in row.chunks_exact_mut(4).zip(&channels) {
+                    let [r, g, b] = channels.map(|channel| channel as u8);
+                    pixel.copy_from_slice(&[r, g, b, 255]);
+                }
+            });
+    }
+
+    /// Renders the current view with supersampling, but only where it
+    /// shows: pixels are colored from their escapes first, and only those
+    /// whose escape differs from a neighbour's by more than
+    /// [`ADAPTIVE_AA_THRESHOLD`] are sampled again on an `aa_samples` by
+    /// `aa_samples` grid. Reuses the cached escapes of the view if there
+    /// are any.
+    ///
+    /// Schemes that need more than the escapes, and the split view, are
+    /// supersampled everywhere instead.
+    pub fn render_adaptive_aa(&self, frame: &mut [u8]) {
+        let mode = self
+            .mode_for(self.color_scheme)
+            .filter(|_| self.compare_scheme.is_none());
+        let Some(mode) = mode else {
+            self.render_rect(
+                frame,
+                0,
+                0,
+                self.viewport.width as usize,
+                self.viewport.height as usize,
+            );
+            return;
+        };
+
+        let traced;
+        let escapes = match self.cached_escapes() {
+            Some(escapes) => escapes,
+            None => {
+                traced = self.trace_region(&self.viewport);
+                if self.is_cancel_requested() {
+                    return;
+                }
+                &traced
+            }
+        };
+        self.colorize_frame(frame, escapes, mode);
+        if self.aa_samples > 1 {
+            self.supersample_edges(frame, escapes);
+        }
+    }
+
+    /// Supersamples the pixels of the frame at edges between escapes, see
+    /// [`Self::render_adaptive_aa`].
+    fn supersample_edges(&self, frame: &mut [u8], escapes: &[EscapeResult]) {
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+        let iterations = |x: usize, y: usize| escapes[y * width + x].iterations;
+
+        frame
+            .par_chunks_exact_mut(width * 4)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let edges: Vec<usize> = (0..width)
+                    .filter(|&x| {
+                        let own = iterations(x, y);
+                        let differs = |other: u32| own.abs_diff(other) > ADAPTIVE_AA_THRESHOLD;
+                        (x > 0 && differs(iterations(x - 1, y)))
+                            || (x + 1 < width && differs(iterations(x + 1, y)))
+                            || (y > 0 && differs(iterations(x, y - 1)))
+                            || (y + 1 < height && differs(iterations(x, y + 1)))
+                    })
+                    .collect();
+                if edges.is_empty() {
+                    return;
+                }
+
+                let imag = self.pixel_to_complex(0.0, y as f64).im;
+                let edge_reals: Vec<f64> = edges.iter().map(|&x| reals[x]).collect();
+                let mut colors = vec![0; edges.len() * 4];
+                self.color_row_supersampled(&edge_reals, imag, &mut colors);
+                for (&x, color) in edges.iter().zip(colors.chunks_exact(4)) {
+                    row[x * 4..x * 4 + 4].copy_from_slice(color);
+                }
+            });
+    }
+
+    /// The iteration count of every pixel of the current view, row by row,
+    /// for tools that want the raw counts rather than colors. Reuses the
+    /// cached escapes when they are up to date.
+    pub fn iteration_counts(&self) -> Vec<u32> {
+        self.iteration_counts_cancellable(&AtomicBool::new(false))
+            .expect("counting can't be cancelled")
+    }
+
+    /// [`Self::iteration_counts`], giving up with `None` as soon as `cancel`
+    /// is set. It is checked between bands of tiles, so the counts are the
+    /// same as tracing the whole view at once.
+    pub(crate) fn iteration_counts_cancellable(&self, cancel: &AtomicBool) -> Option<Vec<u32>> {
+        let counts = |escapes: &[EscapeResult]| {
+            escapes
+                .iter()
+                .map(|escape| escape.iterations)
+                .collect::<Vec<_>>()
+        };
+        if let Some(escapes) = self.cached_escapes() {
+            return Some(counts(escapes));
+        }
+
+        let height = self.viewport.height as usize;
+        let mut all = Vec::with_capacity(self.viewport.width as usize * height);
+        for start in (0..height).step_by(TRACE_TILE) {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let rows = start..(start + TRACE_TILE).min(height);
+            all.extend(counts(&self.trace_rows(&self.viewport, rows)));
+        }
+        Some(all)
+    }
+
+    /// Renders the current view with histogram equalization: escaped points
+    /// are colored by their rank among all escaped points in the frame
+    /// rather than by their raw iteration count, so the palette is spread
+    /// evenly over what is actually on screen. Interior points stay black and
+    /// aren't counted.
+    ///
+    /// Paths that color a row at a time, like the split view, the loupe and
+    /// exports, can't see the whole frame and use the plain iteration count.
+    pub fn render_histogram(&self, frame: &mut [u8]) {
+        match self.cached_escapes() {
+            Some(escapes) => self.color_histogram(frame, escapes),
+            None => self.color_histogram(frame, &self.trace_region(&self.viewport)),
+        }
+    }
+
+    /// Colors the frame from the escapes of all of its pixels, see
+    /// [`Self::render_histogram`].
+    fn color_histogram(&self, frame: &mut [u8], escapes: &[EscapeResult]) {
+        let ranks = histogram_ranks(escapes, self.max_iterations);
+        let max_iterations = self.max_iterations as f64;
+        let width = self.viewport.width as usize;
+
+        frame
+            .par_chunks_exact_mut(4)
+            .zip(escapes)
+            .enumerate()
+            .for_each(|(index, (pixel, escape))| {
+                let [r, g, b] = if escape.escaped {
+                    let mut value = ranks[escape.iterations as usize] * max_iterations;
+                    if self.invert_palette {
+                        value = max_iterations - value;
+                    }
+                    if self.color_offset != 0.0 {
+                        value = (value / max_iterations + self.color_offset).rem_euclid(1.0)
+                            * max_iterations;
+                    }
+                    color_map_value(value, self.max_iterations, ColorMode::Histogram)
+                        .map(|channel| channel as u8)
+                } else {
+                    let point =
+                        self.pixel_to_complex((index % width) as f64, (index / width) as f64);
+                    self.interior_channels(escape, point)
+                        .map(|channel| channel as u8)
+                };
+                pixel.copy_from_slice(&[r, g, b, 255]);
+            });
+    }
+
+    /// Renders the whole view like [`Self::render_rect`], but in squares of
+    /// `tile_size` pixels rather than whole rows. The set's interior takes
+    /// far longer per pixel than the outside, and the smaller pieces let idle
+    /// threads take over work from the busy ones.
+    pub fn render_tiles(&self, frame: &mut [u8], tile_size: usize) {
+        let width = self.viewport.width as usize;
+        let tile_size = tile_size.max(1);
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        frame
+            .par_chunks_mut(width * 4 * tile_size)
+            .enumerate()
+            .for_each(|(band_index, band)| {
+                let y0 = band_index * tile_size;
+                let rows = band.len() / (width * 4);
+                // Cancelled tiles are left out whole
+                let tiles: Vec<Option<(usize, Vec<u8>)>> = (0..width)
+                    .step_by(tile_size)
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+                    .map(|x0| {
+                        let reals = &reals[x0..(x0 + tile_size).min(width)];
+                        let mut tile = vec![0; reals.len() * 4 * rows];
+                        for (row_index, row) in tile.chunks_exact_mut(reals.len() * 4).enumerate() {
+                            if self.is_cancel_requested() {
+                                return None;
+                            }
+                            let imag = self.pixel_to_complex(0.0, (y0 + row_index) as f64).im;
+                            if self.aa_samples > 1 {
+                                self.color_row_supersampled(reals, imag, row);
+                            } else {
+                                self.color_row(reals, imag, row);
+                            }
+                        }
+                        Some((x0, tile))
+                    })
+                    .collect();
+
+                for (x0, tile) in tiles.into_iter().flatten() {
+                    let tile_bytes = tile.len() / rows;
+                    for (row, tile_row) in band
+                        .chunks_exact_mut(width * 4)
+                        .zip(tile.chunks_exact(tile_bytes))
+                    {
+                        row[x0 * 4..x0 * 4 + tile_bytes].copy_from_slice(tile_row);
+                    }
+                }
+            });
+    }
+
+    /// Renders the pixels in `x0..x1` by `y0..y1` of the current view into the frame.
+    fn render_rect(&self, frame: &mut [u8], x0: usize, y0: usize, x1: usize, y1: usize) {
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let row_bytes = self.viewport.width as usize * 4;
+        let reals: Vec<f64> = (x0..x1)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        frame[y0 * row_bytes..y1 * row_bytes]
+            .par_chunks_exact_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                if self.is_cancel_requested() {
+                    return;
+                }
+                let imag = self.pixel_to_complex(0.0, (y0 + row_index) as f64).im;
+                let row = &mut row[x0 * 4..x1 * 4];
+                if self.aa_samples > 1 {
+                    self.color_row_supersampled(&reals, imag, row);
+                } else {
+                    self.color_row(&reals, imag, row);
+                }
+            });
+    }
+
+    /// Renders the whole view into each half of the frame, colored with the
+    /// current scheme on the left and `compare_scheme` on the right. The
+    /// iterations are only computed once and then colored twice.
+    fn render_split(&mut self, frame: &mut [u8], compare_scheme: ColorScheme) {
+        let width = self.viewport.width as usize;
+        let half = width / 2;
+        if half == 0 {
+            return self.render_full(frame);
+        }
+
+        let side_view = self.viewport.with_size(half as u32, self.viewport.height);
+        let reals: Vec<f64> = (0..half)
+            .map(|x| side_view.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+        let schemes = [self.color_scheme, compare_scheme];
+
+        frame
+            .par_chunks_exact_mut(width * 4)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let imag = self.pixel_to_complex(0.0, y as f64).im;
+                let mut escapes = vec![EscapeResult::default(); half];
+                let mut channels = vec![[0.0; 3]; half];
+                self.iterations_row(&reals, imag, &mut escapes);
+
+                for (side, scheme) in schemes.into_iter().enumerate() {
+                    match self.mode_for(scheme) {
+                        Some(mode) => {
+                            self.colorize_row(mode, &reals, imag, &escapes, &mut channels)
+                        }
+                        None => {
+                            // These schemes need the orbit itself, so that side is computed again
+                            let mut orbit_escapes = vec![EscapeResult::default(); half];
+                            self.orbit_row(scheme, &reals, imag, &mut orbit_escapes, &mut channels);
+                        }
+                    }
+
+                    let side_pixels = &mut row[side * half * 4..(side + 1) * half * 4];
+                    for (pixel, channels) in side_pixels.chunks_exact_mut(4).zip(&channels) {
+                        let [r, g, b] = channels.map(|channel| channel as u8);
+                        pixel.copy_from_slice(&[r, g, b, 255]);
+                    }
+                }
+
+                // Divide the halves, and blank the odd column out if there is one
+                row[half * 4..half * 4 + 4].copy_from_slice(&[255, 255, 255, 255]);
+                for pixel in row[half * 8..].chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&[0, 0, 0, 255]);
+                }
+            });
+    }
+
+    /// Draws `rows` of the frame in blocks of `stride` by `stride` pixels,
+    /// each colored by its top left pixel. `rows` starts on a block.
+    fn render_with_stride(&self, frame: &mut [u8], stride: u32, rows: Range<usize>) {
+        let width = self.viewport.width as usize;
+        let stride = stride as usize;
+
+        // Each band of `stride` rows is sampled along its top row and filled
+        // in blocks. The last band and column of blocks may be cut short.
+        frame[rows.start * width * 4..rows.end * width * 4]
+            .par_chunks_mut(width * 4 * stride)
+            .enumerate()
+            .for_each(|(band, rows_of_band)| {
+                if self.is_cancel_requested() {
+                    return;
+                }
+                let y = (band * stride + rows.start) as f64;
+                let reals: Vec<f64> = (0..width)
+                    .step_by(stride)
+                    .map(|x| self.viewport.pixel_to_complex(x as f64, y).re)
+                    .collect();
+                let imag = self.viewport.pixel_to_complex(0.0, y).im;
+
+                let mut colors = vec![0; reals.len() * 4];
+                self.color_row(&reals, imag, &mut colors);
+
+                for row in rows_of_band.chunks_mut(width * 4) {
+                    for (block, color) in row.chunks_mut(stride * 4).zip(colors.chunks_exact(4)) {
+                        for pixel in block.chunks_exact_mut(4) {
+                            pixel.copy_from_slice(color);
+                        }
+                    }
+                }
+            });
+    }
+
+    /// Maps a pixel position in the frame to its point on the complex plane.
+    pub fn pixel_to_complex(&self, x: f64, y: f64) -> Complex64 {
+        self.viewport.pixel_to_complex(x, y)
+    }
+
+    /// Computes the escape of every pixel of an arbitrary view.
+    fn compute_region(&self, view: &Viewport) -> Vec<EscapeResult> {
+        self.compute_rows(view, 0..view.height as usize)
+    }
+
+    /// Computes the escapes of `rows` of a view, see [`Self::compute_region`].
+    fn compute_rows(&self, view: &Viewport, rows: Range<usize>) -> Vec<EscapeResult> {
+        let width = view.width as usize;
+        let mut escapes = vec![EscapeResult::default(); width * rows.len()];
+
+        let reals: Vec<f64> = (0..width)
+            .map(|x| view.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        escapes
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                if self.is_cancel_requested() {
+                    return;
+                }
+                let imag = view.pixel_to_complex(0.0, (rows.start + y) as f64).im;
+                self.iterations_row(&reals, imag, row);
+            });
+
+        escapes
+    }
+
+    /// Computes the escape of every pixel of a view like
+    /// [`Self::compute_region`], but only iterates the border of each tile
+    /// at first. A tile whose border is entirely inside the set is filled as
+    /// interior, others are split into quarters and traced again.
+    ///
+    /// The Mandelbrot set and the filled Julia sets have no holes, so a
+    /// closed loop of interior points only surrounds more interior. Other
+    /// fractals can have holes and are computed in full.
+    fn trace_region(&self, view: &Viewport) -> Vec<EscapeResult> {
+        self.trace_rows(view, 0..view.height as usize)
+    }
+
+    /// Traces the escapes of `rows` of a view, see [`Self::trace_region`].
+    /// `rows` starts on a tile, so the tiles are the same as the whole view's.
+    fn trace_rows(&self, view: &Viewport, rows: Range<usize>) -> Vec<EscapeResult> {
+        if self.custom_fractal.is_some()
+            || !matches!(
+                self.fractal,
+                FractalKind::Mandelbrot | FractalKind::Julia { .. }
+            )
+        {
+            return self.compute_rows(view, rows);
+        }
+
+        let width = view.width as usize;
+        let height = view.height as usize;
+        let reals: Vec<f64> = (0..width)
+            .map(|x| view.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+        let imags: Vec<f64> = (0..height)
+            .map(|y| view.pixel_to_complex(0.0, y as f64).im)
+            .collect();
+
+        let tiles: Vec<(usize, usize)> = rows
+            .clone()
+            .step_by(TRACE_TILE)
+            .flat_map(|y0| (0..width).step_by(TRACE_TILE).map(move |x0| (x0, y0)))
+            .collect();
+        let traced: Vec<_> = tiles
+            .par_iter()
+            .map(|&(x0, y0)| {
+                let x1 = (x0 + TRACE_TILE).min(width);
+                let y1 = (y0 + TRACE_TILE).min(rows.end);
+                if self.is_cancel_requested() {
+                    // Filled in with anything, the caller throws it away
+                    return vec![Some(EscapeResult::default()); (x1 - x0) * (y1 - y0)];
+                }
+                let mut tile = TracedTile {
+                    reals: &reals[x0..x1],
+                    imags: &imags[y0..y1],
+                    width: x1 - x0,
+                    escapes: vec![None; (x1 - x0) * (y1 - y0)],
+                };
+                self.trace_tile(&mut tile, (0, 0), (x1 - x0, y1 - y0));
+                tile.escapes
+            })
+            .collect();
+
+        let mut escapes = vec![EscapeResult::default(); width * rows.len()];
+        for (&(x0, y0), tile) in tiles.iter().zip(traced) {
+            let tile_width = (x0 + TRACE_TILE).min(width) - x0;
+            for (row_index, row) in tile.chunks_exact(tile_width).enumerate() {
+                let start = (y0 - rows.start + row_index) * width + x0;
+                for (escape, traced) in escapes[start..start + tile_width].iter_mut().zip(row) {
+                    *escape = traced.expect("every pixel of a traced tile is filled in");
+                }
+            }
+        }
+        escapes
+    }
+
+    /// Traces the part `(x0, y0)..(x1, y1)` of a tile, see [`Self::trace_region`].
+    fn trace_tile(
+        &self,
+        tile: &mut TracedTile,
+        (x0, y0): (usize, usize),
+        (x1, y1): (usize, usize),
+    ) {
+        let mut border_interior = true;
+        for x in x0..x1 {
+            for y in [y0, y1 - 1] {
+                border_interior &= !self.tile_escape(tile, x, y).escaped;
+            }
+        }
+        for y in y0..y1 {
+            for x in [x0, x1 - 1] {
+                border_interior &= !self.tile_escape(tile, x, y).escaped;
+            }
+        }
+
+        if border_interior {
+            let interior = EscapeResult {
+                iterations: self.max_iterations,
+                escaped: false,
+                final_z: Complex64::default(),
+                stripe: 0.0,
+            };
+            for y in y0..y1 {
+                for escape in &mut tile.escapes[y * tile.width + x0..y * tile.width + x1] {
+                    escape.get_or_insert(interior);
+                }
+            }
+        } else if x1 - x0 <= TRACE_MIN_TILE || y1 - y0 <= TRACE_MIN_TILE {
+            // Whole rows at a time, so the vectorized kernels can be used
+            let mut row = vec![EscapeResult::default(); x1 - x0];
+            for y in y0..y1 {
+                self.iterations_row(&tile.reals[x0..x1], tile.imags[y], &mut row);
+                for (escape, &computed) in tile.escapes[y * tile.width + x0..].iter_mut().zip(&row)
+                {
+                    escape.get_or_insert(computed);
+                }
+            }
+        } else {
+            let (xm, ym) = ((x0 + x1) / 2, (y0 + y1) / 2);
+            self.trace_tile(tile, (x0, y0), (xm, ym));
+            self.trace_tile(tile, (xm, y0), (x1, ym));
+            self.trace_tile(tile, (x0, ym), (xm, y1));
+            self.trace_tile(tile, (xm, ym), (x1, y1));
+        }
+    }
+
+    /// The escape of one pixel of a tile, iterating it only the first time.
+    fn tile_escape(&self, tile: &mut TracedTile, x: usize, y: usize) -> EscapeResult {
+        let index = y * tile.width + x;
+        if let Some(escape) = tile.escapes[index] {
+            return escape;
+        }
+
+        let mut escape = [EscapeResult::default()];
+        self.iterations_row(&tile.reals[x..=x], tile.imags[y], &mut escape);
+        tile.escapes[index] = Some(escape[0]);
+        escape[0]
+    }
+
+    /// Renders the current view into a new RGBA buffer of any size, leaving
+    /// the frame alone. Overlays are not included.
+    pub fn render_to_buffer(&self, width: u32, height: u32) -> Vec<u8> {
+        self.render_region(&self.viewport.with_size(width, height))
+    }
+
+    /// [`Self::render_to_buffer`] into a `buffer` the caller allocated,
+    /// giving up with `None` as soon as `cancel` is set.
+    pub(crate) fn render_to_buffer_cancellable(
+        &self,
+        width: u32,
+        height: u32,
+        buffer: &mut [u8],
+        cancel: &AtomicBool,
+    ) -> Option<()> {
+        self.render_region_into(&self.viewport.with_size(width, height), buffer, cancel)
+    }
+
+    /// Renders an arbitrary view into a new RGBA buffer without touching the frame.
+    pub fn render_region(&self, view: &Viewport) -> Vec<u8> {
+        let mut buffer = vec![0; frame_buffer_len(view.width, view.height, 4)];
+        self.render_region_into(view, &mut buffer, &AtomicBool::new(false))
+            .expect("render can't be cancelled");
+        buffer
+    }
+
+    fn render_region_into(
+        &self,
+        view: &Viewport,
+        buffer: &mut [u8],
+        cancel: &AtomicBool,
+    ) -> Option<()> {
+        let width = view.width as usize;
+        let reals: Vec<f64> = (0..width)
+            .map(|x| view.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        buffer
+            .par_chunks_mut(width * 4)
+            .enumerate()
+            .try_for_each(|(y, row)| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+
+                let imag = view.pixel_to_complex(0.0, y as f64).im;
+                self.color_row(&reals, imag, row);
+                Some(())
+            })
+    }
+
+    /// Renders the current view with 16 bits per channel and saves it as a PNG.
+    ///
+    /// Useful for the gradient-heavy color schemes, where 8 bits per channel
+    /// shows visible banding.
+    pub fn export_png16<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        let mut buffer = vec![0; frame_buffer_len(self.viewport.width, self.viewport.height, 3)];
+        self.render_rgb16(&mut buffer, &AtomicBool::new(false))
+            .expect("render can't be cancelled");
+
+        image::save_buffer(
+            path,
+            bytemuck::cast_slice(&buffer),
+            self.viewport.width,
+            self.viewport.height,
+            image::ExtendedColorType::Rgb16,
+        )
+    }
+
+    /// Renders the current view into an RGB `buffer` with 16 bits per
+    /// channel. Gives up with `None` as soon as `cancel` is set.
+    pub(crate) fn render_rgb16(&self, buffer: &mut [u16], cancel: &AtomicBool) -> Option<()> {
+        let width = self.viewport.width as usize;
+
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        buffer
+            .par_chunks_mut(width * 3)
+            .enumerate()
+            .try_for_each(|(y, row)| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+
+                let imag = self.pixel_to_complex(0.0, y as f64).im;
+                let mut escapes = vec![EscapeResult::default(); width];
+                let mut channels = vec![[0.0; 3]; width];
+                self.channels_row(&reals, imag, &mut escapes, &mut channels);
+
+                for (pixel, channels) in row.chunks_exact_mut(3).zip(&channels) {
+                    pixel.copy_from_slice(&channels.map(|channel| (channel * 257.0) as u16));
+                }
+                Some(())
+            })
+    }
+
+    /// Renders the current view off-screen into a new RGBA buffer, measuring
+    /// the render as it goes.
+    pub fn render_headless(&self) -> (Vec<u8>, RenderStats) {
+        let start = Instant::now();
+        let width = self.viewport.width as usize;
+        let mut buffer = vec![0; frame_buffer_len(self.viewport.width, self.viewport.height, 4)];
+
+        let reals: Vec<f64> = (0..width)
+            .map(|x| self.pixel_to_complex(x as f64, 0.0).re)
+            .collect();
+
+        let (total_iterations, interior) = buffer
+            .par_chunks_mut(width * 4)
+            .enumerate()
+            .map(|(y, row)| {
+                let imag = self.pixel_to_complex(0.0, y as f64).im;
+                let mut escapes = vec![EscapeResult::default(); width];
+                let mut channels = vec![[0.0; 3]; width];
+                self.channels_row(&reals, imag, &mut escapes, &mut channels);
+
+                for (pixel, channels) in row.chunks_exact_mut(4).zip(&channels) {
+                    let [r, g, b] = channels.map(|channel| channel as u8);
+                    pixel.copy_from_slice(&[r, g, b, 255]);
+                }
+
+                let total: u64 = escapes.iter().map(|escape| escape.iterations as u64).sum();
+                let interior = escapes.iter().filter(|escape| !escape.escaped).count();
+                (total, interior)
+            })
+            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+        let upper_left = self.pixel_to_complex(0.0, 0.0);
+        let lower_right =
+            self.pixel_to_complex(self.viewport.width as f64, self.viewport.height as f64);
+        let stats = RenderStats {
+            version: STATS_VERSION,
+            view: ViewStats {
+                center_re: self.viewport.center_x,
+                center_im: self.viewport.center_y,
+                scale: self.viewport.scale,
+                width: self.viewport.width,
+                height: self.viewport.height,
+                upper_left: [upper_left.re, upper_left.im],
+                lower_right: [lower_right.re, lower_right.im],
+            },
+            max_iterations: self.max_iterations,
+            color_scheme: format!("{:?}", self.color_scheme),
+            total_iterations,
+            render_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+            interior_fraction: interior as f64 / (width * self.viewport.height as usize) as f64,
+        };
+
+        (buffer, stats)
+    }
+
+    /// Renders the current view off-screen and saves it as a PNG, optionally
+    /// writing a JSON sidecar next to it describing how it was made.
+    pub fn render_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        write_stats: bool,
+    ) -> Result<RenderStats, Box<dyn std::error::Error>> {
+        let (buffer, stats) = self.render_headless();
+        save_png(&path, &buffer, self.viewport.width, self.viewport.height)?;
+
+        if write_stats {
+            stats.write_sidecar(&path)?;
+        }
+        Ok(stats)
+    }
+
+    /// Times `iterations` full renders of the current view into an
+    /// off-screen frame. Every render starts from scratch rather than from
+    /// the escapes the previous one cached.
+    pub fn bench(&self, iterations: usize) -> Duration {
+        let mut renderer = self.clone();
+        let mut frame = vec![0; frame_buffer_len(self.viewport.width, self.viewport.height, 4)];
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            renderer.escape_cache = None;
+            renderer.render_full(&mut frame);
+        }
+        start.elapsed()
+    }
+
+    /// Renders a zoom into `(target_x, target_y)` as `frames` PNGs named
+    /// `frame_0000.png`, `frame_0001.png`, ... in `out_dir`, for turning into
+    /// a video with e.g. ffmpeg. The scale shrinks by the same factor every
+    /// frame, going from `start_scale` to `end_scale`. Frames are the size
+    /// of the current view and use its coloring; no window is needed.
+    pub fn render_zoom_sequence<P: AsRef<Path>>(
+        &self,
+        target_x: f64,
+        target_y: f64,
+        start_scale: f64,
+        end_scale: f64,
+        frames: u32,
+        out_dir: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let out_dir = out_dir.as_ref();
+        std::fs::create_dir_all(out_dir)?;
+
+        let mut renderer = self.clone();
+        let target = Complex64::new(target_x, target_y);
+        let steps = frames.saturating_sub(1).max(1) as f64;
+        for frame in 0..frames {
+            let progress = frame as f64 / steps;
+            let scale = start_scale * (end_scale / start_scale).powf(progress);
+            renderer.set_view(target, scale);
+
+            let path = out_dir.join(format!("frame_{frame:04}.png"));
+            let buffer = renderer.render_to_buffer(self.viewport.width, self.viewport.height);
+            save_png(&path, &buffer, self.viewport.width, self.viewport.height)?;
+            println!("Rendered frame {}/{frames} at scale {scale:e}", frame + 1);
+        }
+        Ok(())
+    }
+
+    /// Pans to the tile of the view with the most varied iteration counts and
+    /// zooms in a step. Tiles that are mostly interior are skipped, so the
+    /// view doesn't drift into the solid black of the set. Returns `false`
+    /// when no tile has any detail worth moving to.
+    pub fn focus_on_detail(&mut self) -> bool {
+        let Some(target) = self.find_detail() else {
+            return false;
+        };
+
+        self.viewport.center_x = target.re;
+        self.viewport.center_y = target.im;
+        self.zoom(DETAIL_ZOOM);
+        true
+    }
+
+    /// The center of the most detailed tile of the view, see [`Self::focus_on_detail`].
+    fn find_detail(&self) -> Option<Complex64> {
+        // A downsampled pass is plenty to compare tiles
+        let width = (self.viewport.width / DETAIL_DOWNSAMPLE).max(DETAIL_TILES as u32) as usize;
+        let height = (self.viewport.height / DETAIL_DOWNSAMPLE).max(DETAIL_TILES as u32) as usize;
+        let escapes = self.compute_region(&self.viewport.with_size(width as u32, height as u32));
+
+        let tile_width = width / DETAIL_TILES;
+        let tile_height = height / DETAIL_TILES;
+        let mut best: Option<(f64, usize, usize)> = None;
+
+        for tile_y in 0..DETAIL_TILES {
+            for tile_x in 0..DETAIL_TILES {
+                let mut count = 0.0;
+                let mut interior = 0.0;
+                let mut sum = 0.0;
+                let mut sum_sq = 0.0;
+
+                for y in tile_y * tile_height..(tile_y + 1) * tile_height {
+                    for x in tile_x * tile_width..(tile_x + 1) * tile_width {
+                        let escape = escapes[y * width + x];
+                        let value = escape.iterations;
+                        count += 1.0;
+                        if !escape.escaped {
+                            interior += 1.0;
+                        }
+                        sum += value as f64;
+                        sum_sq += value as f64 * value as f64;
+                    }
+                }
+
+                if interior / count > 0.5 {
+                    continue;
+                }
+
+                let mean = sum / count;
+                let variance = sum_sq / count - mean * mean;
+                if variance > 0.0
+                    && best.is_none_or(|(best_variance, _, _)| variance > best_variance)
+                {
+                    best = Some((variance, tile_x, tile_y));
+                }
+            }
+        }
+
+        let (_, tile_x, tile_y) = best?;
+        let x =
+            (tile_x as f64 + 0.5) * tile_width as f64 * self.viewport.width as f64 / width as f64;
+        let y = (tile_y as f64 + 0.5) * tile_height as f64 * self.viewport.height as f64
+            / height as f64;
+        Some(self.pixel_to_complex(x, y))
+    }
+
+    /// Starts zooming from the full set towards the next well-known deep
+    /// zoom location, or stops a running auto-zoom.
+    pub fn toggle_auto_zoom(&mut self) {
+        if self.stop_auto_zoom() {
+            return;
+        }
+
+        self.start_auto_zoom_home();
+    }
+
+    /// Starts zooming from the current view into its most detailed part.
+    /// Returns `false` when there is no detail to head for.
+    pub fn auto_zoom_into_detail(&mut self) -> bool {
+        self.stop_auto_zoom();
+        let Some(target) = self.find_detail() else {
+            return false;
+        };
+
+        self.auto_zoom = Some(AutoZoom {
+            target,
+            base_iterations: self.max_iterations,
+        });
+        true
+    }
+
+    pub fn is_auto_zooming(&self) -> bool {
+        self.auto_zoom.is_some()
+    }
+
+    /// Advances the auto-zoom by `elapsed` seconds: the scale shrinks while
+    /// the center closes in on the target, and the iteration limit grows with
+    /// the depth. Once `f64` runs out of precision it starts over from the
+    /// full set with the next target.
+    pub fn step_auto_zoom(&mut self, elapsed: f64) {
+        let Some(auto_zoom) = &self.auto_zoom else {
+            return;
+        };
+        let target = auto_zoom.target;
+        let base_iterations = auto_zoom.base_iterations;
+
+        let factor = AUTO_ZOOM_RATE.powf(elapsed.clamp(0.0, AUTO_ZOOM_MAX_STEP));
+        // Pulling the center in faster than the scale shrinks moves the
+        // target towards the middle of the view
+        let center = self.viewport.center();
+        let center = target + (center - target) * factor * factor;
+        self.set_view(center, self.viewport.scale * factor);
+
+        let octaves = self.magnification().log2().max(0.0);
+        self.max_iterations = base_iterations + (octaves * ITERATIONS_PER_OCTAVE) as u32;
+
+        if self.is_precision_exhausted() {
+            self.max_iterations = base_iterations;
+            self.start_auto_zoom_home();
+        }
+    }
+
+    /// Starts or keeps up a smooth zoom in or out of the center, for as long
+    /// as the zoom key is held.
+    pub fn hold_zoom(&mut self, zoom_in: bool) {
+        self.stop_auto_zoom();
+        // As many wheel notches per second as the default rate takes
+        let rate = HELD_ZOOM_RATE.powf(self.zoom_step.ln() / WHEEL_ZOOM.ln());
+        let held_rate = if zoom_in { rate } else { 1.0 / rate };
+        let target_scale = self
+            .animation
+            .as_ref()
+            .map_or(self.viewport.scale, |animation| animation.target_scale);
+        self.animation = Some(AnimationState {
+            target_scale,
+            held_rate: Some(held_rate),
+        });
+    }
+
+    /// Lets go of the zoom key: the zoom eases out and then stops.
+    pub fn release_zoom(&mut self) {
+        if let Some(animation) = &mut self.animation {
+            animation.held_rate = None;
+        }
+    }
+
+    /// Keeps up the inertial pan in the direction `dx`/`dy`, each -1, 0 or
+    /// 1, for as long as the pan keys are held. It builds up speed while
+    /// they are, and once 0 in both lets go of them the view glides to a
+    /// stop, see [`Self::step_animation`].
+    pub fn hold_pan(&mut self, dx: f64, dy: f64) {
+        self.held_pan = (dx, dy);
+    }
+
+    /// Velocity of the inertial pan, in units of [`Self::pan`] per second.
+    pub fn pan_velocity(&self) -> (f64, f64) {
+        (self.pan_velocity_x, self.pan_velocity_y)
+    }
+
+    pub fn is_pan_inertia(&self) -> bool {
+        self.pan_inertia
+    }
+
+    /// Whether the pan keys go through [`Self::hold_pan`], or move the view
+    /// by a fixed step per update and stop the moment they are released.
+    /// Turning it off stops a glide in progress.
+    pub fn set_pan_inertia(&mut self, inertia: bool) {
+        self.pan_inertia = inertia;
+        if !inertia {
+            self.pan_velocity_x = 0.0;
+            self.pan_velocity_y = 0.0;
+            self.held_pan = (0.0, 0.0);
+            self.pan_remainder = (0.0, 0.0);
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+            || self.held_pan != (0.0, 0.0)
+            || self.pan_velocity_x != 0.0
+            || self.pan_velocity_y != 0.0
+    }
+
+    /// Advances the smooth zoom and the inertial pan by `elapsed` seconds.
+    /// The scale follows its target with some easing, so starting and
+    /// stopping don't jerk, and every step restarts the progressive render.
+    pub fn step_animation(&mut self, elapsed: f64) {
+        let elapsed = elapsed.clamp(0.0, AUTO_ZOOM_MAX_STEP);
+        self.step_pan(elapsed);
+
+        let min_scale = self.min_scale();
+        let Some(animation) = &mut self.animation else {
+            return;
+        };
+
+        if let Some(rate) = animation.held_rate {
+            let target_scale = animation.target_scale * rate.powf(elapsed);
+            if target_scale < min_scale && animation.target_scale >= min_scale {
+                eprintln!("Warning: f64 precision runs out here, not zooming in further");
+            }
+            animation.target_scale = target_scale.min(self.max_scale).max(min_scale);
+        }
+        // Ease in log space, so zooming in and out feel the same
+        let remaining = animation.target_scale / self.viewport.scale;
+        self.viewport.scale *= remaining.powf(1.0 - (-ZOOM_EASING * elapsed).exp());
+
+        if animation.held_rate.is_none()
+            && (self.viewport.scale / animation.target_scale).ln().abs() < 1e-3
+        {
+            self.viewport.scale = animation.target_scale;
+            self.animation = None;
+        }
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Eases the pan's velocity towards the held direction, or towards
+    /// standing still once the keys are released, and moves the view by it.
+    /// The view only moves by whole pixels, so the escapes can be shifted
+    /// along, and the rest is carried over to the next step.
+    fn step_pan(&mut self, elapsed: f64) {
+        let ease = |velocity: f64, held: f64| {
+            let rate = if held == 0.0 {
+                PAN_FRICTION
+            } else {
+                PAN_ACCELERATION
+            };
+            let velocity =
+                velocity + (held * HELD_PAN_RATE - velocity) * (1.0 - (-rate * elapsed).exp());
+            velocity.clamp(-HELD_PAN_RATE, HELD_PAN_RATE)
+        };
+        let (held_x, held_y) = self.held_pan;
+        self.pan_velocity_x = ease(self.pan_velocity_x, held_x);
+        self.pan_velocity_y = ease(self.pan_velocity_y, held_y);
+        if self.held_pan == (0.0, 0.0)
+            && self.pan_velocity_x.hypot(self.pan_velocity_y) < PAN_STOP_SPEED
+        {
+            self.pan_velocity_x = 0.0;
+            self.pan_velocity_y = 0.0;
+            self.pan_remainder = (0.0, 0.0);
+            return;
+        }
+
+        let pixels = self.viewport.scale * self.pan_speed / self.viewport.pixel_size();
+        let dx = self.pan_remainder.0 - self.pan_velocity_x * elapsed * pixels;
+        let dy = self.pan_remainder.1 - self.pan_velocity_y * elapsed * pixels;
+        self.pan_remainder = (dx - dx.round(), dy - dy.round());
+        if dx.round() != 0.0 || dy.round() != 0.0 {
+            self.pan_pixels(dx.round(), dy.round());
+        }
+    }
+
+    /// Starts or stops moving the colors through the palette over time.
+    pub fn toggle_palette_cycling(&mut self) {
+        self.palette_cycling = !self.palette_cycling;
+    }
+
+    pub fn is_cycling_palette(&self) -> bool {
+        self.palette_cycling
+    }
+
+    /// Advances the palette cycle by `elapsed` seconds and redraws the frame
+    /// with the shifted colors. The escapes of the last full render are
+    /// reused where possible, so only the coloring is redone.
+    pub fn cycle_palette(&mut self, frame: &mut [u8], elapsed: f64) {
+        self.back_buffer.clear();
+        let elapsed = elapsed.clamp(0.0, AUTO_ZOOM_MAX_STEP);
+        self.color_offset = (self.color_offset + elapsed * PALETTE_CYCLE_SPEED).rem_euclid(1.0);
+
+        // An unfinished scan picks the new colors up in its next pass
+        if self.is_scanning() {
+            return self.render(frame);
+        }
+        if !self.recolor(frame) {
+            match self.compare_scheme {
+                Some(compare_scheme) => self.render_split(frame, compare_scheme),
+                None => self.render_full(frame),
+            }
+        }
+        self.add_glow(frame);
+        self.draw_overlays(frame);
+    }
+
+    fn start_auto_zoom_home(&mut self) {
+        let (center, scale) = self.fractal.default_view();
+        self.set_view(center, scale);
+
+        // The famous locations are all in the Mandelbrot set
+        let target = match self.fractal {
+            FractalKind::Mandelbrot => {
+                let (re, im) = AUTO_ZOOM_TARGETS[self.next_auto_zoom_target];
+                self.next_auto_zoom_target =
+                    (self.next_auto_zoom_target + 1) % AUTO_ZOOM_TARGETS.len();
+                Complex64::new(re, im)
+            }
+            _ => self.find_detail().unwrap_or(center),
+        };
+
+        self.auto_zoom = Some(AutoZoom {
+            target,
+            base_iterations: self.max_iterations,
+        });
+    }
+
+    /// Stops the auto-zoom, restoring the iteration limit it started with.
+    /// Returns whether one was running.
+    fn stop_auto_zoom(&mut self) -> bool {
+        let Some(auto_zoom) = self.auto_zoom.take() else {
+            return false;
+        };
+
+        self.max_iterations = auto_zoom.base_iterations;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+        true
+    }
+
+    pub fn toggle_loupe(&mut self) {
+        self.loupe_enabled = !self.loupe_enabled;
+        // The loupe covers part of the frame, so it has to be rendered again
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_loupe_enabled(&self) -> bool {
+        self.loupe_enabled
+    }
+
+    /// Follows the mouse, for the overlays that show the point under it.
+    pub fn set_cursor(&mut self, x: f64, y: f64) {
+        self.cursor = Some((x, y));
+    }
+
+    /// Moves the cursor and updates the overlays that depend on it, rendering
+    /// the fractal again where the previous orbit was drawn.
+    pub fn move_cursor(&mut self, frame: &mut [u8], x: f64, y: f64) {
+        let previous_orbit = self.orbit_rect();
+        self.set_cursor(x, y);
+        self.back_buffer.clear();
+
+        if self.compare_scheme.is_some() || matches!(self.color_scheme, ColorScheme::Histogram) {
+            // The split view doesn't map onto the frame pixel for pixel, and
+            // equalized colors depend on the whole frame
+            self.render(frame);
+            return;
+        }
+        match previous_orbit {
+            Some(rect) => self.redraw_rects(frame, &[rect]),
+            None => self.draw_overlays(frame),
+        }
+    }
+
+    /// Draws a rubber band from `from` to `to`, in frame pixels, over the
+    /// frame, rendering the fractal again where the previous one was. Zoom
+    /// into it with [`Self::zoom_to_rect`].
+    pub fn select(&mut self, frame: &mut [u8], from: (f64, f64), to: (f64, f64)) {
+        let previous = self.selection_rects();
+        self.selection = Some((from, to));
+        self.redraw_rects(frame, &previous);
+    }
+
+    /// Takes the rubber band of [`Self::select`] off the frame again.
+    pub fn clear_selection(&mut self, frame: &mut [u8]) {
+        let previous = self.selection_rects();
+        self.selection = None;
+        self.redraw_rects(frame, &previous);
+    }
+
+    /// The rubber band's corners, ordered and clamped to the frame.
+    fn selection_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let ((x0, y0), (x1, y1)) = self.selection?;
+        let max_x = self.viewport.width.saturating_sub(1) as f64;
+        let max_y = self.viewport.height.saturating_sub(1) as f64;
+        let x = |x: f64| x.round().clamp(0.0, max_x) as usize;
+        let y = |y: f64| y.round().clamp(0.0, max_y) as usize;
+        Some((x(x0.min(x1)), y(y0.min(y1)), x(x0.max(x1)), y(y0.max(y1))))
+    }
+
+    /// The four one pixel wide edges of the rubber band.
+    fn selection_rects(&self) -> Vec<(usize, usize, usize, usize)> {
+        let Some((x0, y0, x1, y1)) = self.selection_box() else {
+            return Vec::new();
+        };
+        vec![
+            (x0, y0, x1 + 1, y0 + 1),
+            (x0, y1, x1 + 1, y1 + 1),
+            (x0, y0, x0 + 1, y1 + 1),
+            (x1, y0, x1 + 1, y1 + 1),
+        ]
+    }
+
+    pub fn draw_selection(&self, frame: &mut [u8]) {
+        let Some((x0, y0, x1, y1)) = self.selection_box() else {
+            return;
+        };
+        let width = self.viewport.width as usize;
+        let [x0, y0, x1, y1] = [x0, y0, x1, y1].map(|corner| corner as f64);
+        for (from, to) in [
+            ((x0, y0), (x1, y0)),
+            ((x0, y1), (x1, y1)),
+            ((x0, y0), (x0, y1)),
+            ((x1, y0), (x1, y1)),
+        ] {
+            overlay::draw_line(frame, width, from, to, [255, 255, 255]);
+        }
+    }
+
+    /// Renders the fractal again in `rects` of the frame, then the overlays.
+    fn redraw_rects(&mut self, frame: &mut [u8], rects: &[(usize, usize, usize, usize)]) {
+        self.back_buffer.clear();
+        if self.compare_scheme.is_some()
+            || matches!(self.color_scheme, ColorScheme::Histogram)
+            || self.glow_enabled
+        {
+            // The split view doesn't map onto the frame pixel for pixel, and
+            // equalized colors and the glow depend on the whole frame
+            if !self.recolor(frame) {
+                self.render_full(frame);
+            }
+            self.add_glow(frame);
+        } else {
+            for &(x0, y0, x1, y1) in rects {
+                self.render_rect(frame, x0, y0, x1, y1);
+            }
+        }
+        self.draw_overlays(frame);
+    }
+
+    pub fn toggle_orbit(&mut self) {
+        self.orbit_enabled = !self.orbit_enabled;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_orbit_enabled(&self) -> bool {
+        self.orbit_enabled
+    }
+
+    /// Frame positions of the orbit of the point under the cursor.
+    fn orbit_points(&self) -> Vec<(f64, f64)> {
+        if !self.orbit_enabled {
+            return Vec::new();
+        }
+        let Some((x, y)) = self.cursor.filter(|_| self.custom_fractal.is_none()) else {
+            return Vec::new();
+        };
+
+        let c = self.pixel_to_complex(x, y);
+        let max_points = self.max_iterations.min(ORBIT_MAX_POINTS);
+        // Julia and Newton orbits don't pass through the point itself, so start there
+        let start = matches!(
+            self.fractal,
+            FractalKind::Julia { .. } | FractalKind::Newton
+        )
+        .then_some(c);
+        start
+            .into_iter()
+            .chain(self.fractal.orbit(c.re, c.im, max_points, self.bailout))
+            .map(|z| self.viewport.complex_to_pixel(z))
+            .collect()
+    }
+
+    /// The part of the frame the orbit is drawn over, if any.
+    fn orbit_rect(&self) -> Option<(usize, usize, usize, usize)> {
+        let points = self.orbit_points();
+        let (first, rest) = points.split_first()?;
+        let (mut x0, mut y0, mut x1, mut y1) = (first.0, first.1, first.0, first.1);
+        for &(x, y) in rest {
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+
+        // Line pixels are rounded, so leave a pixel of slack
+        let clamp = |value: f64, limit: u32| value.clamp(0.0, limit as f64) as usize;
+        Some((
+            clamp(x0 - 1.0, self.viewport.width),
+            clamp(y0 - 1.0, self.viewport.height),
+            clamp(x1 + 2.0, self.viewport.width),
+            clamp(y1 + 2.0, self.viewport.height),
+        ))
+    }
+
+    /// Draws the orbit of the point under the cursor as a polyline.
+    pub fn draw_orbit(&self, frame: &mut [u8]) {
+        let width = self.viewport.width as usize;
+        let mut previous = None;
+        for point in self.orbit_points() {
+            if let Some(from) = previous {
+                overlay::draw_line(frame, width, from, point, [255, 255, 0]);
+            }
+            previous = Some(point);
+        }
+    }
+
+    pub fn toggle_scale_bar(&mut self) {
+        self.scale_bar_enabled = !self.scale_bar_enabled;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Draws everything that sits on top of the fractal.
+    pub fn draw_overlays(&self, frame: &mut [u8]) {
+        self.draw_selection(frame);
+        self.draw_orbit(frame);
+        self.draw_scale_bar(frame);
+        self.draw_loupe(frame);
+        self.draw_hud(frame);
+    }
+
+    pub fn toggle_hud(&mut self) {
+        self.hud_enabled = !self.hud_enabled;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    fn hud_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("x: {:.15}", self.viewport.center_x),
+            format!("y: {:.15}", self.viewport.center_y),
+            format!("scale: {:.3e}", self.viewport.scale),
+            format!("iterations: {}", self.max_iterations),
+        ];
+        if self.is_at_precision_limit() {
+            lines.push("f64 precision limit".to_string());
+        }
+        lines
+    }
+
+    /// Size of the HUD's backing box.
+    fn hud_size(&self) -> (usize, usize) {
+        let lines = self.hud_lines();
+        let text_width = lines
+            .iter()
+            .map(|line| overlay::text_width(line, 1))
+            .max()
+            .unwrap_or(0);
+        let line_height = overlay::text_height(1) + HUD_LINE_SPACING;
+        (
+            text_width + 8,
+            lines.len() * line_height + 8 - HUD_LINE_SPACING,
+        )
+    }
+
+    fn hud_fits(&self) -> bool {
+        let (box_width, box_height) = self.hud_size();
+        self.hud_enabled
+            && box_width + 2 * HUD_MARGIN <= self.viewport.width as usize
+            && box_height + 2 * HUD_MARGIN <= self.viewport.height as usize
+    }
+
+    /// Draws the center, scale and iteration limit in the top-left corner.
+    pub fn draw_hud(&self, frame: &mut [u8]) {
+        if !self.hud_fits() {
+            return;
+        }
+
+        let width = self.viewport.width as usize;
+        let (box_width, box_height) = self.hud_size();
+        overlay::fill_rect(
+            frame,
+            width,
+            (HUD_MARGIN, HUD_MARGIN),
+            (HUD_MARGIN + box_width, HUD_MARGIN + box_height),
+            [0, 0, 0],
+        );
+
+        let line_height = overlay::text_height(1) + HUD_LINE_SPACING;
+        for (index, line) in self.hud_lines().iter().enumerate() {
+            let y = HUD_MARGIN + 4 + index * line_height;
+            overlay::draw_text(frame, width, (HUD_MARGIN + 4, y), line, [255, 255, 255], 1);
+        }
+    }
+
+    /// The `(x0, y0, x1, y1)` areas of the frame currently covered by overlays.
+    fn overlay_rects(&self) -> Vec<(usize, usize, usize, usize)> {
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+        let mut rects: Vec<_> = self.orbit_rect().into_iter().collect();
+        rects.extend(self.selection_rects());
+
+        if self.loupe_enabled && self.cursor.is_some() {
+            let size = LOUPE_SIZE
+                .min(self.viewport.width)
+                .min(self.viewport.height) as usize;
+            rects.push((width - size, 0, width, size));
+        }
+        if self.hud_fits() {
+            let (box_width, box_height) = self.hud_size();
+            rects.push((
+                HUD_MARGIN,
+                HUD_MARGIN,
+                HUD_MARGIN + box_width,
+                HUD_MARGIN + box_height,
+            ));
+        }
+        if self.scale_bar_fits() {
+            let (box_width, box_height) = self.scale_bar_size();
+            let y1 = height.saturating_sub(SCALE_BAR_MARGIN);
+            rects.push((
+                SCALE_BAR_MARGIN,
+                y1.saturating_sub(box_height),
+                SCALE_BAR_MARGIN + box_width,
+                y1,
+            ));
+        }
+
+        rects
+    }
+
+    fn scale_bar_label(&self) -> String {
+        let span = self.pixel_to_complex(SCALE_BAR_PIXELS as f64, 0.0).re
+            - self.pixel_to_complex(0.0, 0.0).re;
+        format!("{SCALE_BAR_PIXELS} px = {span:.2e}")
+    }
+
+    /// Size of the scale bar's backing box, label included.
+    fn scale_bar_size(&self) -> (usize, usize) {
+        let label_width = overlay::text_width(&self.scale_bar_label(), 1);
+        (
+            label_width.max(SCALE_BAR_PIXELS) + 8,
+            overlay::text_height(1) + 18,
+        )
+    }
+
+    fn scale_bar_fits(&self) -> bool {
+        let (box_width, box_height) = self.scale_bar_size();
+        self.scale_bar_enabled
+            && box_width + 2 * SCALE_BAR_MARGIN <= self.viewport.width as usize
+            && box_height + 2 * SCALE_BAR_MARGIN <= self.viewport.height as usize
+    }
+
+    /// Draws a ruler in the bottom-left corner labelled with how far
+    /// `SCALE_BAR_PIXELS` pixels reach along the real axis.
+    pub fn draw_scale_bar(&self, frame: &mut [u8]) {
+        if !self.scale_bar_fits() {
+            return;
+        }
+
+        let width = self.viewport.width as usize;
+        let (box_width, box_height) = self.scale_bar_size();
+        let y1 = (self.viewport.height as usize).saturating_sub(SCALE_BAR_MARGIN);
+        let x0 = SCALE_BAR_MARGIN;
+        let y0 = y1.saturating_sub(box_height);
+
+        overlay::fill_rect(frame, width, (x0, y0), (x0 + box_width, y1), [0, 0, 0]);
+        overlay::draw_text(
+            frame,
+            width,
+            (x0 + 4, y0 + 4),
+            &self.scale_bar_label(),
+            [255, 255, 255],
+            1,
+        );
+
+        // The ruler itself, with ticks at both ends
+        let bar_x0 = x0 + 4;
+        let bar_x1 = bar_x0 + SCALE_BAR_PIXELS;
+        let bar_y = y1 - 7;
+        let white = [255, 255, 255];
+        overlay::fill_rect(frame, width, (bar_x0, bar_y), (bar_x1, bar_y + 2), white);
+        overlay::fill_rect(
+            frame,
+            width,
+            (bar_x0, bar_y - 4),
+            (bar_x0 + 1, bar_y + 4),
+            white,
+        );
+        overlay::fill_rect(
+            frame,
+            width,
+            (bar_x1 - 1, bar_y - 4),
+            (bar_x1, bar_y + 4),
+            white,
+        );
+    }
+
+    /// Composites a magnified view of the region under the cursor into the
+    /// top-right corner of the frame.
+    pub fn draw_loupe(&self, frame: &mut [u8]) {
+        if !self.loupe_enabled {
+            return;
+        }
+        let Some((cursor_x, cursor_y)) = self.cursor else {
+            return;
+        };
+
+        let size = LOUPE_SIZE
+            .min(self.viewport.width)
+            .min(self.viewport.height);
+        let center = self.pixel_to_complex(cursor_x, cursor_y);
+        let scale = self.viewport.pixel_size() * size as f64 / LOUPE_ZOOM;
+        let loupe = self.render_region(&Viewport::new(center, scale, size, size));
+
+        let width = self.viewport.width as usize;
+        let size = size as usize;
+        let origin_x = width - size;
+
+        for y in 0..size {
+            for x in 0..size {
+                let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+                let color = if border {
+                    [255, 255, 255, 255]
+                } else {
+                    let index = (y * size + x) * 4;
+                    [loupe[index], loupe[index + 1], loupe[index + 2], 255]
+                };
+
+                let frame_index = (y * width + origin_x + x) * 4;
+                frame[frame_index..frame_index + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Computes the escapes and color channels, on a 0.0..=255.0 scale, of
+    /// the points `(reals[i], imag)` into `escapes[i]` and `out[i]`.
+    fn channels_row(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        escapes: &mut [EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        let Some(mode) = self.color_mode() else {
+            self.orbit_row(self.color_scheme, reals, imag, escapes, out);
+            return;
+        };
+
+        self.iterations_row(reals, imag, escapes);
+        self.colorize_row(mode, reals, imag, escapes, out);
+    }
+
+    /// Escapes of the points `(reals[i], imag)`.
+    fn iterations_row(&self, reals: &[f64], imag: f64, out: &mut [EscapeResult]) {
+        if let Some(fractal) = &self.custom_fractal {
+            for (escape, &real) in out.iter_mut().zip(reals) {
+                *escape = fractal.iterate(
+                    Complex64::new(real, imag),
+                    self.max_iterations,
+                    self.bailout,
+                );
+            }
+            return;
+        }
+
+        if let Some(frequency) = self.stripe_frequency_in_use() {
+            for (escape, &real) in out.iter_mut().zip(reals) {
+                *escape =
+                    self.fractal
+                        .stripe(real, imag, self.max_iterations, self.bailout, frequency);
+            }
+            return;
+        }
+
+        match self.fractal {
+            // Only the Mandelbrot set has vectorized kernels
+            FractalKind::Mandelbrot => {
+                self.backend
+                    .mandelbrot_row(reals, imag, self.max_iterations, self.bailout, out)
+            }
+            fractal => {
+                for (escape, &real) in out.iter_mut().zip(reals) {
+                    *escape = fractal.escape(real, imag, self.max_iterations, self.bailout);
+                }
+            }
+        }
+    }
+
+    /// The frequency to take stripe averages with while iterating, if either
+    /// half of the view is colored by them.
+    fn stripe_frequency_in_use(&self) -> Option<f64> {
+        let striped = self.color_scheme == ColorScheme::StripeAverage
+            || self.compare_scheme == Some(ColorScheme::StripeAverage);
+        (striped && self.custom_fractal.is_none()).then_some(self.stripe_frequency)
+    }
+
+    /// Colors for a row with a `scheme` that has no [`ColorMode`], because
+    /// it depends on more of the orbit than the row kernels keep, or of a
+    /// fractal that isn't colored by escape counts at all.
+    fn orbit_row(
+        &self,
+        scheme: ColorScheme,
+        reals: &[f64],
+        imag: f64,
+        escapes: &mut [EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        match scheme {
+            _ if self.fractal == FractalKind::Newton => self.newton_row(reals, imag, escapes, out),
+            ColorScheme::DistanceEstimate => self.distance_row(reals, imag, escapes, out),
+            _ => self.trap_row(reals, imag, escapes, out),
+        }
+        self.color_interior(reals, imag, escapes, out);
+    }
+
+    /// Recolors the points `(reals[i], imag)` of a row that never escaped,
+    /// unless the interior is plain black like the color functions leave it.
+    fn color_interior(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        escapes: &[EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        if self.interior_color == [0, 0, 0] && !self.interior_shading {
+            return;
+        }
+        for ((channels, escape), &real) in out.iter_mut().zip(escapes).zip(reals) {
+            if !escape.escaped {
+                *channels = self.interior_channels(escape, Complex64::new(real, imag));
+            }
+        }
+    }
+
+    /// The color of `point`, which never escaped, on a 0.0..=255.0 scale.
+    fn interior_channels(&self, escape: &EscapeResult, point: Complex64) -> [f64; 3] {
+        let color = self.interior_color.map(f64::from);
+        if !self.interior_shading {
+            return color;
+        }
+
+        // The cycle is found again rather than read off `final_z`, which
+        // depends on the kernel, and is left at 0 in traced tiles
+        let magnitude = match &self.custom_fractal {
+            Some(_) => escape.final_z.norm(),
+            None => {
+                self.fractal
+                    .cycle_magnitude(point.re, point.im, self.max_iterations, self.bailout)
+            }
+        };
+        // Orbits that settled far from 0 are lit up, ones that shrank keep the color
+        let lightness = (magnitude / self.bailout).min(1.0);
+        color.map(|channel| channel + (255.0 - channel) * lightness)
+    }
+
+    /// Orbit trap colors for a row.
+    fn trap_row(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        escapes: &mut [EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        for ((channels, escape), &real) in out.iter_mut().zip(escapes).zip(reals) {
+            let (result, distance) = self.fractal.trap(
+                real,
+                imag,
+                self.max_iterations,
+                self.bailout,
+                &self.orbit_trap,
+            );
+            *escape = result;
+            *channels = trap_color_channels(
+                result.iterations,
+                self.max_iterations,
+                distance,
+                self.invert_palette,
+            );
+        }
+    }
+
+    /// Newton fractal colors for a row, whatever the scheme: the root each
+    /// point converges to picks its hue.
+    fn newton_row(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        escapes: &mut [EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        self.iterations_row(reals, imag, escapes);
+        for (channels, escape) in out.iter_mut().zip(escapes.iter()) {
+            *channels = newton_color_channels(
+                escape.iterations,
+                self.max_iterations,
+                escape.final_z,
+                self.invert_palette,
+            );
+        }
+    }
+
+    /// Distance estimate colors for a row, which follow the derivative of
+    /// the orbit alongside it.
+    fn distance_row(
+        &self,
+        reals: &[f64],
+        imag: f64,
+        escapes: &mut [EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        let pixel_size = self.viewport.pixel_size();
+        for ((channels, escape), &real) in out.iter_mut().zip(escapes).zip(reals) {
+            let (result, distance) =
+                self.fractal
+                    .distance(real, imag, self.max_iterations, self.bailout);
+            *escape = result;
+            *channels = distance_color_channels(
+                result.iterations,
+                self.max_iterations,
+                distance,
+                pixel_size,
+                self.invert_palette,
+            );
+        }
+    }
+
+    /// Turns already computed escapes into colors with `mode`, applying the
+    /// jitter, tonemap and inversion settings. The smooth mode, and every
+    /// mode with smooth coloring on, uses the fractional escape value rather
+    /// than the step count.
+    fn colorize_row(
+        &self,
+        mode: ColorMode,
+        reals: &[f64],
+        imag: f64,
+        escapes: &[EscapeResult],
+        out: &mut [[f64; 3]],
+    ) {
+        let max_iterations = self.max_iterations as f64;
+        let palette_offset =
+            self.palette_depth_coupling * self.magnification().ln() + self.color_offset;
+        let smooth = self.smooth_coloring
+            || matches!(
+                mode,
+                ColorMode::Smooth
+                    | ColorMode::Potential { .. }
+                    | ColorMode::Angle
+                    | ColorMode::StripeAverage(_)
+            );
+        for (channels, (escape, &real)) in out.iter_mut().zip(escapes.iter().zip(reals)) {
+            let iterations = escape.iterations;
+            if !escape.escaped
+                || (!smooth
+                    && self.color_jitter == 0.0
+                    && self.tonemap == ToneMap::None
+                    && !self.invert_palette
+                    && self.palette_depth_coupling == 0.0
+                    && self.color_offset == 0.0)
+            {
+                *channels = if iterations == self.max_iterations {
+                    // Black for points inside the set
+                    [0.0; 3]
+                } else {
+                    self.iteration_color(iterations, mode)
+                };
+                continue;
+            }
+
+            let mut value = if smooth {
+                escape.smooth_iterations().clamp(0.0, max_iterations)
+            } else {
+                iterations as f64
+            };
+            if self.color_jitter > 0.0 {
+                // Dither the value rather than the color, breaking bands up into noise
+                let offset = self.color_jitter * point_noise(self.jitter_seed, real, imag);
+                value = (value + offset).clamp(0.0, max_iterations);
+            }
+            if self.tonemap != ToneMap::None {
+                value = self.tonemap.apply(value / max_iterations) * max_iterations;
+            }
+            if self.invert_palette {
+                value = max_iterations - value;
+            }
+            if palette_offset != 0.0 {
+                value = (value / max_iterations + palette_offset).rem_euclid(1.0) * max_iterations;
+            }
+            *channels = match mode {
+                ColorMode::Angle => {
+                    angle_color_channels(escape.angle(), value, self.max_iterations)
+                }
+                ColorMode::StripeAverage(_) => {
+                    stripe_color_channels(escape.stripe, value, self.max_iterations)
+                }
+                _ => self.color_value(value, mode),
+            };
+        }
+        self.color_interior(reals, imag, escapes, out);
+    }
+
+    /// [`Self::color_value`] of a whole iteration count, from the gradient
+    /// table when it is up to date.
+    fn iteration_color(&self, iterations: u32, mode: ColorMode) -> [f64; 3] {
+        self.gradient_lut
+            .as_ref()
+            .filter(|lut| lut.mode == mode && lut.max_iterations == self.max_iterations)
+            .and_then(|lut| lut.colors.get(iterations as usize))
+            .copied()
+            .unwrap_or_else(|| self.color_value(iterations as f64, mode))
+    }
+
+    /// Like [`color_map_value`], but colors [`ColorMode::Gradient`] with this
+    /// renderer's palette, and blends it and [`ColorMode::Smooth`] in Oklab
+    /// when perceptual blending is on.
+    fn color_value(&self, value: f64, mode: ColorMode) -> [f64; 3] {
+        let t = value / self.max_iterations as f64;
+        match mode {
+            ColorMode::Gradient if self.perceptual => gradient_color_oklab(self.palette.stops(), t),
+            ColorMode::Gradient => gradient_color(self.palette.stops(), t),
+            ColorMode::Smooth if self.perceptual => smooth_color(value, self.max_iterations, true),
+            _ => color_map_value(value, self.max_iterations, mode),
+        }
+    }
+
+    /// Writes the RGBA colors of the points `(reals[i], imag)` into `row`.
+    fn color_row(&self, reals: &[f64], imag: f64, row: &mut [u8]) {
+        let mut escapes = vec![EscapeResult::default(); reals.len()];
+        let mut channels = vec![[0.0; 3]; reals.len()];
+        self.channels_row(reals, imag, &mut escapes, &mut channels);
+
+        for (pixel, channels) in row.chunks_exact_mut(4).zip(&channels) {
+            let [r, g, b] = channels.map(|channel| channel as u8);
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    /// Like [`Self::color_row`], but averages the colors of an
+    /// `aa_samples` by `aa_samples` grid of points spread over each pixel.
+    fn color_row_supersampled(&self, reals: &[f64], imag: f64, row: &mut [u8]) {
+        let samples = self.aa_samples;
+        let pixel_size = self.viewport.pixel_size();
+        // Centers of the grid cells, relative to the pixel's own point
+        let offset = |sample: u32| (sample as f64 + 0.5) / samples as f64 - 0.5;
+
+        // Averaged in linear light unless gamma correction is off
+        let gamma = if self.gamma_correction {
+            self.gamma
+        } else {
+            1.0
+        };
+        let mut sums = vec![[0.0; 3]; reals.len()];
+        let mut sample_reals = vec![0.0; reals.len()];
+        let mut escapes = vec![EscapeResult::default(); reals.len()];
+        let mut channels = vec![[0.0; 3]; reals.len()];
+        for sample_y in 0..samples {
+            let sample_imag = imag + offset(sample_y) * pixel_size;
+            for sample_x in 0..samples {
+                let shift = offset(sample_x) * pixel_size;
+                for (sample_real, &real) in sample_reals.iter_mut().zip(reals) {
+                    *sample_real = real + shift;
+                }
+                self.channels_row(&sample_reals, sample_imag, &mut escapes, &mut channels);
+
+                for (sum, channels) in sums.iter_mut().zip(&channels) {
+                    for (sum, channel) in sum.iter_mut().zip(channels) {
+                        *sum += (channel / 255.0).powf(gamma);
+                    }
+                }
+            }
+        }
+
+        let count = (samples * samples) as f64;
+        for (pixel, sum) in row.chunks_exact_mut(4).zip(&sums) {
+            // Powers don't round-trip exactly, so a color every sample agrees on
+            // could otherwise truncate to the level below
+            let [r, g, b] =
+                sum.map(|channel| ((channel / count).powf(gamma.recip()) * 255.0 + 1e-6) as u8);
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    /// The palette for the current scheme, or `None` if the scheme colors by
+    /// something other than the iteration count.
+    pub fn color_mode(&self) -> Option<ColorMode> {
+        self.mode_for(self.color_scheme)
+    }
+
+    fn mode_for(&self, scheme: ColorScheme) -> Option<ColorMode> {
+        if self.fractal == FractalKind::Newton && self.custom_fractal.is_none() {
+            return None;
+        }
+        let mode = match scheme {
+            ColorScheme::Smooth => ColorMode::Smooth,
+            ColorScheme::Zebra => ColorMode::Zebra,
+            ColorScheme::Red => ColorMode::Red,
+            ColorScheme::Blue => ColorMode::Blue,
+            ColorScheme::BlackAndWhite => ColorMode::BlackAndWhite,
+            ColorScheme::Rainbow => ColorMode::Rainbow,
+            ColorScheme::Psychedelic => ColorMode::Psychedelic,
+            ColorScheme::GreenGradient => ColorMode::GreenGradient,
+            ColorScheme::Electric => ColorMode::Electric,
+            ColorScheme::EscapeVelocity => ColorMode::EscapeVelocity(self.escape_velocity_exponent),
+            // Custom fractals have no orbits to color by
+            ColorScheme::OrbitTrap | ColorScheme::DistanceEstimate
+                if self.custom_fractal.is_some() =>
+            {
+                ColorMode::Smooth
+            }
+            ColorScheme::OrbitTrap | ColorScheme::DistanceEstimate => return None,
+            ColorScheme::Histogram => ColorMode::Histogram,
+            ColorScheme::Gradient => ColorMode::Gradient,
+            ColorScheme::Potential => ColorMode::Potential {
+                contours: self.potential_contours,
+            },
+            ColorScheme::Angle => ColorMode::Angle,
+            // Custom fractals only report their escapes
+            ColorScheme::StripeAverage if self.custom_fractal.is_some() => ColorMode::Smooth,
+            ColorScheme::StripeAverage => ColorMode::StripeAverage(self.stripe_frequency),
+            ColorScheme::SingleHue => ColorMode::SingleHue(self.single_hue),
+        };
+        Some(mode)
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replaces the stops of the gradient color scheme.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn change_color_scheme(&mut self, scheme: ColorScheme) {
+        self.color_scheme = scheme;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Splits the frame to show the view with a second scheme on the right,
+    /// or goes back to a single view.
+    pub fn toggle_compare(&mut self) {
+        self.compare_scheme = match self.compare_scheme {
+            Some(_) => None,
+            None => Some(compare_scheme_for(self.color_scheme)),
+        };
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// The scheme on the right half of the split view, if it is shown.
+    pub fn compare_scheme(&self) -> Option<ColorScheme> {
+        self.compare_scheme
+    }
+
+    pub fn set_compare_scheme(&mut self, scheme: ColorScheme) {
+        self.compare_scheme = Some(scheme);
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn escape_velocity_exponent(&self) -> f64 {
+        self.escape_velocity_exponent
+    }
+
+    pub fn set_escape_velocity_exponent(&mut self, exponent: f64) {
+        self.escape_velocity_exponent = exponent.max(0.1);
+        if matches!(self.color_scheme, ColorScheme::EscapeVelocity) && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_potential_contours(&self) -> bool {
+        self.potential_contours
+    }
+
+    /// Draws equipotential lines over the potential scheme, or stops.
+    pub fn toggle_potential_contours(&mut self) {
+        self.potential_contours = !self.potential_contours;
+        if matches!(self.color_scheme, ColorScheme::Potential) && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_perceptual(&self) -> bool {
+        self.perceptual
+    }
+
+    /// Blends the colors of the smooth and gradient schemes in Oklab rather
+    /// than channel by channel, which keeps the midtones between distant
+    /// hues from turning muddy.
+    pub fn set_perceptual(&mut self, perceptual: bool) {
+        self.perceptual = perceptual;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn single_hue(&self) -> [u8; 3] {
+        self.single_hue
+    }
+
+    /// Changes the color the single hue scheme fades up to.
+    pub fn set_single_hue(&mut self, color: [u8; 3]) {
+        self.single_hue = color;
+        if matches!(self.color_scheme, ColorScheme::SingleHue) && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn stripe_frequency(&self) -> f64 {
+        self.stripe_frequency
+    }
+
+    /// Changes how many stripes the stripe average scheme wraps around each
+    /// turn of the orbits.
+    ///
+    /// # Panics
+    ///
+    /// If `frequency` isn't finite and greater than 0.
+    pub fn set_stripe_frequency(&mut self, frequency: f64) {
+        if !(frequency.is_finite() && frequency > 0.0) {
+            panic!("Stripe frequency must be greater than 0");
+        }
+        self.stripe_frequency = frequency;
+        if matches!(self.color_scheme, ColorScheme::StripeAverage) && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn orbit_trap(&self) -> TrapShape {
+        self.orbit_trap
+    }
+
+    pub fn set_orbit_trap(&mut self, trap: TrapShape) {
+        self.orbit_trap = trap;
+        if matches!(self.color_scheme, ColorScheme::OrbitTrap) && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn interior_color(&self) -> [u8; 3] {
+        self.interior_color
+    }
+
+    /// Colors the points that never escape, which are black by default.
+    pub fn set_interior_color(&mut self, color: [u8; 3]) {
+        self.interior_color = color;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_interior_shading(&self) -> bool {
+        self.interior_shading
+    }
+
+    /// Lightens the interior color by how close to 0 the cycle each point's
+    /// orbit settles on comes, see [`FractalKind::cycle_magnitude`], which
+    /// brings out the bulbs and their centers inside the set.
+    pub fn set_interior_shading(&mut self, shading: bool) {
+        self.interior_shading = shading;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    /// Changes the keys the window responds to.
+    pub fn set_keymap(&mut self, keymap: KeyMap) {
+        self.keymap = keymap;
+    }
+
+    pub fn is_smooth_coloring(&self) -> bool {
+        self.smooth_coloring
+    }
+
+    /// Feeds the fractional escape value, see
+    /// [`EscapeResult::smooth_iterations`], into whichever palette is active
+    /// instead of the step count, or goes back to the step count. The smooth
+    /// scheme is smooth either way.
+    pub fn set_smooth_coloring(&mut self, smooth: bool) {
+        self.smooth_coloring = smooth;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn color_jitter(&self) -> f64 {
+        self.color_jitter
+    }
+
+    /// Adds up to `amount / 2` iterations of seeded random offset to each
+    /// point before coloring. A cheap alternative to supersampling for hiding
+    /// color banding; 0.0 turns it off.
+    pub fn set_color_jitter(&mut self, amount: f64) {
+        self.color_jitter = amount.max(0.0);
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Picks a different, but still reproducible, jitter pattern.
+    pub fn set_jitter_seed(&mut self, seed: u64) {
+        self.jitter_seed = seed;
+        if self.color_jitter > 0.0 && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn aa_samples(&self) -> u32 {
+        self.aa_samples
+    }
+
+    /// Samples a `samples` by `samples` grid within every pixel of full
+    /// renders and averages the colors, smoothing out jagged edges at
+    /// `samples²` times the cost. 1 turns it off.
+    pub fn set_aa_samples(&mut self, samples: u32) {
+        self.aa_samples = samples.max(1);
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_temporal_aa(&self) -> bool {
+        self.temporal_aa
+    }
+
+    /// Keeps refining the view once the progressive scan is done, adding
+    /// another jittered sample of every pixel each frame, up to
+    /// [`TEMPORAL_AA_SAMPLES`], and showing their average. Any change to the
+    /// view starts over from a single sample. Only while scanning is enabled
+    /// and supersampling isn't on.
+    pub fn set_temporal_aa(&mut self, temporal_aa: bool) {
+        self.temporal_aa = temporal_aa;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Temporal samples of every pixel on screen, 0 when the scan drew it.
+    pub fn accumulated_samples(&self) -> u32 {
+        self.accumulated_samples
+    }
+
+    pub fn is_glow_enabled(&self) -> bool {
+        self.glow_enabled
+    }
+
+    /// Turns the glow of [`Self::set_glow`] on or off.
+    pub fn set_glow_enabled(&mut self, enabled: bool) {
+        self.glow_enabled = enabled;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Radius and strength of the glow.
+    pub fn glow(&self) -> (usize, f64) {
+        (self.glow_radius, self.glow_strength)
+    }
+
+    /// Sets how far, in pixels, the glow [`Self::render`] adds around the
+    /// bright parts of the view reaches, and how bright it is, see
+    /// [`Self::apply_glow`]. Takes effect once it's turned on with
+    /// [`Self::set_glow_enabled`].
+    ///
+    /// # Panics
+    ///
+    /// If `strength` isn't finite and at least 0.
+    pub fn set_glow(&mut self, radius: usize, strength: f64) {
+        if !(strength.is_finite() && strength >= 0.0) {
+            panic!("Glow strength must be at least 0");
+        }
+
+        self.glow_radius = radius;
+        self.glow_strength = strength;
+        if self.glow_enabled && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Adds a soft glow around the bright parts of `frame`: the luminance
+    /// is blurred with a box `2 * radius + 1` pixels wide, along the rows
+    /// and then down the columns, and `strength` times the blur is added to
+    /// every channel. Near the borders the box only averages the pixels
+    /// within the frame, so they neither darken nor pick up light from the
+    /// other side.
+    pub fn apply_glow(&self, frame: &mut [u8], radius: usize, strength: f64) {
+        let width = self.viewport.width as usize;
+        let height = self.viewport.height as usize;
+        if radius == 0 || strength == 0.0 || width == 0 || height == 0 {
+            return;
+        }
+
+        // Blurred along the rows, stored column by column for the second pass
+        let mut columns = vec![0.0; width * height];
+        let mut blurred = vec![0.0; width];
+        for (y, row) in frame.chunks_exact(width * 4).take(height).enumerate() {
+            let luminance: Vec<f32> = row
+                .chunks_exact(4)
+                .map(|pixel| {
+                    (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32)
+                        / 255.0
+                })
+                .collect();
+            box_blur(&luminance, &mut blurred, radius);
+            for (x, &value) in blurred.iter().enumerate() {
+                columns[x * height + y] = value;
+            }
+        }
+
+        let mut glow = vec![0.0; width * height];
+        glow.par_chunks_exact_mut(height)
+            .zip(columns.par_chunks_exact(height))
+            .for_each(|(blurred, column)| box_blur(column, blurred, radius));
+
+        frame
+            .par_chunks_exact_mut(width * 4)
+            .take(height)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                    let light = strength as f32 * glow[x * height + y] * 255.0;
+                    for channel in &mut pixel[..3] {
+                        *channel = (*channel as f32 + light).min(255.0) as u8;
+                    }
+                }
+            });
+    }
+
+    /// Applies the glow to `frame` if it's turned on.
+    fn add_glow(&self, frame: &mut [u8]) {
+        // A partial frame would get the glow again where it kept the previous one
+        if self.glow_enabled && !self.is_cancel_requested() {
+            self.apply_glow(frame, self.glow_radius, self.glow_strength);
+        }
+    }
+
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// Sets the gamma the colors are taken to be encoded with. Supersampled
+    /// colors are decoded to linear light before they are averaged and
+    /// encoded again afterwards, which keeps blended edges from coming out
+    /// too dark.
+    ///
+    /// # Panics
+    ///
+    /// If `gamma` isn't finite and greater than 0.
+    pub fn set_gamma(&mut self, gamma: f64) {
+        if !(gamma.is_finite() && gamma > 0.0) {
+            panic!("Gamma must be greater than 0");
+        }
+        self.gamma = gamma;
+        if self.aa_samples > 1 && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_gamma_correction(&self) -> bool {
+        self.gamma_correction
+    }
+
+    /// Switches between blending samples in linear light, see
+    /// [`Self::set_gamma`], and averaging the encoded colors directly.
+    pub fn toggle_gamma_correction(&mut self) {
+        self.gamma_correction = !self.gamma_correction;
+        if self.aa_samples > 1 && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn is_adaptive_aa(&self) -> bool {
+        self.adaptive_aa
+    }
+
+    /// Limits supersampling to the pixels at edges, see
+    /// [`Self::render_adaptive_aa`], or goes back to supersampling all of
+    /// them.
+    pub fn set_adaptive_aa(&mut self, adaptive: bool) {
+        self.adaptive_aa = adaptive;
+        if self.aa_samples > 1 && self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Moves on to the next supersampling level, wrapping back to none.
+    pub fn cycle_aa_samples(&mut self) {
+        let index = AA_LEVELS
+            .iter()
+            .position(|&samples| samples == self.aa_samples)
+            .map_or(0, |index| index + 1);
+        self.set_aa_samples(AA_LEVELS[index % AA_LEVELS.len()]);
+    }
+
+    pub fn tonemap(&self) -> ToneMap {
+        self.tonemap
+    }
+
+    pub fn set_tonemap(&mut self, tonemap: ToneMap) {
+        self.tonemap = tonemap;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Runs every palette backwards. Interior points keep their color.
+    pub fn toggle_invert_palette(&mut self) {
+        self.invert_palette = !self.invert_palette;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn bailout(&self) -> f64 {
+        self.bailout
+    }
+
+    /// Sets the escape radius. Larger radii smooth out the smooth coloring's
+    /// gradients at the cost of a few more iterations per escaping point.
+    pub fn set_bailout(&mut self, bailout: f64) {
+        self.bailout = bailout.max(DEFAULT_BAILOUT);
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    pub fn palette_depth_coupling(&self) -> f64 {
+        self.palette_depth_coupling
+    }
+
+    /// Shifts the palette by `coupling * ln(magnification)` of its length, so
+    /// the colors keep cycling as the view zooms in. Zero turns it off.
+    pub fn set_palette_depth_coupling(&mut self, coupling: f64) {
+        self.palette_depth_coupling = coupling;
+        if self.scan_config.enabled {
+            self.scan_level = 0;
+        }
+    }
+
+    /// Whether later frames still improve on the current one, with scan
+    /// passes or temporal samples left to draw.
+    pub fn is_scanning(&self) -> bool {
+        self.scan_config.enabled && (self.scan_stride() >= 1 || self.is_accumulating())
+    }
+
+    /// Stride of the current scan pass, 0 once all passes are done. Halving
+    /// any starting stride ends on a pass with stride 1, which covers every
+    /// pixel. [`Args`] refuses a starting stride of 0.
+    fn scan_stride(&self) -> u32 {
+        self.scan_config
+            .initial_stride
+            .checked_shr(self.scan_level)
+            .unwrap_or(0)
+    }
+}
+
+/// Deterministic noise in `-0.5..0.5` for a point, so jittered renders come
+/// out the same regardless of thread scheduling or render order.
+fn point_noise(seed: u64, real: f64, imag: f64) -> f64 {
+    // splitmix64 finalizer over the point's bits
+    let mut x = seed ^ real.to_bits() ^ imag.to_bits().rotate_left(32);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+}
+
+/// Averages every value of `values` with those up to `radius` away on either
+/// side into `blurred`, leaving out the ones past either end.
+fn box_blur(values: &[f32], blurred: &mut [f32], radius: usize) {
+    let mut sums = Vec::with_capacity(values.len() + 1);
+    sums.push(0.0);
+    for &value in values {
+        sums.push(sums[sums.len() - 1] + value as f64);
+    }
+    for (i, blurred) in blurred.iter_mut().enumerate() {
+        let start = i.saturating_sub(radius);
+        let end = (i + radius + 1).min(values.len());
+        *blurred = ((sums[end] - sums[start]) / (end - start) as f64) as f32;
+    }
+}
+
+/// For every iteration count, the fraction of escaped points that escaped
+/// within that many steps. Interior points are left out.
+fn histogram_ranks(escapes: &[EscapeResult], max_iterations: u32) -> Vec<f64> {
+    let mut counts = vec![0u64; max_iterations as usize + 1];
+    for escape in escapes.iter().filter(|escape| escape.escaped) {
+        counts[escape.iterations as usize] += 1;
+    }
+
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    let mut cumulative = 0;
+    counts
+        .into_iter()
+        .map(|count| {
+            cumulative += count;
+            cumulative as f64 / total
+        })
+        .collect()
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Like [`buffer_len`], for buffers about to be allocated.
+///
+/// # Panics
+///
+/// If the buffer is too large to address.
+fn frame_buffer_len(width: u32, height: u32, channels: usize) -> usize {
+    buffer_len(width, height, channels)
+        .unwrap_or_else(|| panic!("A {width}x{height} image is too large to hold in memory"))
+}
+
+/// The scheme to compare `scheme` with, in the split view or a second
+/// window.
+fn compare_scheme_for(scheme: ColorScheme) -> ColorScheme {
+    match scheme {
+        ColorScheme::Smooth => ColorScheme::Rainbow,
+        _ => ColorScheme::Smooth,
+    }
+}
+
+/// The left mouse button is down, either for a click or a drag.
+struct LeftDrag {
+    /// Window position the view has been dragged to so far
+    last: (f32, f32),
+    start: (f32, f32),
+    /// Whether the mouse went far enough for this to be a drag
+    moved: bool,
+    /// Whether this drags out a box to zoom into rather than the view
+    selecting: bool,
+}
+
+/// A full frame rendering on a thread of its own, so the window keeps
+/// handling input meanwhile, see [`Pane::render`].
+struct RenderJob {
+    /// The center and scale being rendered, to tell when the window has
+    /// moved on from them
+    view: (Complex64, f64),
+    cancel: Arc<AtomicBool>,
+    handle: JoinHandle<(Renderer, Vec<u8>)>,
+}
+
+impl RenderJob {
+    /// Starts rendering the view of `renderer` into a copy of `frame`, and
+    /// wakes the event loop up through `proxy` once it's done.
+    fn spawn(renderer: &Renderer, frame: &[u8], proxy: EventLoopProxy<()>) -> Self {
+        let mut renderer = renderer.detached();
+        let mut frame = frame.to_vec();
+        let view = (renderer.viewport.center(), renderer.viewport.scale);
+        let cancel = renderer.cancel_handle();
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            renderer.render(&mut frame);
+            renderer.render_time = start.elapsed();
+            // Fails once the event loop is gone, with nobody left to show it to
+            let _ = proxy.send_event(());
+            (renderer, frame)
+        });
+
+        Self {
+            view,
+            cancel,
+            handle,
+        }
+    }
+
+    /// Asks the render to stop after the row or tile it is on. Its thread
+    /// is left to wind down on its own.
+    fn cancel(self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A window with its own view, for comparing views in several windows.
+struct Pane {
+    /// Title shown before the render stats
+    name: String,
+    /// The title last set, to only update it when it changes
+    title: String,
+    window: winit::window::Window,
+    pixels: Pixels,
+    renderer: Renderer,
+    input: WinitInputHelper,
+    /// Text typed into the magnification prompt, while it is open
+    prompt: Option<String>,
+    /// The left mouse button, while it is held
+    left_drag: Option<LeftDrag>,
+    /// When the previous auto-zoom or animation frame was drawn
+    last_frame: Instant,
+    /// When the scan last drew a slice, to pace them by
+    /// [`ScanConfig::frame_interval`]
+    last_scan_frame: Instant,
+    /// Whether the window has keyboard focus. Scans only refine while one
+    /// of the windows does, see [`Pane::redraw`].
+    focused: bool,
+    /// The frame rendering in the background, if any
+    job: Option<RenderJob>,
+    /// Wakes the event loop up when `job` is done
+    proxy: EventLoopProxy<()>,
+}
+
+impl Pane {
+    fn new(
+        name: String,
+        window: winit::window::Window,
+        pixels: Pixels,
+        renderer: Renderer,
+        input: WinitInputHelper,
+        proxy: EventLoopProxy<()>,
+    ) -> Self {
+        Self {
+            name,
+            title: String::new(),
+            window,
+            pixels,
+            renderer,
+            input,
+            prompt: None,
+            left_drag: None,
+            last_frame: Instant::now(),
+            last_scan_frame: Instant::now(),
+            focused: true,
+            job: None,
+            proxy,
+        }
+    }
+
+    /// The center and scale of the view.
+    fn view(&self) -> (Complex64, f64) {
+        let viewport = &self.renderer.viewport;
+        (viewport.center(), viewport.scale)
+    }
+
+    /// Shows how long the last frame took to render and the scale in the
+    /// title, how far the scan has got and whether zooming in has run out of
+    /// precision, unless the magnification prompt is using it.
+    fn update_title(&mut self) {
+        if self.prompt.is_some() {
+            // Set it again once the prompt is done with it
+            self.title.clear();
+            return;
+        }
+
+        let mut title = format!(
+            "{} - {}ms - scale {:.1e}",
+            self.name,
+            self.renderer.render_time().as_millis(),
+            self.renderer.viewport.scale
+        );
+        if self.renderer.is_at_precision_limit() {
+            title.push_str(" - f64 precision limit");
+        }
+        let progress = self.renderer.scan_progress();
+        if progress < 1.0 {
+            title.push_str(&format!(" - scanning {:.0}%", progress * 100.0));
+        }
+        if title != self.title {
+            self.window.set_title(&title);
+            self.title = title;
+        }
+    }
+
+    /// Fits the surface to a window resized to `size` and renders at the new
+    /// resolution. Minimized windows have no size and are left alone.
+    fn resize(&mut self, size: PhysicalSize<u32>) -> Result<(), Error> {
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+
+        self.pixels.resize_surface(size.width, size.height)?;
+        if (size.width, size.height) == (self.renderer.width(), self.renderer.height()) {
+            return Ok(());
+        }
+
+        self.pixels.resize_buffer(size.width, size.height)?;
+        self.renderer.resize(size.width, size.height);
+        // The GPU renderer was set up for the old buffer size
+        if self.renderer.gpu.is_some() {
+            self.renderer.set_gpu(GpuRenderer::new(&self.pixels));
+        }
+        self.render()
+    }
+
+    /// Renders the view and presents it. Scans render a slice at a time
+    /// here, and views the GPU can draw are drawn at once. Other frames
+    /// render on a [`RenderJob`], stopping the one still running, and are
+    /// presented by [`Pane::finish_render`].
+    fn render(&mut self) -> Result<(), Error> {
+        if let Some(job) = self.job.take() {
+            job.cancel();
+        }
+        if self.renderer.scan_config.enabled {
+            return self.renderer.render_to(&mut self.pixels);
+        }
+        if self.renderer.draw_gpu(&mut self.pixels)? {
+            return Ok(());
+        }
+
+        self.job = Some(RenderJob::spawn(
+            &self.renderer,
+            self.pixels.frame(),
+            self.proxy.clone(),
+        ));
+        Ok(())
+    }
+
+    /// Stops the [`RenderJob`] still running if the view has moved on from
+    /// it without asking for a render, like a drag does.
+    fn cancel_stale_render(&mut self) {
+        let view = self.view();
+        if let Some(job) = self.job.take_if(|job| job.view != view) {
+            job.cancel();
+        }
+    }
+
+    /// Presents the frame of a finished [`RenderJob`], unless it was
+    /// cancelled or the view or buffer size has changed since.
+    fn finish_render(&mut self) -> Result<(), Error> {
+        let Some(job) = self.job.take_if(|job| job.handle.is_finished()) else {
+            return Ok(());
+        };
+
+        let (done, frame) = job.handle.join().expect("Render thread panicked");
+        if done.was_cancelled()
+            || job.view != self.view()
+            || frame.len() != self.pixels.frame().len()
+        {
+            return Ok(());
+        }
+        self.pixels.frame_mut().copy_from_slice(&frame);
+        self.renderer.adopt_render(done);
+        self.pixels.render()
+    }
+
+    /// Steps whatever animation is running and draws the next frame. An
+    /// unfinished scan only gets its next pass with `refine`, so it can be
+    /// paused where it is and resumed later. Nothing moves on while a
+    /// [`RenderJob`] is still drawing the last frame.
+    fn redraw(&mut self, refine: bool) {
+        if self.job.is_some() {
+            return;
+        }
+
+        let now = Instant::now();
+        let elapsed = (now - self.last_frame).as_secs_f64();
+        let renderer = &mut self.renderer;
+        let result = if renderer.is_auto_zooming() {
+            renderer.step_auto_zoom(elapsed);
+            self.last_frame = now;
+            self.render()
+        } else if renderer.is_animating() {
+            renderer.step_animation(elapsed);
+            self.last_frame = now;
+            self.render()
+        } else if renderer.is_cycling_palette() {
+            renderer.cycle_palette(self.pixels.frame_mut(), elapsed);
+            self.last_frame = now;
+            renderer.present(&mut self.pixels)
+        } else if renderer.is_scanning() && refine {
+            // The next slice is requested once the frame budget is up, see
+            // `Event::MainEventsCleared`
+            self.last_scan_frame = now;
+            renderer.render_to(&mut self.pixels)
+        } else {
+            Ok(())
+        };
+        result.expect("pixels.render() failed");
+    }
+}
+
+pub struct RendererRunner {
+    event_loop: EventLoop<()>,
+    window: winit::window::Window,
+    pixels: Pixels,
+    renderer: Renderer,
+    input: WinitInputHelper,
+    args: Args,
+}
+
+impl RendererRunner {
+    pub fn new() -> Result<Self, Error> {
+        let event_loop = EventLoop::new();
+        let input = WinitInputHelper::new();
+        let window = Self::create_window(&event_loop);
+        let args = Args::default();
+        let pixels = Self::create_pixels(&window, &args)?;
+        let mut renderer = Renderer::new();
+        let size = Self::buffer_size(&window, &args);
+        renderer.resize(size.width, size.height);
+
+        Ok(Self {
+            event_loop,
+            window,
+            pixels,
+            renderer,
+            input,
+            args,
+        })
+    }
+
+    fn create_window(event_loop: &EventLoop<()>) -> winit::window::Window {
+        let size = LogicalSize::new(800.0, 600.0);
+        WindowBuilder::new()
+            .with_title(WINDOW_TITLE)
+            .with_inner_size(size)
+            .with_min_inner_size(size)
+            .build(event_loop)
+            .unwrap()
+    }
+
+    /// The frame buffer size for a window of the size in `args`. The window
+    /// is sized in logical pixels, so on HiDPI displays it covers more
+    /// physical pixels than that, and the frame gets one for each of them.
+    fn buffer_size(window: &winit::window::Window, args: &Args) -> PhysicalSize<u32> {
+        LogicalSize::new(args.get_width(), args.get_height()).to_physical(window.scale_factor())
+    }
+
+    fn create_pixels(window: &winit::window::Window, args: &Args) -> Result<Pixels, Error> {
+        let window_size = window.inner_size();
+        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, window);
+        let size = Self::buffer_size(window, args);
+        Pixels::new(size.width, size.height, surface_texture)
+    }
+
+    pub fn with_args(mut self, args: Args) -> Self {
+        // Update renderer configuration
+        self.renderer.max_iterations = args.get_max_iterations();
+        self.renderer.bailout = args.get_bailout();
+        self.renderer.color_scheme = args.get_color_scheme();
+        self.renderer.home_settings = (self.renderer.color_scheme, self.renderer.max_iterations);
+        self.renderer.set_fractal_kind(args.get_fractal_kind());
+        self.renderer.apply_view_args(&args);
+        self.renderer.scan_config = args.get_scan_config();
+        self.renderer.orbit_trap = args.get_orbit_trap();
+        self.renderer.color_jitter = args.get_color_jitter();
+        self.renderer.tonemap = args.get_tonemap();
+        self.renderer.invert_palette = args.get_invert_palette();
+        self.renderer.interior_color = args.get_interior_color();
+        self.renderer.interior_shading = args.get_interior_shading();
+        self.renderer.single_hue = args.get_single_hue();
+        self.renderer.stripe_frequency = args.get_stripe_frequency();
+        self.renderer.perceptual = args.get_perceptual();
+        if let Some(keymap) = args.get_keymap() {
+            self.renderer.keymap = keymap.clone();
+        }
+        self.renderer.palette_depth_coupling = args.get_palette_depth_coupling();
+        self.renderer.aa_samples = args.get_aa_samples();
+        self.renderer.temporal_aa = args.get_temporal_aa();
+        self.renderer.glow_enabled = args.get_glow().is_some();
+        self.renderer.glow_strength = args.get_glow().unwrap_or(DEFAULT_GLOW.1);
+        self.renderer.glow_radius = args.get_glow_radius().unwrap_or(DEFAULT_GLOW.0);
+        if let Some(palette) = args.get_palette() {
+            self.renderer.palette = palette.clone();
+        }
+        self.renderer.gamma = args.get_gamma().unwrap_or(DEFAULT_GAMMA);
+        self.renderer.pan_speed = args.get_pan_speed().unwrap_or(PAN_SPEED);
+        self.renderer.set_pan_inertia(args.get_pan_inertia());
+        self.renderer.zoom_step = args.get_zoom_step().unwrap_or(WHEEL_ZOOM);
+        self.renderer.max_scale = args.get_max_scale().unwrap_or(MAX_SCALE);
+        if let Some(backend) = args.get_render_backend() {
+            if !self.renderer.set_backend(backend) {
+                eprintln!(
+                    "{backend:?} is not supported on this CPU, using {:?}",
+                    self.renderer.backend()
+                );
+            }
+        }
+
+        if args.get_fullscreen() {
+            self.window
+                .set_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+
+        // Check if window size needs to be updated, comparing physical pixels
+        let current_size = self.window.inner_size();
+        let size = Self::buffer_size(&self.window, &args);
+
+        if current_size != size || (self.renderer.width(), self.renderer.height()) != size.into() {
+            // Resize the window
+            self.window.set_inner_size(LogicalSize::new(
+                args.get_width() as f64,
+                args.get_height() as f64,
+            ));
+
+            // Recreate pixels with new dimensions
+            self.pixels = Self::create_pixels(&self.window, &args)
+                .expect("Failed to create pixels with new dimensions");
+
+            self.renderer.viewport.width = size.width;
+            self.renderer.viewport.height = size.height;
+        }
+
+        if args.get_gpu() {
+            let gpu = GpuRenderer::new(&self.pixels);
+            if gpu.is_none() {
+                eprintln!("This GPU can't run compute shaders, rendering on the CPU");
+            }
+            self.renderer.set_gpu(gpu);
+        }
+
+        // Update stored args
+        self.args = args;
+
+        self
+    }
+
+    pub fn run(self) -> Result<(), Error> {
+        let RendererRunner {
+            event_loop,
+            window,
+            pixels,
+            renderer,
+            input,
+            args,
+        } = self;
+
+        let mut panes = vec![Pane::new(
+            WINDOW_TITLE.to_string(),
+            window,
+            pixels,
+            renderer,
+            input,
+            event_loop.create_proxy(),
+        )];
+        if args.get_compare() {
+            let pane = Self::compare_pane(&event_loop, &args, &panes[0].renderer)?;
+            panes.push(pane);
+        }
+        let multiple = panes.len() > 1;
+
+        // The export running in the background, if any
+        let mut export: Option<ExportTask> = None;
+        // Set once closing has been put off to let an export finish
+        let mut closing = false;
+        // Whether moving one window's view moves the others along
+        let mut sync = true;
+        let mut synced_view = panes[0].view();
+
+        // Initial render
+        for pane in &mut panes {
+            pane.render()?;
+        }
+
+        event_loop.run(move |event, _, control_flow| {
+            for pane in &mut panes {
+                // Each window only sees its own window events
+                let for_pane = match &event {
+                    Event::WindowEvent { window_id, .. } => *window_id == pane.window.id(),
+                    _ => true,
+                };
+                // Only act on input once per step so pressed keys don't fire for every event
+                if !for_pane || !pane.input.update(&event) {
+                    continue;
+                }
+
+                let Pane {
+                    window,
+                    pixels,
+                    renderer,
+                    input,
+                    prompt,
+                    left_drag,
+                    ..
+                } = pane;
+                let needs_update = if prompt.is_some() {
+                    Self::handle_prompt(prompt, renderer, input, window)
+                } else if renderer.keymap().pressed(input, Action::Quit) {
+                    if Self::request_exit(&mut export, &mut closing) {
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
+                    false
+                } else {
+                    // Keep the compared views together, or let them go separate ways
+                    if multiple && renderer.keymap().pressed(input, Action::SyncViews) {
+                        sync = !sync;
+                        println!("View sync {}", if sync { "on" } else { "off" });
+                    }
+                    Self::handle_input(
+                        renderer,
+                        input,
+                        pixels,
+                        window,
+                        prompt,
+                        &mut export,
+                        left_drag,
+                    )
+                };
+                if needs_update {
+                    pane.render().expect("pixels.render() failed");
+                    pane.window.request_redraw();
+                } else {
+                    pane.cancel_stale_render();
+                }
+            }
+
+            // Report a finished export, then exit if it was what held closing up
+            if export.as_ref().is_some_and(ExportTask::is_finished) {
+                if let Some(task) = export.take() {
+                    Self::finish_export(task);
+                }
+                if closing {
+                    *control_flow = ControlFlow::Exit;
+                    return;
+                }
+            }
+
+            // Handle window events
+            match event {
+                Event::WindowEvent { window_id, event } => match event {
+                    // Closing either window closes them all
+                    winit::event::WindowEvent::CloseRequested
+                        if Self::request_exit(&mut export, &mut closing) =>
+                    {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    winit::event::WindowEvent::Resized(size) => {
+                        if let Some(pane) =
+                            panes.iter_mut().find(|pane| pane.window.id() == window_id)
+                        {
+                            pane.resize(size).expect("Failed to resize pixels");
+                            pane.window.request_redraw();
+                        }
+                    }
+                    // Moving to a monitor with another scale factor keeps the
+                    // window's logical size, so its physical size changes
+                    winit::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        if let Some(pane) =
+                            panes.iter_mut().find(|pane| pane.window.id() == window_id)
+                        {
+                            pane.resize(*new_inner_size)
+                                .expect("Failed to resize pixels");
+                            pane.window.request_redraw();
+                        }
+                    }
+                    // Scans pause in the background to save power, and pick up
+                    // where they were once a window is focused again
+                    winit::event::WindowEvent::Focused(focused) => {
+                        if let Some(pane) =
+                            panes.iter_mut().find(|pane| pane.window.id() == window_id)
+                        {
+                            pane.focused = focused;
+                        }
+                        if focused {
+                            for pane in &panes {
+                                pane.window.request_redraw();
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                Event::RedrawRequested(window_id) => {
+                    let refine = panes.iter().any(|pane| pane.focused);
+                    if let Some(pane) = panes.iter_mut().find(|pane| pane.window.id() == window_id)
+                    {
+                        pane.redraw(refine);
+                    }
+                }
+                Event::MainEventsCleared => {
+                    let refine = panes.iter().any(|pane| pane.focused);
+                    let now = Instant::now();
+                    let mut redrawing = false;
+                    // When the soonest scan that is ahead of its frame budget is due
+                    let mut next_scan: Option<Instant> = None;
+                    for pane in &mut panes {
+                        pane.finish_render().expect("pixels.render() failed");
+                        let renderer = &pane.renderer;
+                        if pane.job.is_some() {
+                            // The job wakes the loop up once the frame is done,
+                            // and the animation moves on from there
+                        } else if renderer.is_auto_zooming()
+                            || renderer.is_animating()
+                            || renderer.is_cycling_palette()
+                        {
+                            pane.window.request_redraw();
+                            redrawing = true;
+                        } else if renderer.is_scanning() && refine {
+                            let due = pane.last_scan_frame + renderer.scan_config.frame_interval;
+                            if now >= due {
+                                pane.window.request_redraw();
+                                redrawing = true;
+                            } else {
+                                next_scan = Some(next_scan.map_or(due, |next| next.min(due)));
+                            }
+                        } else {
+                            // Not animating, so the next auto-zoom starts from a fresh clock
+                            pane.last_frame = now;
+                        }
+                    }
+                    // Sleep until the next slice rather than spin, unless
+                    // something else needs drawing straight away. With nothing
+                    // to draw, sleep until the next event, or until it is time
+                    // to check on a running export. Refocusing a window asks
+                    // for a redraw, and a finished render job sends an event,
+                    // either of which wakes the loop up again.
+                    let next_export = export.as_ref().map(|_| now + EXPORT_POLL_INTERVAL);
+                    *control_flow = match next_scan.into_iter().chain(next_export).min() {
+                        _ if redrawing => ControlFlow::Poll,
+                        Some(due) => ControlFlow::WaitUntil(due),
+                        None => ControlFlow::Wait,
+                    };
+                }
+                Event::LoopDestroyed => {
+                    // Nothing left to wait on, so give up rather than leave a truncated file
+                    if let Some(task) = export.take() {
+                        task.cancel();
+                        Self::finish_export(task);
+                    }
+                    *control_flow = ControlFlow::Exit;
+                }
+                _ => {}
+            }
+
+            if sync && multiple {
+                Self::sync_views(&mut panes, &mut synced_view);
+            }
+            for pane in &mut panes {
+                pane.update_title();
+            }
+        })
+    }
+
+    /// A second window for `--compare`, showing the view of `renderer` in
+    /// the scheme the split view would compare it with.
+    fn compare_pane(
+        event_loop: &EventLoop<()>,
+        args: &Args,
+        renderer: &Renderer,
+    ) -> Result<Pane, Error> {
+        let name = format!("{WINDOW_TITLE} - compare");
+        let window = Self::create_window(event_loop);
+        window.set_title(&name);
+        window.set_inner_size(LogicalSize::new(
+            args.get_width() as f64,
+            args.get_height() as f64,
+        ));
+        let pixels = Self::create_pixels(&window, args)?;
+
+        let mut renderer = renderer.clone();
+        let size = Self::buffer_size(&window, args);
+        renderer.resize(size.width, size.height);
+        renderer.change_color_scheme(compare_scheme_for(renderer.color_scheme));
+        // The GPU renderer belongs to the other window's surface
+        renderer.set_gpu(None);
+        if args.get_gpu() {
+            renderer.set_gpu(GpuRenderer::new(&pixels));
+        }
+
+        Ok(Pane::new(
+            name,
+            window,
+            pixels,
+            renderer,
+            WinitInputHelper::new(),
+            event_loop.create_proxy(),
+        ))
+    }
+
+    /// Moves every window to the view of the one that moved away from
+    /// `view`, and remembers that view.
+    fn sync_views(panes: &mut [Pane], view: &mut (Complex64, f64)) {
+        let Some(leader) = panes.iter().position(|pane| pane.view() != *view) else {
+            return;
+        };
+
+        *view = panes[leader].view();
+        for (index, pane) in panes.iter_mut().enumerate() {
+            if index != leader {
+                pane.renderer.set_view(view.0, view.1);
+                pane.render().expect("pixels.render() failed");
+                pane.window.request_redraw();
+            }
+        }
+    }
+
+    /// Applies the keys and mouse input of this step to `renderer`. Returns
+    /// whether the view changed and needs rendering again.
+    fn handle_input(
+        renderer: &mut Renderer,
+        input: &WinitInputHelper,
+        pixels: &mut Pixels,
+        window: &winit::window::Window,
+        prompt: &mut Option<String>,
+        export: &mut Option<ExportTask>,
+        left_drag: &mut Option<LeftDrag>,
+    ) -> bool {
+        let mut needs_update = false;
+        let keys = renderer.keymap().clone();
+
+        // Handle panning
+        let mut pan_x = 0.0;
+        let mut pan_y = 0.0;
+        if keys.held(input, Action::PanLeft) {
+            pan_x -= 1.0;
+        }
+        if keys.held(input, Action::PanRight) {
+            pan_x += 1.0;
+        }
+        if keys.held(input, Action::PanUp) {
+            pan_y -= 1.0;
+        }
+        if keys.held(input, Action::PanDown) {
+            pan_y += 1.0;
+        }
+
+        if renderer.is_pan_inertia() {
+            // Glide while a pan key is held and a bit after, the animation is
+            // driven by redraws
+            renderer.hold_pan(pan_x, pan_y);
+            if pan_x != 0.0 || pan_y != 0.0 {
+                window.request_redraw();
+            }
+        } else if pan_x != 0.0 || pan_y != 0.0 {
+            renderer.pan(pan_x * 0.05, pan_y * 0.05);
+            needs_update = true;
+        }
+
+        // Grab and drag the view with the left or middle mouse button
+        let mut drag_from = None;
+        if input.mouse_pressed(0) {
+            // Space makes it a drag from the start, Shift a box to zoom into
+            let moved = keys.held(input, Action::Grab);
+            *left_drag = input.mouse().map(|start| LeftDrag {
+                last: start,
+                start,
+                moved,
+                selecting: input.held_shift(),
+            });
+        }
+        // Window positions outside the frame are clamped to its edge
+        let frame_pos = |pixels: &Pixels, position| {
+            let (x, y) = pixels
+                .window_pos_to_pixel(position)
+                .unwrap_or_else(|outside| pixels.clamp_pixel_pos(outside));
+            (x as f64, y as f64)
+        };
+        if let (Some(drag), Some(mouse)) = (left_drag.as_mut(), input.mouse()) {
+            let distance = (mouse.0 - drag.start.0).hypot(mouse.1 - drag.start.1);
+            drag.moved |= distance > DRAG_THRESHOLD;
+            if drag.moved && input.mouse_held(0) {
+                if drag.selecting {
+                    if mouse != drag.last {
+                        let (from, to) = (frame_pos(pixels, drag.start), frame_pos(pixels, mouse));
+                        renderer.select(pixels.frame_mut(), from, to);
+                        renderer.present(pixels).expect("pixels.render() failed");
+                    }
+                } else {
+                    // Catch up on everything since the last drag, including the threshold
+                    drag_from = Some(drag.last);
+                }
+                drag.last = mouse;
+            }
+        }
+        let (diff_x, diff_y) = input.mouse_diff();
+        if input.mouse_held(2) && (diff_x, diff_y) != (0.0, 0.0) {
+            drag_from = input
+                .mouse()
+                .map(|(mouse_x, mouse_y)| (mouse_x - diff_x, mouse_y - diff_y));
+        }
+        if let (Some(from), Some(mouse)) = (drag_from, input.mouse()) {
+            let to_pixel = |pos| match pixels.window_pos_to_pixel(pos) {
+                Ok((x, y)) => (x as isize, y as isize),
+                Err(outside) => outside,
+            };
+            let (x, y) = to_pixel(mouse);
+            let (previous_x, previous_y) = to_pixel(from);
+
+            if (x, y) != (previous_x, previous_y) {
+                renderer.drag(pixels.frame_mut(), x - previous_x, y - previous_y);
+                renderer.present(pixels).expect("pixels.render() failed");
+                window.request_redraw();
+            }
+        }
+
+        // A left click that didn't turn into a drag centers on the point
+        // under the cursor and zooms in, a dragged out box is zoomed into
+        if input.mouse_released(0) {
+            if let Some(drag) = left_drag
+                .as_ref()
+                .filter(|drag| drag.selecting && drag.moved)
+            {
+                renderer.clear_selection(pixels.frame_mut());
+                if let Some(position) = input.mouse() {
+                    let (x0, y0) = frame_pos(pixels, drag.start);
+                    let (x1, y1) = frame_pos(pixels, position);
+                    renderer.zoom_to_rect(x0, y0, x1, y1);
+                }
+                needs_update = true;
+            }
+            if let (Some(drag), Some(position)) = (left_drag.take(), input.mouse()) {
+                if !drag.moved {
+                    if let Ok((x, y)) = pixels.window_pos_to_pixel(position) {
+                        renderer.zoom_at(x as f64, y as f64, CLICK_ZOOM);
+                        needs_update = true;
+                    }
+                }
+            }
+        }
+
+        // The mouse wheel zooms towards the point under the cursor
+        let scroll = input.scroll_diff();
+        if scroll != 0.0 {
+            if let Some(position) = input.mouse() {
+                let (x, y) = match pixels.window_pos_to_pixel(position) {
+                    Ok((x, y)) => (x as f64, y as f64),
+                    Err((x, y)) => (x as f64, y as f64),
+                };
+                renderer.zoom_towards(x, y, renderer.zoom_step().powf(scroll as f64));
+                needs_update = true;
+            }
+        }
+
+        // Zoom smoothly while a zoom key is held, the animation is driven by redraws
+        let zoom_in = keys.held(input, Action::ZoomIn);
+        let zoom_out = keys.held(input, Action::ZoomOut);
+        if zoom_in != zoom_out {
+            renderer.hold_zoom(zoom_in);
+            window.request_redraw();
+        } else {
+            renderer.release_zoom();
+        }
+
+        // Handle color scheme changes, with shift picking the right half of the split view
+        for scheme in ColorScheme::ALL {
+            if keys.pressed(input, Action::SelectScheme(scheme)) {
+                if input.held_shift() && renderer.compare_scheme().is_some() {
+                    renderer.set_compare_scheme(scheme);
+                } else {
+                    renderer.change_color_scheme(scheme);
+                }
+                needs_update = true;
+            }
+        }
+
+        // Switch between the Mandelbrot set and the Julia set of the point under the mouse
+        if keys.pressed(input, Action::ToggleJulia) {
+            let fractal = match renderer.fractal_kind() {
+                FractalKind::Mandelbrot => match input.mouse() {
+                    Some(position) => match pixels.window_pos_to_pixel(position) {
+                        Ok((x, y)) => FractalKind::Julia {
+                            c: renderer.pixel_to_complex(x as f64, y as f64),
+                        },
+                        Err(_) => FractalKind::DEFAULT_JULIA,
+                    },
+                    None => FractalKind::DEFAULT_JULIA,
+                },
+                FractalKind::BurningShip | FractalKind::Tricorn | FractalKind::Multibrot { .. } => {
+                    FractalKind::DEFAULT_JULIA
+                }
+                FractalKind::Julia { .. } | FractalKind::Newton => FractalKind::Mandelbrot,
+            };
+            renderer.set_fractal_kind(fractal);
+            needs_update = true;
+        }
+
+        // Cycle through the fractal kinds
+        if keys.pressed(input, Action::NextFractal) {
+            let fractal = renderer.fractal_kind().next();
+            renderer.set_fractal_kind(fractal);
+            needs_update = true;
+        }
+
+        // Change the power of the multibrot set
+        if let Some(power) = renderer.power() {
+            if keys.pressed(input, Action::RaisePower) {
+                renderer.set_power(power + 1.0);
+                needs_update = true;
+            }
+            if keys.pressed(input, Action::LowerPower) && power >= 3.0 {
+                renderer.set_power(power - 1.0);
+                needs_update = true;
+            }
+        }
+
+        // Trade detail for speed, always by at least one iteration
+        for (action, factor) in [
+            (Action::FewerIterations, 1.0 / ITERATION_STEP),
+            (Action::MoreIterations, ITERATION_STEP),
+        ] {
+            if keys.pressed(input, action) {
+                let current = renderer.max_iterations();
+                let scaled = (current as f64 * factor).round() as u32;
+                renderer.set_max_iterations(if factor > 1.0 {
+                    scaled.max(current + 1)
+                } else {
+                    scaled.min(current - 1)
+                });
+                println!("Max iterations: {}", renderer.max_iterations());
+                needs_update = true;
+            }
+        }
+
+        // Compare two palettes side by side
+        if keys.pressed(input, Action::Compare) {
+            renderer.toggle_compare();
+            needs_update = true;
+        }
+
+        // Switch and tune the orbit trap, selecting the orbit trap scheme
+        if keys.pressed(input, Action::OrbitTrap) {
+            let trap = match renderer.color_scheme {
+                ColorScheme::OrbitTrap => renderer.orbit_trap().next(),
+                _ => renderer.orbit_trap(),
+            };
+            renderer.change_color_scheme(ColorScheme::OrbitTrap);
+            renderer.set_orbit_trap(trap);
+            needs_update = true;
+        }
+        for (action, steps) in [(Action::ShrinkTrap, -1.0), (Action::GrowTrap, 1.0)] {
+            if keys.pressed(input, action) {
+                let mut trap = renderer.orbit_trap();
+                trap.adjust(steps);
+                renderer.set_orbit_trap(trap);
+                needs_update = true;
+            }
+        }
+
+        // Toggle smooth coloring for every palette
+        if keys.pressed(input, Action::SmoothColoring) {
+            renderer.set_smooth_coloring(!renderer.is_smooth_coloring());
+            needs_update = true;
+        }
+
+        // Toggle dithering of the iteration values
+        if keys.pressed(input, Action::Jitter) {
+            let jitter = if renderer.color_jitter() > 0.0 {
+                0.0
+            } else {
+                DEFAULT_COLOR_JITTER
+            };
+            renderer.set_color_jitter(jitter);
+            needs_update = true;
+        }
+
+        // Cycle the supersampling level, or with shift limit it to edges
+        if keys.pressed(input, Action::Supersampling) {
+            if input.held_shift() {
+                renderer.set_adaptive_aa(!renderer.is_adaptive_aa());
+            } else {
+                renderer.cycle_aa_samples();
+            }
+            needs_update = true;
+        }
+
+        // Toggle refining the view with a sample per frame while it stays still
+        if keys.pressed(input, Action::TemporalAa) {
+            renderer.set_temporal_aa(!renderer.is_temporal_aa());
+            let state = if renderer.is_temporal_aa() {
+                "on"
+            } else {
+                "off"
+            };
+            println!("Temporal supersampling {state}");
+            needs_update = true;
+        }
+
+        // Toggle the glow around the bright parts of the view
+        if keys.pressed(input, Action::Glow) {
+            renderer.set_glow_enabled(!renderer.is_glow_enabled());
+            let state = if renderer.is_glow_enabled() {
+                "on"
+            } else {
+                "off"
+            };
+            println!("Glow {state}");
+            needs_update = true;
+        }
+
+        // Compare blending the samples in linear light with averaging the bytes
+        if keys.pressed(input, Action::GammaCorrection) {
+            renderer.toggle_gamma_correction();
+            let state = if renderer.is_gamma_correction() {
+                "on"
+            } else {
+                "off"
+            };
+            println!("Gamma correction {state}");
+            needs_update = true;
+        }
+
+        // Flip the palette direction
+        if keys.pressed(input, Action::InvertPalette) {
+            renderer.toggle_invert_palette();
+            needs_update = true;
+        }
+
+        // Toggle cycling the palette with the zoom depth
+        if keys.pressed(input, Action::DepthCoupling) {
+            let coupling = if renderer.palette_depth_coupling() != 0.0 {
+                0.0
+            } else {
+                DEFAULT_PALETTE_DEPTH_COUPLING
+            };
+            renderer.set_palette_depth_coupling(coupling);
+            needs_update = true;
+        }
+
+        // Cycle through the tonemapping operators
+        if keys.pressed(input, Action::Tonemap) {
+            let tonemap = renderer.tonemap().next();
+            renderer.set_tonemap(tonemap);
+            needs_update = true;
+        }
+
+        // Draw equipotential lines over the potential scheme
+        if keys.pressed(input, Action::PotentialContours) {
+            renderer.toggle_potential_contours();
+            needs_update = true;
+        }
+
+        // Tune the escape velocity falloff
+        if keys.pressed(input, Action::LowerExponent) {
+            let exponent = renderer.escape_velocity_exponent();
+            renderer.set_escape_velocity_exponent(exponent / 1.5);
+            needs_update = true;
+        }
+        if keys.pressed(input, Action::RaiseExponent) {
+            let exponent = renderer.escape_velocity_exponent();
+            renderer.set_escape_velocity_exponent(exponent * 1.5);
+            needs_update = true;
+        }
+
+        // Head towards the most detailed part of the view
+        if keys.pressed(input, Action::FocusDetail) {
+            if renderer.focus_on_detail() {
+                needs_update = true;
+            } else {
+                println!("No detail found in the current view");
+            }
+        }
+
+        // Open the magnification prompt
+        if keys.pressed(input, Action::MagnificationPrompt) {
+            *prompt = Some(String::new());
+            window.set_title(&format!(
+                "{WINDOW_TITLE} - currently {:.3e}x, zoom to: _",
+                renderer.magnification()
+            ));
+            return false;
+        }
+
+        // Auto-zoom into a famous location, or with shift into the current detail
+        if keys.pressed(input, Action::AutoZoom) {
+            if input.held_shift() {
+                if !renderer.auto_zoom_into_detail() {
+                    println!("No detail found in the current view");
+                }
+            } else {
+                renderer.toggle_auto_zoom();
+            }
+            needs_update = true;
+        }
+
+        // Toggle iteration limits that follow the zoom depth, or on Shift pick
+        // just enough for the view once
+        if keys.pressed(input, Action::AutoIterations) {
+            if input.held_shift() {
+                let max_iterations = renderer.auto_tune_iterations();
+                println!("Max iterations: {max_iterations}");
+            } else {
+                renderer.toggle_auto_iterations();
+            }
+            needs_update = true;
+        }
+
+        // Start or stop cycling the palette
+        if keys.pressed(input, Action::PaletteCycling) {
+            renderer.toggle_palette_cycling();
+            window.request_redraw();
+        }
+
+        // Toggle the scale bar
+        if keys.pressed(input, Action::ScaleBar) {
+            renderer.toggle_scale_bar();
             needs_update = true;
         }
-        if input.key_pressed(VirtualKeyCode::Key4) {
-            renderer.change_color_scheme(ColorScheme::Blue);
-            needs_update = true;
+
+        // Toggle the HUD
+        if keys.pressed(input, Action::Hud) {
+            renderer.toggle_hud();
+            needs_update = true;
+        }
+
+        // Handle the loupe
+        if keys.pressed(input, Action::Loupe) {
+            renderer.toggle_loupe();
+            needs_update = true;
+        }
+
+        // Draw the orbit of the point under the cursor
+        if keys.pressed(input, Action::Orbit) {
+            renderer.toggle_orbit();
+            needs_update = true;
+        }
+
+        if renderer.is_loupe_enabled() || renderer.is_orbit_enabled() {
+            if let Some(Ok((x, y))) = input.mouse().map(|pos| pixels.window_pos_to_pixel(pos)) {
+                if !needs_update && input.mouse_diff() != (0.0, 0.0) {
+                    renderer.move_cursor(pixels.frame_mut(), x as f64, y as f64);
+                    renderer.present(pixels).expect("pixels.render() failed");
+                } else {
+                    renderer.set_cursor(x as f64, y as f64);
+                }
+            }
+        }
+
+        // Write the view to a file, or print a token to share it with on Shift,
+        // or go back to the one written last
+        if keys.pressed(input, Action::SaveView) && input.held_shift() {
+            println!("Location: {}", renderer.to_location_string());
+        } else if keys.pressed(input, Action::SaveView) {
+            match renderer.export_state().save(VIEW_STATE_PATH) {
+                Ok(()) => println!("Saved the view to {VIEW_STATE_PATH}"),
+                Err(err) => eprintln!("Failed to save the view to {VIEW_STATE_PATH}: {err}"),
+            }
+        }
+        // Back to the start, with the starting colors and iterations too on Shift
+        if keys.pressed(input, Action::Reset) {
+            renderer.reset();
+            if input.held_shift() {
+                renderer.restore_settings();
+            }
+            needs_update = true;
+        }
+        if keys.pressed(input, Action::LoadView) {
+            match ViewState::load(VIEW_STATE_PATH) {
+                Ok(state) => match renderer.load_state(state) {
+                    Ok(()) => needs_update = true,
+                    Err(err) => eprintln!("Ignored the view in {VIEW_STATE_PATH}: {err}"),
+                },
+                Err(err) => eprintln!("Failed to load the view from {VIEW_STATE_PATH}: {err}"),
+            }
+        }
+
+        // Save the colors of the current scheme as a strip, one column per iteration
+        if keys.pressed(input, Action::PaletteStrip) {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            let path = PathBuf::from(format!("palette_{timestamp}.png"));
+            let (width, height) = PALETTE_STRIP_SIZE;
+            match renderer.color_mode() {
+                Some(mode) => {
+                    let strip = color_map_strip(mode, width, width, height);
+                    match save_png(&path, &strip, width, height) {
+                        Ok(()) => println!("Saved the palette to {}", path.display()),
+                        Err(err) => {
+                            eprintln!("Failed to save the palette to {}: {err}", path.display())
+                        }
+                    }
+                }
+                None => println!("This color scheme doesn't color by iterations"),
+            }
+        }
+
+        // Handle exports: a 16 bit PNG, or on Shift the raw iteration counts, or a
+        // screenshot at twice the window size
+        let save_png16 = keys.pressed(input, Action::ExportPng16);
+        let save_iterations = save_png16 && input.held_shift();
+        if save_png16 || keys.pressed(input, Action::Screenshot) {
+            if let Some(task) = export {
+                println!("Still exporting to {}", task.path().display());
+            } else {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+                let task = if save_iterations {
+                    let path = PathBuf::from(format!("fractal_{timestamp}_iterations.pgm"));
+                    ExportTask::spawn_iterations(renderer.clone(), path)
+                } else if save_png16 {
+                    let path = PathBuf::from(format!("fractal_{timestamp}_16bit.png"));
+                    ExportTask::spawn_png16(renderer.clone(), path)
+                } else {
+                    let path = PathBuf::from(format!("fractal_{timestamp}.png"));
+                    let (width, height) = (renderer.width() * 2, renderer.height() * 2);
+                    ExportTask::spawn_png(renderer.clone(), path, width, height)
+                };
+                println!("Exporting to {}", task.path().display());
+                *export = Some(task);
+            }
+        }
+
+        needs_update
+    }
+
+    /// Whether the event loop can exit now. A running export gets to finish
+    /// first; asking again cancels it instead.
+    fn request_exit(export: &mut Option<ExportTask>, closing: &mut bool) -> bool {
+        let Some(task) = export.as_ref() else {
+            return true;
+        };
+
+        if !*closing {
+            *closing = true;
+            println!(
+                "Waiting for the export to {} to finish, close again to cancel it",
+                task.path().display()
+            );
+            return false;
+        }
+
+        task.cancel();
+        if let Some(task) = export.take() {
+            Self::finish_export(task);
+        }
+        true
+    }
+
+    /// Waits for the export and reports how it went.
+    fn finish_export(task: ExportTask) {
+        let path = task.path().to_path_buf();
+        match task.wait() {
+            Ok(()) => println!("Saved {}", path.display()),
+            Err(ExportError::Cancelled) => println!("Cancelled the export to {}", path.display()),
+            Err(err) => eprintln!("Failed to save {}: {err}", path.display()),
+        }
+    }
+
+    /// Collects typed characters for the magnification prompt, shown in the
+    /// window title, and applies the value on Enter. Returns whether that
+    /// changed the view.
+    fn handle_prompt(
+        prompt: &mut Option<String>,
+        renderer: &mut Renderer,
+        input: &WinitInputHelper,
+        window: &winit::window::Window,
+    ) -> bool {
+        let Some(text) = prompt.as_mut() else {
+            return false;
+        };
+
+        if input.key_pressed(VirtualKeyCode::Escape) {
+            *prompt = None;
+            window.set_title(WINDOW_TITLE);
+            return false;
+        }
+
+        if input.key_pressed(VirtualKeyCode::Return)
+            || input.key_pressed(VirtualKeyCode::NumpadEnter)
+        {
+            let value = text.trim().trim_end_matches(['x', 'X', '×']);
+            let needs_update = match value.parse::<f64>() {
+                Ok(magnification) => {
+                    renderer.set_magnification(magnification);
+                    true
+                }
+                Err(_) => {
+                    eprintln!("Invalid magnification: {text}");
+                    false
+                }
+            };
+
+            *prompt = None;
+            window.set_title(WINDOW_TITLE);
+            return needs_update;
+        }
+
+        for character in input.text() {
+            match character {
+                TextChar::Char(c) if c.is_ascii_alphanumeric() || "+-.×".contains(c) => {
+                    text.push(c)
+                }
+                TextChar::Back => {
+                    text.pop();
+                }
+                _ => {}
+            }
+        }
+        window.set_title(&format!("{WINDOW_TITLE} - zoom to: {text}_"));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn test_renderer(width: u32, height: u32) -> Renderer {
+        let mut renderer = Renderer::new();
+        renderer.viewport.width = width;
+        renderer.viewport.height = height;
+        renderer.scan_config.enabled = false;
+        renderer
+    }
+
+    #[test]
+    fn test_zoom_at_centers_on_pixel() {
+        let mut renderer = test_renderer(80, 60);
+        let target = renderer.pixel_to_complex(20.0, 15.0);
+        let scale = renderer.viewport.scale;
+
+        renderer.zoom_at(20.0, 15.0, 0.5);
+        assert_eq!(renderer.viewport.center(), target);
+        assert_eq!(renderer.viewport.scale, scale * 0.5);
+
+        // Zooming out past the limit still recenters but keeps the scale
+        renderer.viewport.scale = 8.0;
+        renderer.zoom_at(40.0, 30.0, 2.0);
+        assert_eq!(renderer.viewport.center(), target);
+        assert_eq!(renderer.viewport.scale, 8.0);
+    }
+
+    #[test]
+    fn test_zoom_towards_keeps_point_under_cursor() {
+        let mut renderer = test_renderer(80, 60);
+        let target = renderer.pixel_to_complex(20.0, 15.0);
+        let scale = renderer.viewport.scale;
+
+        renderer.zoom_towards(20.0, 15.0, 0.5);
+        assert!((renderer.pixel_to_complex(20.0, 15.0) - target).norm() < 1e-12);
+        assert_eq!(renderer.viewport.scale, scale * 0.5);
+
+        // Nothing moves when the zoom is refused
+        renderer.viewport.scale = 8.0;
+        let center = renderer.viewport.center();
+        renderer.zoom_towards(20.0, 15.0, 2.0);
+        assert!((renderer.viewport.center() - center).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_drag_matches_full_render() {
+        for (dx, dy) in [(7, 0), (0, -5), (-12, 9), (3, 3)] {
+            let mut renderer = test_renderer(64, 48);
+            let mut frame = vec![0; 64 * 48 * 4];
+            renderer.render(&mut frame);
+
+            renderer.drag(&mut frame, dx, dy);
+
+            let mut expected = vec![0; 64 * 48 * 4];
+            renderer.render(&mut expected);
+            assert!(frame == expected, "drag by ({dx}, {dy}) differs");
+        }
+    }
+
+    #[test]
+    fn test_drag_redraws_scale_bar() {
+        for (dx, dy) in [(7, 0), (0, -5), (-12, 9)] {
+            let mut renderer = test_renderer(160, 96);
+            renderer.toggle_scale_bar();
+            let mut frame = vec![0; 160 * 96 * 4];
+            renderer.render(&mut frame);
+
+            renderer.drag(&mut frame, dx, dy);
+
+            let mut expected = vec![0; 160 * 96 * 4];
+            renderer.render(&mut expected);
+            assert!(frame == expected, "drag by ({dx}, {dy}) differs");
+        }
+    }
+
+    #[test]
+    fn test_hud_is_drawn_over_scan_passes() {
+        let mut renderer = test_renderer(240, 96);
+        renderer.scan_config = ScanConfig::default();
+        let mut plain = vec![0; 240 * 96 * 4];
+        while renderer.is_scanning() {
+            renderer.render(&mut plain);
+        }
+
+        renderer.toggle_hud();
+        let mut frame = vec![0; 240 * 96 * 4];
+        renderer.render(&mut frame);
+        // The backing box is black apart from the white text
+        let corner = (HUD_MARGIN * 240 + HUD_MARGIN) * 4;
+        assert_eq!(&frame[corner..corner + 4], &[0, 0, 0, 255]);
+        assert!(frame.chunks_exact(4).any(|pixel| pixel == [255; 4]));
+
+        // Toggling it off restarts the scan, which covers the HUD up again
+        renderer.toggle_hud();
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+        assert!(frame == plain);
+    }
+
+    #[test]
+    fn test_iteration_counts_are_traced_in_bands() {
+        // A height that doesn't end on a whole band of tiles
+        let renderer = test_renderer(40, TRACE_TILE as u32 * 2 + 5);
+        let traced: Vec<u32> = renderer
+            .trace_region(&renderer.viewport)
+            .iter()
+            .map(|escape| escape.iterations)
+            .collect();
+        assert_eq!(renderer.iteration_counts(), traced);
+        assert_eq!(
+            renderer.iteration_counts_cancellable(&AtomicBool::new(true)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_auto_tune_iterations_resolves_the_view() {
+        let mut renderer = test_renderer(64, 48);
+        let wide = renderer.auto_tune_iterations();
+        assert_eq!(renderer.max_iterations(), wide);
+        assert!(wide.is_power_of_two() && wide >= AUTO_TUNE_LIMITS.0);
+
+        // Doubling the tuned limit lets hardly any more pixels escape
+        let escaped = |renderer: &Renderer| {
+            renderer
+                .iteration_counts()
+                .iter()
+                .filter(|&&count| count < renderer.max_iterations())
+                .count()
+        };
+        let tuned = escaped(&renderer);
+        renderer.set_max_iterations(wide * 2);
+        assert!(escaped(&renderer) - tuned <= 64 * 48 / 100);
+
+        // Near the boundary deep in, far more are needed than for the whole set
+        renderer.set_view(Complex64::new(-0.743_643_887, 0.131_825_904), 1e-6);
+        let deep = renderer.auto_tune_iterations();
+        assert!(deep > wide, "{deep} <= {wide}");
+        assert!(deep <= AUTO_TUNE_LIMITS.1);
+    }
+
+    #[test]
+    fn test_auto_iterations_follow_zoom() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.scan_config = ScanConfig::default();
+        renderer.viewport.scale = DEFAULT_SCALE;
+        renderer.toggle_auto_iterations();
+        assert_eq!(renderer.max_iterations, 200);
+
+        let mut frame = vec![0; 32 * 24 * 4];
+        renderer.render(&mut frame);
+        renderer.render(&mut frame);
+        assert_eq!(renderer.scan_level, 2);
+
+        // Zooming in mid-scan without restarting it, the new limit still does
+        renderer.viewport.scale = DEFAULT_SCALE / 16.0;
+        renderer.render(&mut frame);
+        assert_eq!(
+            renderer.max_iterations,
+            200 + 4 * ITERATIONS_PER_OCTAVE as u32
+        );
+        assert_eq!(renderer.scan_level, 1);
+
+        renderer.toggle_auto_iterations();
+        assert_eq!(renderer.max_iterations, 200);
+        assert_eq!(renderer.scan_level, 0);
+    }
+
+    #[test]
+    fn test_set_max_iterations() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.scan_config = ScanConfig::default();
+        renderer.scan_level = 3;
+        renderer.set_max_iterations(0);
+        assert_eq!(renderer.max_iterations(), 1);
+        assert_eq!(renderer.scan_level, 0);
+
+        // Deeper views keep their extra iterations on top
+        renderer.set_max_iterations(200);
+        renderer.viewport.scale = DEFAULT_SCALE / 16.0;
+        renderer.toggle_auto_iterations();
+        renderer.update_auto_iterations();
+        renderer.set_max_iterations(renderer.max_iterations() + 50);
+        renderer.update_auto_iterations();
+        assert_eq!(
+            renderer.max_iterations(),
+            250 + 4 * ITERATIONS_PER_OCTAVE as u32
+        );
+    }
+
+    #[test]
+    fn test_perceptual_blending_recolors_smooth_and_gradient() {
+        let mut renderer = test_renderer(32, 24);
+        for scheme in [
+            ColorScheme::Smooth,
+            ColorScheme::Gradient,
+            ColorScheme::Zebra,
+        ] {
+            renderer.set_perceptual(false);
+            renderer.change_color_scheme(scheme);
+            let mut channels = vec![0; 32 * 24 * 4];
+            renderer.render(&mut channels);
+
+            renderer.set_perceptual(true);
+            let mut perceptual = vec![0; 32 * 24 * 4];
+            renderer.render(&mut perceptual);
+            assert_eq!(
+                channels != perceptual,
+                scheme != ColorScheme::Zebra,
+                "{scheme:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_palette_cycling_recolors_from_cache() {
+        let mut renderer = test_renderer(64, 48);
+        let mut frame = vec![0; 64 * 48 * 4];
+        renderer.render(&mut frame);
+        assert!(renderer.cached_escapes().is_some());
+
+        let before = frame.clone();
+        renderer.toggle_palette_cycling();
+        renderer.cycle_palette(&mut frame, 0.05);
+        assert!(renderer.color_offset > 0.0);
+        assert!(frame != before);
+
+        // Recoloring gives the same picture as computing it all again
+        renderer.escape_cache = None;
+        let mut expected = vec![0; 64 * 48 * 4];
+        renderer.render(&mut expected);
+        assert!(frame == expected);
+
+        // A cache of another view isn't used
+        renderer.pan(0.1, 0.0);
+        assert!(renderer.cached_escapes().is_none());
+        assert!(!renderer.recolor(&mut frame));
+    }
+
+    #[test]
+    fn test_stripe_frequency_invalidates_cached_escapes() {
+        let mut renderer = test_renderer(64, 48);
+        let mut frame = vec![0; 64 * 48 * 4];
+        renderer.render(&mut frame);
+        let smooth = frame.clone();
+
+        // The plain escapes have no stripes to color by
+        renderer.change_color_scheme(ColorScheme::StripeAverage);
+        assert!(renderer.cached_escapes().is_none());
+        renderer.render(&mut frame);
+        assert!(renderer.cached_escapes().is_some());
+        assert!(frame != smooth);
+
+        let striped = frame.clone();
+        renderer.set_stripe_frequency(2.0);
+        assert!(renderer.cached_escapes().is_none());
+        renderer.render(&mut frame);
+        assert!(frame != striped);
+
+        let mut fresh = test_renderer(64, 48);
+        fresh.change_color_scheme(ColorScheme::StripeAverage);
+        fresh.set_stripe_frequency(2.0);
+        let mut expected = vec![0; 64 * 48 * 4];
+        fresh.render(&mut expected);
+        assert!(frame == expected);
+    }
+
+    #[test]
+    fn test_scheme_switch_recolors_at_once() {
+        let mut renderer = test_renderer(64, 48);
+        renderer.scan_config = ScanConfig::default();
+        let mut frame = vec![0; 64 * 48 * 4];
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+
+        for scheme in [
+            ColorScheme::Rainbow,
+            ColorScheme::Histogram,
+            ColorScheme::Smooth,
+        ] {
+            renderer.change_color_scheme(scheme);
+            renderer.render(&mut frame);
+            assert!(!renderer.is_scanning());
+
+            let mut fresh = test_renderer(64, 48);
+            fresh.change_color_scheme(scheme);
+            let mut expected = vec![0; 64 * 48 * 4];
+            fresh.render(&mut expected);
+            assert!(frame == expected);
+        }
+
+        // Moving the view needs the escapes computed again
+        renderer.zoom(0.5);
+        renderer.render(&mut frame);
+        assert!(renderer.is_scanning());
+    }
+
+    #[test]
+    fn test_input_restarts_only_fine_passes() {
+        let mut renderer = test_renderer(160, 200);
+        renderer.scan_config = ScanConfig::default();
+        let mut frame = vec![0; 160 * 200 * 4];
+        renderer.render(&mut frame);
+        renderer.render(&mut frame);
+        assert!(renderer.scan_level >= 1);
+
+        // Input mid-refinement draws the whole coarse pass of the new view
+        renderer.zoom(0.5);
+        renderer.render(&mut frame);
+        assert_eq!((renderer.scan_level, renderer.scan_row), (1, 0));
+        let mut coarse = vec![0; 160 * 200 * 4];
+        renderer.render_with_stride(&mut coarse, 8, 0..200);
+        assert!(frame == coarse);
+
+        // The slices of the fine passes add up to a full render
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+        let mut plain = renderer.clone();
+        plain.scan_config.enabled = false;
+        plain.escape_cache = None;
+        let mut expected = vec![0; 160 * 200 * 4];
+        plain.render(&mut expected);
+        assert!(frame == expected);
+        assert!(renderer.cached_escapes() == plain.cached_escapes());
+
+        // Sliced tracing gives the same escapes as tracing the whole view
+        let sliced: Vec<EscapeResult> = (0..200)
+            .step_by(SCAN_SLICE_ROWS)
+            .flat_map(|y| {
+                renderer.trace_rows(&renderer.viewport, y..(y + SCAN_SLICE_ROWS).min(200))
+            })
+            .collect();
+        assert!(sliced == renderer.trace_region(&renderer.viewport));
+    }
+
+    #[test]
+    fn test_scan_progress_counts_every_pass() {
+        let mut renderer = test_renderer(160, 400);
+        assert_eq!(renderer.scan_progress(), 1.0);
+
+        for (initial_stride, passes) in [(8, 4), (6, 3), (1, 1)] {
+            renderer.scan_config = ScanConfig {
+                initial_stride,
+                ..ScanConfig::default()
+            };
+            renderer.set_max_iterations(renderer.max_iterations() + 1);
+            assert_eq!(renderer.scan_progress(), 0.0);
+
+            let mut frame = vec![0; 160 * 400 * 4];
+            let mut progress = 0.0;
+            let mut finished = Vec::new();
+            while renderer.is_scanning() {
+                renderer.render(&mut frame);
+                assert!(renderer.scan_progress() > progress);
+                progress = renderer.scan_progress();
+                if renderer.scan_row == 0 {
+                    finished.push(progress);
+                }
+            }
+            let expected: Vec<f32> = (1..=passes)
+                .map(|pass| pass as f32 / passes as f32)
+                .collect();
+            assert_eq!(finished, expected, "initial stride {initial_stride}");
         }
-        if input.key_pressed(VirtualKeyCode::Key5) {
-            renderer.change_color_scheme(ColorScheme::BlackAndWhite);
-            needs_update = true;
+    }
+
+    #[test]
+    fn test_double_buffered_scan_shows_whole_passes() {
+        let mut renderer = test_renderer(160, 200);
+        renderer.scan_config = ScanConfig {
+            present_interval: Duration::from_secs(3600),
+            ..ScanConfig::default()
+        };
+        let mut plain = renderer.clone();
+        plain.scan_config.double_buffered = false;
+
+        // Every frame shown is one a plain scan shows at the end of a pass
+        let mut frame = vec![0; 160 * 200 * 4];
+        let mut passes = Vec::new();
+        let mut plain_frame = frame.clone();
+        while plain.is_scanning() {
+            plain.render(&mut plain_frame);
+            if plain.scan_row == 0 {
+                passes.push(plain_frame.clone());
+            }
         }
-        if input.key_pressed(VirtualKeyCode::Key6) {
-            renderer.change_color_scheme(ColorScheme::Rainbow);
-            needs_update = true;
+        let mut shown = 0;
+        while renderer.is_scanning() {
+            let before = frame.clone();
+            if renderer.render_buffered(&mut frame) {
+                assert!(frame == passes[shown]);
+                shown += 1;
+            } else {
+                assert!(frame == before);
+            }
         }
-        if input.key_pressed(VirtualKeyCode::Key7) {
-            renderer.change_color_scheme(ColorScheme::Psychedelic);
-            needs_update = true;
+        assert_eq!(shown, passes.len());
+        assert!(frame == plain_frame);
+
+        // Drawing to the frame directly takes the back buffer along
+        renderer.zoom(0.5);
+        renderer.render_buffered(&mut frame);
+        renderer.drag(&mut frame, 5, 0);
+        assert!(renderer.back_buffer.is_empty());
+        while renderer.is_scanning() {
+            renderer.render_buffered(&mut frame);
         }
-        if input.key_pressed(VirtualKeyCode::Key8) {
-            renderer.change_color_scheme(ColorScheme::GreenGradient);
-            needs_update = true;
+        plain.zoom(0.5);
+        plain.pan_pixels(5.0, 0.0);
+        plain.scan_config.enabled = false;
+        plain.render(&mut plain_frame);
+        assert!(frame == plain_frame);
+    }
+
+    #[test]
+    fn test_scan_passes_cover_every_pixel() {
+        for initial_stride in 1..=9 {
+            let mut renderer = test_renderer(37, 23);
+            renderer.scan_config = ScanConfig {
+                enabled: true,
+                initial_stride,
+                ..ScanConfig::default()
+            };
+            // Nothing is ever drawn with a zero alpha
+            let mut frame = vec![0; 37 * 23 * 4];
+            let mut passes = 0;
+            while renderer.is_scanning() {
+                renderer.render(&mut frame);
+                passes += 1;
+            }
+
+            assert!(passes >= 1, "stride {initial_stride}");
+            assert!(
+                frame.chunks_exact(4).all(|pixel| pixel[3] == 255),
+                "stride {initial_stride} left gaps"
+            );
         }
-        if input.key_pressed(VirtualKeyCode::Key9) {
-            renderer.change_color_scheme(ColorScheme::Electric);
-            needs_update = true;
+    }
+
+    #[test]
+    fn test_reset_returns_home() {
+        let mut renderer = test_renderer(80, 60);
+        let home = renderer.viewport;
+        renderer.pan(1.0, -2.0);
+        renderer.zoom_at(10.0, 10.0, 0.01);
+        renderer.change_color_scheme(ColorScheme::Rainbow);
+        renderer.max_iterations = 1000;
+
+        renderer.reset();
+        assert_eq!(renderer.viewport, home);
+        assert!(matches!(renderer.color_scheme, ColorScheme::Rainbow));
+
+        renderer.restore_settings();
+        assert!(matches!(renderer.color_scheme, ColorScheme::Smooth));
+        assert_eq!(renderer.max_iterations, 200);
+    }
+
+    #[test]
+    fn test_view_follows_corners_from_args() {
+        let (upper_left, lower_right) = (Complex64::new(-2.0, 1.0), Complex64::new(1.0, -1.0));
+        let args = Args::new(
+            600,
+            400,
+            100,
+            false,
+            upper_left,
+            lower_right,
+            ColorScheme::Red,
+        );
+        let renderer = Renderer::from_args(&args);
+        assert_eq!(renderer.viewport.center(), Complex64::new(-0.5, 0.0));
+        assert_eq!(renderer.viewport.scale, 2.0);
+
+        // A wider region than the frame fits its width
+        let args = args.with_bounds(Complex64::new(-3.0, 1.0), Complex64::new(1.0, -1.0));
+        let renderer = Renderer::from_args(&args);
+        assert_eq!(renderer.viewport.center(), Complex64::new(-1.0, 0.0));
+        assert!((renderer.viewport.scale - 4.0 * 400.0 / 600.0).abs() < 1e-12);
+
+        // The default corners leave the fractal's own view, and a given
+        // center and scale win over the corners
+        let renderer = Renderer::from_args(&Args::default());
+        assert_eq!(renderer.viewport.center(), Complex64::new(-0.5, 0.0));
+        let args = args.with_center(Complex64::new(0.25, 0.0)).with_scale(0.5);
+        let renderer = Renderer::from_args(&args);
+        assert_eq!(renderer.viewport.center(), Complex64::new(0.25, 0.0));
+        assert_eq!(renderer.viewport.scale, 0.5);
+    }
+
+    #[test]
+    fn test_pan_reuses_cached_escapes() {
+        for scheme in [ColorScheme::Smooth, ColorScheme::Histogram] {
+            let mut renderer = test_renderer(64, 48);
+            renderer.change_color_scheme(scheme);
+            let mut frame = vec![0; 64 * 48 * 4];
+            renderer.render(&mut frame);
+
+            for (dx, dy) in [(0.1, 0.0), (-0.2, 0.3), (0.0, -0.05)] {
+                renderer.pan(dx, dy);
+                renderer.render(&mut frame);
+                let cache = renderer.escape_cache.as_ref().unwrap();
+                assert_eq!(cache.viewport, renderer.viewport);
+
+                // The shifted pixels hold what computing them afresh gives
+                let fresh = renderer.compute_region(&renderer.viewport);
+                let iterations = |escapes: &[EscapeResult]| {
+                    escapes
+                        .iter()
+                        .map(|escape| (escape.iterations, escape.escaped))
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(iterations(&cache.escapes), iterations(&fresh));
+
+                let mut expected = vec![0; 64 * 48 * 4];
+                let mut fresh_renderer = renderer.clone();
+                fresh_renderer.escape_cache = None;
+                fresh_renderer.render(&mut expected);
+                assert!(frame == expected, "{scheme:?} after {dx}, {dy}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_pans_add_up() {
+        let mut renderer = test_renderer(64, 48);
+        let start = renderer.viewport.center();
+        // A third of a pixel at a time, which rounds to nothing on its own
+        let step = renderer.viewport.pixel_size() / 3.0;
+        let amount = step / (renderer.viewport.scale * renderer.pan_speed());
+        for _ in 0..30 {
+            renderer.pan(amount, -amount);
         }
 
-        if needs_update {
-            renderer.render(pixels.frame_mut());
-            pixels.render().expect("pixels.render() failed");
-            window.request_redraw();
+        let moved = renderer.viewport.center() - start;
+        let pixel_size = renderer.viewport.pixel_size();
+        assert!(
+            (moved.re - 10.0 * pixel_size).abs() <= pixel_size / 2.0,
+            "{moved}"
+        );
+        assert!(
+            (moved.im + 10.0 * pixel_size).abs() <= pixel_size / 2.0,
+            "{moved}"
+        );
+    }
+
+    #[test]
+    fn test_zoom_to_rect_fits_the_box() {
+        let mut renderer = test_renderer(160, 120);
+        let corners = [(10.0, 20.0), (50.0, 40.0)].map(|(x, y)| renderer.pixel_to_complex(x, y));
+        let center = renderer.pixel_to_complex(30.0, 30.0);
+        let scale = renderer.viewport.scale;
+
+        // Dragging up and to the left selects the same box
+        let mut inverted = renderer.clone();
+        renderer.zoom_to_rect(10.0, 20.0, 50.0, 40.0);
+        inverted.zoom_to_rect(50.0, 40.0, 10.0, 20.0);
+        for renderer in [&renderer, &inverted] {
+            assert!((renderer.viewport.center() - center).norm() < 1e-12);
+            // The box is wider than the frame's shape, so it spans the width
+            assert!((renderer.viewport.scale - scale * 0.25).abs() < 1e-12);
+            for corner in corners {
+                let (x, y) = renderer.viewport.complex_to_pixel(corner);
+                assert!((-1e-6..=160.0 + 1e-6).contains(&x), "{x}");
+                assert!((-1e-6..=120.0 + 1e-6).contains(&y), "{y}");
+            }
+        }
+
+        // A shift-click without a box leaves the view alone
+        let view = renderer.viewport;
+        renderer.zoom_to_rect(5.0, 5.0, 5.0, 30.0);
+        assert!(renderer.viewport == view);
+    }
+
+    #[test]
+    fn test_selection_is_drawn_and_cleared() {
+        let mut renderer = test_renderer(64, 48);
+        let mut frame = vec![0; 64 * 48 * 4];
+        renderer.render(&mut frame);
+        let plain = frame.clone();
+
+        renderer.select(&mut frame, (40.0, 30.0), (10.0, 5.0));
+        let at = |frame: &[u8], x: usize, y: usize| frame[(y * 64 + x) * 4..][..4].to_vec();
+        for (x, y) in [(10, 5), (40, 30), (25, 5), (10, 20)] {
+            assert_eq!(at(&frame, x, y), [255; 4], "({x}, {y})");
+        }
+        // Only the outline is drawn
+        assert_eq!(at(&frame, 25, 20), at(&plain, 25, 20));
+
+        // Moving it leaves nothing of the previous box behind
+        renderer.select(&mut frame, (40.0, 30.0), (100.0, -10.0));
+        assert_eq!(at(&frame, 10, 5), at(&plain, 10, 5));
+        assert_eq!(at(&frame, 63, 0), [255; 4]);
+        renderer.clear_selection(&mut frame);
+        assert!(frame == plain);
+    }
+
+    #[test]
+    fn test_resize_renders_at_new_size() {
+        let mut renderer = test_renderer(40, 30);
+        renderer.scan_config.enabled = true;
+        let mut frame = vec![0; 40 * 30 * 4];
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+
+        renderer.resize(64, 20);
+        assert!(renderer.is_scanning());
+        assert_eq!((renderer.width(), renderer.height()), (64, 20));
+        let mut frame = vec![0; 64 * 20 * 4];
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+
+        let mut expected = vec![0; 64 * 20 * 4];
+        test_renderer(64, 20).render(&mut expected);
+        assert!(frame == expected);
+    }
+
+    #[test]
+    fn test_custom_fractal_replaces_builtin() {
+        /// Everything outside the unit circle escapes at once
+        struct Disk;
+        impl Fractal for Disk {
+            fn iterate(&self, c: Complex64, max_iter: u32, _bailout: f64) -> EscapeResult {
+                let escaped = c.norm() > 1.0;
+                EscapeResult {
+                    iterations: if escaped { 1 } else { max_iter },
+                    escaped,
+                    final_z: c,
+                    stripe: 0.0,
+                }
+            }
+        }
+
+        let mut expected = vec![0; 48 * 32 * 4];
+        let mut frame = vec![0; 48 * 32 * 4];
+        // The custom fractal is handed the renderer's bailout too
+        for bailout in [DEFAULT_BAILOUT, 100.0] {
+            let mut builtin = test_renderer(48, 32);
+            builtin.set_bailout(bailout);
+            builtin.render(&mut expected);
+            let mut renderer = test_renderer(48, 32);
+            renderer.set_bailout(bailout);
+            renderer.set_custom_fractal(Some(Arc::new(FractalKind::Mandelbrot)));
+            renderer.render(&mut frame);
+            assert!(frame == expected, "bailout {bailout}");
+        }
+
+        let mut renderer = test_renderer(48, 32);
+
+        renderer.set_custom_fractal(Some(Arc::new(Disk)));
+        renderer.change_color_scheme(ColorScheme::OrbitTrap);
+        renderer.render(&mut frame);
+        for (px, py) in [(0.0, 0.0), (24.0, 16.0), (47.0, 31.0)] {
+            let c = renderer.pixel_to_complex(px, py);
+            let index = (py as usize * 48 + px as usize) * 4;
+            let black = frame[index..index + 3] == [0, 0, 0];
+            assert_eq!(black, c.norm() <= 1.0, "{c}");
+        }
+    }
+
+    #[test]
+    fn test_gradient_lut_matches_formula() {
+        for scheme in [
+            ColorScheme::Red,
+            ColorScheme::Blue,
+            ColorScheme::GreenGradient,
+            ColorScheme::BlackAndWhite,
+        ] {
+            let mut renderer = test_renderer(64, 48);
+            renderer.change_color_scheme(scheme);
+            for max_iterations in [50, 333] {
+                renderer.set_max_iterations(max_iterations);
+                let mut frame = vec![0; 64 * 48 * 4];
+                renderer.render(&mut frame);
+                let lut = renderer.gradient_lut.as_ref().unwrap();
+                assert_eq!(lut.colors.len(), max_iterations as usize);
+
+                let mut plain = renderer.clone();
+                plain.gradient_lut = None;
+                let mut expected = vec![0; 64 * 48 * 4];
+                plain.render_full(&mut expected);
+                assert!(frame == expected, "{scheme:?} at {max_iterations}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_odd_sized_frames_are_filled() {
+        // Sizes that divide evenly into neither the strides nor the threads
+        let mut renderer = test_renderer(799, 601);
+        renderer.max_iterations = 50;
+
+        for stride in [1, 2, 3, 4, 8, 16] {
+            let mut frame = vec![0; 799 * 601 * 4];
+            renderer.render_with_stride(&mut frame, stride, 0..601);
+            assert!(
+                frame.chunks_exact(4).all(|pixel| pixel[3] == 255),
+                "stride {stride} left gaps"
+            );
+        }
+
+        let mut frame = vec![0; 799 * 601 * 4];
+        renderer.render(&mut frame);
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_tiled_render_matches_full_render() {
+        let views = [
+            (FractalKind::Mandelbrot, Complex64::new(-0.5, 0.0), 3.0),
+            (FractalKind::Mandelbrot, Complex64::new(-0.1, 0.0), 0.5),
+            (FractalKind::Mandelbrot, Complex64::new(-0.745, 0.113), 0.01),
+            (
+                FractalKind::Julia {
+                    c: Complex64::new(-0.4, 0.6),
+                },
+                Complex64::new(0.0, 0.0),
+                3.0,
+            ),
+        ];
+        for (fractal, center, scale) in views {
+            let mut renderer = test_renderer(150, 110);
+            renderer.fractal = fractal;
+            renderer.set_view(center, scale);
+
+            let mut tiled = vec![0; 150 * 110 * 4];
+            renderer.render_tiled(&mut tiled);
+            let mut full = vec![0; 150 * 110 * 4];
+            renderer.render_rect(&mut full, 0, 0, 150, 110);
+            assert!(tiled == full, "{fractal:?} at {center}");
+        }
+    }
+
+    #[test]
+    fn test_render_tiles_matches_rows() {
+        let mut renderer = test_renderer(150, 110);
+        renderer.change_color_scheme(ColorScheme::OrbitTrap);
+        for aa_samples in [1, 2] {
+            renderer.aa_samples = aa_samples;
+            let mut rows = vec![0; 150 * 110 * 4];
+            renderer.render_rect(&mut rows, 0, 0, 150, 110);
+
+            // Sizes that divide the frame evenly, unevenly and not at all
+            for tile_size in [1, 10, 32, 37, 200] {
+                let mut tiles = vec![0; 150 * 110 * 4];
+                renderer.render_tiles(&mut tiles, tile_size);
+                assert!(tiles == rows, "{tile_size} pixel tiles at {aa_samples}x");
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_zoom_deepens_then_restarts() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.toggle_auto_zoom();
+        let target = renderer.auto_zoom.as_ref().unwrap().target;
+
+        renderer.step_auto_zoom(0.1);
+        assert!(renderer.magnification() > 1.0);
+        assert!(renderer.max_iterations > 200);
+
+        // Run until f64 gives out, which sends it back to the full set
+        let mut steps = 0;
+        while renderer.auto_zoom.as_ref().unwrap().target == target {
+            let distance = (renderer.pixel_to_complex(16.0, 12.0) - target).norm();
+            assert!(distance <= renderer.viewport.scale);
+            renderer.step_auto_zoom(0.1);
+            steps += 1;
+            assert!(steps < 10_000, "auto-zoom never hit the precision limit");
+        }
+        assert_eq!(renderer.viewport.scale, DEFAULT_SCALE);
+        assert_eq!(renderer.max_iterations, 200);
+
+        renderer.toggle_auto_zoom();
+        assert!(!renderer.is_auto_zooming());
+    }
+
+    #[test]
+    fn test_split_view_matches_single_renders() {
+        let mut split = test_renderer(48, 16);
+        split.change_color_scheme(ColorScheme::Red);
+        split.toggle_compare();
+        split.set_compare_scheme(ColorScheme::Blue);
+        let mut frame = vec![0; 48 * 16 * 4];
+        split.render(&mut frame);
+
+        for (side, scheme) in [ColorScheme::Red, ColorScheme::Blue]
+            .into_iter()
+            .enumerate()
+        {
+            let mut single = test_renderer(24, 16);
+            single.change_color_scheme(scheme);
+            let mut expected = vec![0; 24 * 16 * 4];
+            single.render(&mut expected);
+
+            for y in 0..16 {
+                // The first column of the right half is the divider
+                for x in side..24 {
+                    let actual = (y * 48 + side * 24 + x) * 4;
+                    let expected_index = (y * 24 + x) * 4;
+                    assert_eq!(
+                        frame[actual..actual + 4],
+                        expected[expected_index..expected_index + 4],
+                        "{scheme:?} at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_to_file_writes_sidecar() {
+        let renderer = test_renderer(32, 24);
+        let path = crate::temp_path("sidecar.png");
+
+        let stats = renderer.render_to_file(&path, true).unwrap();
+        let json = std::fs::read_to_string(RenderStats::sidecar_path(&path)).unwrap();
+        std::fs::remove_file(RenderStats::sidecar_path(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stats.version, STATS_VERSION);
+        assert!(stats.total_iterations > 0);
+        assert!((0.0..=1.0).contains(&stats.interior_fraction));
+        assert!(json.contains("\"interior_fraction\""));
+    }
+
+    #[test]
+    fn test_zoom_sequence_writes_numbered_frames() {
+        let renderer = test_renderer(16, 12);
+        let out_dir = crate::temp_path("zoom_sequence");
+
+        renderer
+            .render_zoom_sequence(-0.743, 0.131, 2.5, 0.01, 3, &out_dir)
+            .unwrap();
+
+        let first = image::open(out_dir.join("frame_0000.png")).unwrap();
+        let last = image::open(out_dir.join("frame_0002.png")).unwrap();
+        assert_eq!((first.width(), first.height()), (16, 12));
+        assert!(first != last);
+        assert!(!out_dir.join("frame_0003.png").exists());
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        // The first frame is the plain render of the starting view
+        let mut start = renderer.clone();
+        start.set_view(Complex64::new(-0.743, 0.131), 2.5);
+        assert!(first.to_rgba8().into_raw() == start.render_to_buffer(16, 12));
+    }
+
+    #[test]
+    fn test_color_jitter_is_reproducible() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.set_color_jitter(2.0);
+
+        let mut first = vec![0; 32 * 24 * 4];
+        let mut second = vec![0; 32 * 24 * 4];
+        renderer.render(&mut first);
+        renderer.render(&mut second);
+        assert!(first == second);
+
+        for (real, imag) in [(0.0, 0.0), (-1.5, 0.25), (0.3, -0.7)] {
+            assert!((-0.5..0.5).contains(&point_noise(7, real, imag)));
+        }
+    }
+
+    #[test]
+    fn test_palette_depth_coupling_follows_magnification() {
+        let reals = [0.0, 0.3, 1.0];
+        let channels_at = |renderer: &Renderer| {
+            let mut escapes = [EscapeResult::default(); 3];
+            let mut channels = [[0.0; 3]; 3];
+            renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+            channels
+        };
+
+        let mut renderer = test_renderer(8, 8);
+        renderer.color_scheme = ColorScheme::Red;
+        let plain = channels_at(&renderer);
+
+        // No offset at the default scale
+        renderer.set_palette_depth_coupling(0.25);
+        assert_eq!(channels_at(&renderer), plain);
+
+        // Zooming in by e^2 shifts the palette by half its length
+        renderer.set_magnification(std::f64::consts::E.powi(2));
+        let shifted = channels_at(&renderer);
+        assert_eq!(shifted[0], [0.0, 0.0, 0.0]);
+        for (shifted, plain) in shifted.iter().zip(&plain).skip(1) {
+            let expected = (plain[0] / 255.0 + 0.5).rem_euclid(1.0) * 255.0;
+            assert!((shifted[0] - expected).abs() < 1e-6);
         }
     }
+
+    #[test]
+    fn test_histogram_spreads_palette() {
+        let mut renderer = test_renderer(64, 48);
+        renderer.color_scheme = ColorScheme::Histogram;
+        let mut frame = vec![0; 64 * 48 * 4];
+        renderer.render_histogram(&mut frame);
+
+        let escapes = renderer.compute_region(&renderer.viewport);
+        let mut brightest = 0;
+        for (pixel, escape) in frame.chunks_exact(4).zip(&escapes) {
+            if escape.escaped {
+                brightest = brightest.max(pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32);
+            } else {
+                assert_eq!(pixel, [0, 0, 0, 255]);
+            }
+        }
+        // The slowest escapes rank last and reach the white end of the palette
+        assert_eq!(brightest, 3 * 255);
+
+        let ranks = histogram_ranks(&escapes, renderer.max_iterations);
+        assert!(ranks.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(ranks[renderer.max_iterations as usize - 1], 1.0);
+    }
+
+    #[test]
+    fn test_temporal_aa_converges_and_restarts() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.scan_config = ScanConfig::default();
+        renderer.color_scheme = ColorScheme::Zebra;
+        renderer.set_temporal_aa(true);
+        let mut frame = vec![0; 32 * 24 * 4];
+        renderer.render(&mut frame);
+        let mut frames = 1;
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+            frames += 1;
+        }
+        assert_eq!(renderer.accumulated_samples(), TEMPORAL_AA_SAMPLES);
+        assert!(frames < 2 * TEMPORAL_AA_SAMPLES);
+
+        // Averaging the jittered samples blends the stripes, where a scan alone
+        // only draws pure black and white
+        let is_gray = |pixel: &[u8]| pixel[0] != 0 && pixel[0] != 255;
+        assert!(frame.chunks_exact(4).any(is_gray));
+        let mut plain = renderer.clone();
+        plain.set_temporal_aa(false);
+        let mut scanned = vec![0; 32 * 24 * 4];
+        while plain.is_scanning() {
+            plain.render(&mut scanned);
+        }
+        assert!(!scanned.chunks_exact(4).any(is_gray));
+
+        // Its first sample is the scan's own, and any change starts over
+        renderer.zoom(0.5);
+        plain.zoom(0.5);
+        renderer.render(&mut frame);
+        assert_eq!(renderer.accumulated_samples(), 0);
+        while renderer.accumulated_samples() < 1 {
+            renderer.render(&mut frame);
+        }
+        while plain.is_scanning() {
+            plain.render(&mut scanned);
+        }
+        assert!(frame == scanned);
+        renderer.drag(&mut frame, 3, 0);
+        assert_eq!(renderer.accumulated_samples(), 0);
+    }
+
+    #[test]
+    fn test_glow_spreads_light_within_the_frame() {
+        let renderer = test_renderer(8, 4);
+        let mut frame = vec![0; 8 * 4 * 4];
+        for pixel in frame.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+        // A white pixel at the end of the second row
+        frame[(8 + 7) * 4..][..3].fill(255);
+        renderer.apply_glow(&mut frame, 1, 1.0);
+
+        let brightness = |x: usize, y: usize| frame[(y * 8 + x) * 4];
+        assert_eq!(brightness(7, 1), 255);
+        // Averaged over the pixels of their boxes that lie within the frame
+        assert_eq!(brightness(6, 1), 255 / 9);
+        assert_eq!(brightness(6, 2), 255 / 9);
+        assert_eq!(brightness(7, 0), 255 / 4);
+        assert_eq!(brightness(5, 1), 0);
+        assert_eq!(brightness(7, 3), 0);
+        // Nothing wraps around to the start of the next row
+        assert_eq!(brightness(0, 2), 0);
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[3] == 255));
+
+        // Scanning adds it to the whole frame once, not to every pass
+        let mut renderer = test_renderer(32, 24);
+        renderer.set_glow_enabled(true);
+        let mut expected = vec![0; 32 * 24 * 4];
+        renderer.render(&mut expected);
+        let mut plain = vec![0; 32 * 24 * 4];
+        renderer.set_glow_enabled(false);
+        renderer.render(&mut plain);
+        assert!(expected != plain);
+        let (radius, strength) = renderer.glow();
+        renderer.apply_glow(&mut plain, radius, strength);
+        assert!(expected == plain);
+
+        renderer.set_glow_enabled(true);
+        renderer.scan_config = ScanConfig::default();
+        renderer.escape_cache = None;
+        renderer.scan_level = 0;
+        let mut frame = vec![0; 32 * 24 * 4];
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+        assert!(frame == expected);
+    }
+
+    #[test]
+    fn test_cancelled_render_returns_early() {
+        /// The Mandelbrot set, setting `cancel` once `after` points were asked for
+        struct Tripwire {
+            cancel: Arc<AtomicBool>,
+            after: usize,
+            points: AtomicUsize,
+        }
+        impl Fractal for Tripwire {
+            fn iterate(&self, c: Complex64, max_iter: u32, bailout: f64) -> EscapeResult {
+                if self.points.fetch_add(1, Ordering::Relaxed) + 1 == self.after {
+                    self.cancel.store(true, Ordering::Relaxed);
+                }
+                FractalKind::Mandelbrot.iterate(c, max_iter, bailout)
+            }
+        }
+
+        // At the cusp of the cardioid, where orbits neither escape nor settle
+        // into a cycle for a long time, so the whole frame with supersampling
+        // would take a minute
+        let mut renderer = test_renderer(64, 48);
+        renderer.set_view(Complex64::new(0.25 + 1e-6, 0.0), 1e-8);
+        renderer.set_max_iterations(100_000);
+        renderer.set_aa_samples(2);
+        let cancel = renderer.cancel_handle();
+        let mut frame = vec![0; 64 * 48 * 4];
+
+        // A flag set beforehand stops the render before it draws anything
+        cancel.store(true, Ordering::Relaxed);
+        renderer.render(&mut frame);
+        assert!(renderer.was_cancelled());
+        assert!(!cancel.load(Ordering::Relaxed));
+        assert!(frame.iter().all(|&byte| byte == 0));
+
+        // Stopped partway, tiles are either drawn whole or left alone
+        renderer.set_max_iterations(100);
+        renderer.set_custom_fractal(Some(Arc::new(Tripwire {
+            cancel: Arc::clone(&cancel),
+            after: 100,
+            points: AtomicUsize::new(0),
+        })));
+        renderer.render(&mut frame);
+        assert!(renderer.was_cancelled());
+        assert!(!cancel.load(Ordering::Relaxed));
+        let drawn = |x: usize, y: usize| frame[(y * 64 + x) * 4 + 3] == 255;
+        let tiles = [(0, 0), (32, 0), (0, 32), (32, 32)];
+        for (tile_x, tile_y) in tiles {
+            let first = drawn(tile_x, tile_y);
+            for y in tile_y..(tile_y + RENDER_TILE).min(48) {
+                for x in tile_x..tile_x + RENDER_TILE {
+                    assert_eq!(drawn(x, y), first);
+                }
+            }
+        }
+        assert!(!tiles.iter().all(|&(x, y)| drawn(x, y)));
+
+        // The next render starts over and finishes
+        renderer.set_custom_fractal(None);
+        renderer.render(&mut frame);
+        assert!(!renderer.was_cancelled());
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[3] == 255));
+
+        // And the first slice of a scan, which starts over
+        renderer.scan_config = ScanConfig::default();
+        renderer.set_aa_samples(1);
+        let mut frame = vec![0; 64 * 48 * 4];
+        cancel.store(true, Ordering::Relaxed);
+        renderer.render(&mut frame);
+        assert!(renderer.was_cancelled());
+        assert!(frame.iter().all(|&byte| byte == 0));
+        assert!(renderer.cached_escapes().is_none());
+        assert!(renderer.is_scanning());
+        while renderer.is_scanning() {
+            renderer.render(&mut frame);
+        }
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_detached_render_is_adopted() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.toggle_auto_iterations();
+        renderer.set_view(Complex64::new(-0.75, 0.1), 1e-4);
+        let mut expected = vec![0; 32 * 24 * 4];
+        renderer.clone().render(&mut expected);
+
+        // Cancelling the copy leaves the renderer's own flag alone
+        let mut detached = renderer.detached();
+        detached.cancel_handle().store(true, Ordering::Relaxed);
+        assert!(!renderer.cancel_handle().load(Ordering::Relaxed));
+        let mut frame = vec![0; 32 * 24 * 4];
+        detached.render(&mut frame);
+        assert!(detached.was_cancelled());
+
+        let (done, frame) = thread::spawn(move || {
+            detached.render(&mut frame);
+            (detached, frame)
+        })
+        .join()
+        .unwrap();
+        assert!(!done.was_cancelled());
+        assert!(frame == expected);
+
+        assert!(renderer.escape_cache.is_none());
+        renderer.adopt_render(done);
+        assert!(renderer.max_iterations > renderer.base_iterations);
+        assert!(renderer.escape_cache.is_some());
+        assert!(!renderer.was_cancelled());
+    }
+
+    #[test]
+    fn test_supersampling_blends_edges() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.color_scheme = ColorScheme::Zebra;
+        let mut single = vec![0; 32 * 24 * 4];
+        renderer.render(&mut single);
+
+        // Zebra stripes are pure black or white, until neighbouring samples mix
+        let is_gray = |pixel: &[u8]| pixel[0] != 0 && pixel[0] != 255;
+        assert!(!single.chunks_exact(4).any(is_gray));
+
+        renderer.set_aa_samples(2);
+        let mut supersampled = vec![0; 32 * 24 * 4];
+        renderer.render(&mut supersampled);
+        assert!(supersampled.chunks_exact(4).any(is_gray));
+
+        // Blending in linear light gives lighter grays than averaging the bytes,
+        // and leaves pure colors alone
+        renderer.toggle_gamma_correction();
+        let mut plain = vec![0; 32 * 24 * 4];
+        renderer.render(&mut plain);
+        assert!(supersampled
+            .iter()
+            .zip(&plain)
+            .all(|(linear, plain)| linear >= plain));
+        assert!(supersampled
+            .iter()
+            .zip(&plain)
+            .any(|(linear, plain)| linear > plain));
+        let grays = |frame: &[u8]| -> Vec<bool> { frame.chunks_exact(4).map(is_gray).collect() };
+        assert_eq!(grays(&supersampled), grays(&plain));
+        renderer.toggle_gamma_correction();
+
+        renderer.cycle_aa_samples();
+        assert_eq!(renderer.aa_samples(), 4);
+        renderer.cycle_aa_samples();
+        assert_eq!(renderer.aa_samples(), 1);
+    }
+
+    #[test]
+    fn test_smooth_coloring_applies_to_any_palette() {
+        let mut renderer = test_renderer(8, 8);
+        renderer.change_color_scheme(ColorScheme::Rainbow);
+        let reals: Vec<f64> = (0..200).map(|x| 0.26 + x as f64 * 0.001).collect();
+        let distinct_colors = |renderer: &Renderer| {
+            let mut escapes = vec![EscapeResult::default(); reals.len()];
+            let mut channels = vec![[0.0; 3]; reals.len()];
+            renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+            let mut colors: Vec<[u8; 3]> = channels
+                .iter()
+                .map(|channels| channels.map(|channel| channel as u8))
+                .collect();
+            colors.dedup();
+            colors.len()
+        };
+
+        // Step counts give bands, fractional values a gradient across them
+        let banded = distinct_colors(&renderer);
+        renderer.set_smooth_coloring(true);
+        assert!(distinct_colors(&renderer) > banded * 2);
+    }
+
+    #[test]
+    fn test_adaptive_supersampling_only_touches_edges() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.color_scheme = ColorScheme::Zebra;
+        let mut single = vec![0; 32 * 24 * 4];
+        renderer.render(&mut single);
+
+        renderer.set_aa_samples(2);
+        let mut full = vec![0; 32 * 24 * 4];
+        renderer.render(&mut full);
+        renderer.set_adaptive_aa(true);
+        let mut adaptive = vec![0; 32 * 24 * 4];
+        renderer.render(&mut adaptive);
+
+        // Pixels are either left as they were or sampled like full supersampling
+        let mut sampled = 0;
+        for ((single, full), adaptive) in single
+            .chunks_exact(4)
+            .zip(full.chunks_exact(4))
+            .zip(adaptive.chunks_exact(4))
+        {
+            assert!(adaptive == single || adaptive == full);
+            sampled += usize::from(adaptive != single);
+        }
+        assert!(sampled > 0);
+        // The interior and flat stripes aren't sampled again
+        assert!(adaptive != full);
+    }
+
+    #[test]
+    fn test_view_state_round_trips() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.viewport.center_x = -0.743;
+        renderer.viewport.center_y = 0.131;
+        renderer.viewport.scale = 1e-4;
+        renderer.max_iterations = 900;
+        renderer.color_scheme = ColorScheme::BlackAndWhite;
+        let json = serde_json::to_string(&renderer.export_state()).unwrap();
+        assert!(json.contains("\"black-and-white\""));
+
+        let mut restored = test_renderer(32, 24);
+        restored.scan_level = 3;
+        restored
+            .load_state(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!(restored.scan_level, 0);
+
+        let mut expected = vec![0; 32 * 24 * 4];
+        let mut actual = vec![0; 32 * 24 * 4];
+        renderer.render(&mut expected);
+        restored.render(&mut actual);
+        assert!(expected == actual);
+
+        let token = renderer.to_location_string();
+        assert!(token.len() < 64, "{token}");
+        let state = ViewState::from_location_string(&token.to_uppercase()).unwrap();
+        assert_eq!(state.center_x, -0.743);
+        assert_eq!(state.scale, 1e-4);
+        assert_eq!(state.max_iterations, 900);
+        assert_eq!(state.color_scheme, ColorScheme::BlackAndWhite);
+    }
+
+    #[test]
+    fn test_malformed_locations_are_rejected() {
+        let token = test_renderer(8, 8).to_location_string();
+        let cases = [
+            (format!("{token}0"), LocationError::Length(token.len() + 1)),
+            (
+                token.replacen(&token[..1], "g", 1),
+                LocationError::NotHex('g'),
+            ),
+            (format!("02{}", &token[2..]), LocationError::Version(2)),
+            (
+                format!("{}ff", &token[..token.len() - 2]),
+                LocationError::Scheme(255),
+            ),
+            (
+                // A zero scale
+                format!("{}{}{}", &token[..34], "0".repeat(16), &token[50..]),
+                LocationError::Scale(0.0),
+            ),
+        ];
+        for (token, expected) in cases {
+            assert_eq!(
+                ViewState::from_location_string(&token).err(),
+                Some(expected),
+                "{token}"
+            );
+        }
+        assert!(LocationError::Length(3).to_string().contains("60"));
+    }
+
+    #[test]
+    fn test_invalid_view_states_are_not_loaded() {
+        let valid = test_renderer(8, 8).export_state();
+        let cases = [
+            (
+                ViewState {
+                    center_x: f64::NAN,
+                    ..valid
+                },
+                LocationError::Center,
+            ),
+            (
+                ViewState {
+                    scale: -1.0,
+                    ..valid
+                },
+                LocationError::Scale(-1.0),
+            ),
+            (
+                ViewState {
+                    max_iterations: 0,
+                    ..valid
+                },
+                LocationError::Iterations,
+            ),
+        ];
+        for (state, expected) in cases {
+            let mut renderer = test_renderer(8, 8);
+            renderer.scan_level = 3;
+            assert_eq!(renderer.load_state(state), Err(expected));
+            // Nothing of the invalid view was applied
+            assert_eq!(renderer.scan_level, 3);
+            assert_eq!(renderer.viewport.scale, valid.scale);
+            assert_eq!(renderer.max_iterations, valid.max_iterations);
+        }
+    }
+
+    #[test]
+    fn test_held_zoom_eases_out_after_release() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.scan_config.enabled = true;
+        let scale = renderer.viewport.scale;
+
+        renderer.hold_zoom(true);
+        let mut previous = renderer.viewport.scale;
+        for _ in 0..10 {
+            renderer.scan_level = 2;
+            renderer.step_animation(0.05);
+            assert!(renderer.viewport.scale < previous);
+            assert_eq!(renderer.scan_level, 0);
+            previous = renderer.viewport.scale;
+        }
+
+        // The scale keeps going a little after release, then stops on the target
+        renderer.release_zoom();
+        let target = renderer.animation.as_ref().unwrap().target_scale;
+        while renderer.is_animating() {
+            renderer.step_animation(0.05);
+        }
+        assert_eq!(renderer.viewport.scale, target);
+        assert!(target < previous && previous < scale);
+
+        // Zooming out stops at the widest view
+        renderer.hold_zoom(false);
+        for _ in 0..200 {
+            renderer.step_animation(0.1);
+        }
+        assert!(renderer.viewport.scale <= MAX_SCALE);
+    }
+
+    #[test]
+    fn test_zoom_stays_between_scale_limits() {
+        let mut renderer = test_renderer(32, 24);
+        renderer.zoom(100.0);
+        assert_eq!(renderer.viewport.scale, DEFAULT_SCALE);
+        renderer.set_max_scale(1000.0);
+        renderer.zoom(100.0);
+        assert_eq!(renderer.viewport.scale, DEFAULT_SCALE * 100.0);
+
+        // Zooming in stops short of where f64 can't tell pixels apart
+        renderer.set_view(Complex64::new(-0.75, 0.1), 1e-12);
+        assert!(!renderer.is_at_precision_limit());
+        while renderer.viewport.scale > renderer.min_scale() * 10.0 {
+            renderer.zoom(0.5);
+        }
+        let scale = renderer.viewport.scale;
+        for _ in 0..10 {
+            renderer.zoom(0.5);
+        }
+        assert!(renderer.viewport.scale >= renderer.min_scale());
+        assert!(renderer.viewport.scale < scale);
+        assert!(!renderer.is_precision_exhausted());
+        // Down to where a step of the wheel gets refused too
+        for _ in 0..10 {
+            renderer.zoom(renderer.zoom_step());
+        }
+        assert!(renderer.is_at_precision_limit());
+        assert!(renderer
+            .hud_lines()
+            .contains(&"f64 precision limit".to_string()));
+    }
+
+    #[test]
+    fn test_pan_speed_and_zoom_step() {
+        let mut renderer = test_renderer(32, 24);
+        let center = renderer.viewport.center();
+        let scale = renderer.viewport.scale;
+        renderer.set_pan_speed(0.1);
+        // A tenth of the 24 pixel side, rounded to whole pixels
+        renderer.pan(1.0, -2.0);
+        let moved = renderer.viewport.center() - center;
+        let pixel_size = renderer.viewport.pixel_size();
+        assert!((moved - Complex64::new(2.0, -5.0) * pixel_size).norm() < 1e-12);
+
+        // Finer steps zoom held keys more slowly too
+        let mut fine = test_renderer(32, 24);
+        fine.set_zoom_step(0.95);
+        let mut coarse = test_renderer(32, 24);
+        for renderer in [&mut fine, &mut coarse] {
+            renderer.hold_zoom(true);
+            renderer.step_animation(0.1);
+        }
+        assert!(coarse.viewport.scale < fine.viewport.scale);
+        assert!(fine.viewport.scale < scale);
+    }
+
+    #[test]
+    fn test_inertial_pan_glides_to_a_stop() {
+        let mut renderer = test_renderer(320, 240);
+        let start = renderer.viewport.center_x;
+        renderer.hold_pan(1.0, 0.0);
+        assert!(renderer.is_animating());
+        for _ in 0..60 {
+            renderer.step_animation(1.0 / 60.0);
+            assert!(renderer.pan_velocity().0 <= HELD_PAN_RATE);
+        }
+        // Long frames don't make it run away
+        renderer.step_animation(10.0);
+        assert!(renderer.pan_velocity().0 <= HELD_PAN_RATE);
+        assert!(renderer.pan_velocity().0 > 0.9 * HELD_PAN_RATE);
+        assert_eq!(renderer.pan_velocity().1, 0.0);
+
+        // Released, it keeps going for a while and then stops
+        let held = renderer.viewport.center_x;
+        assert!(held > start);
+        renderer.hold_pan(0.0, 0.0);
+        let mut steps = 0;
+        while renderer.is_animating() {
+            renderer.step_animation(1.0 / 60.0);
+            steps += 1;
+            assert!(steps < 600, "the pan never stopped");
+        }
+        assert!(steps > 10);
+        assert!(renderer.viewport.center_x > held);
+        assert_eq!(renderer.pan_velocity(), (0.0, 0.0));
+        // Only ever by whole pixels
+        let pixels = (renderer.viewport.center_x - start) / renderer.viewport.pixel_size();
+        assert!((pixels - pixels.round()).abs() < 1e-6);
+
+        // Without inertia nothing glides
+        renderer.hold_pan(0.0, -1.0);
+        renderer.step_animation(0.1);
+        renderer.set_pan_inertia(false);
+        assert!(!renderer.is_animating());
+    }
+
+    #[test]
+    fn test_invert_palette_keeps_interior() {
+        let mut renderer = test_renderer(8, 8);
+        renderer.color_scheme = ColorScheme::Red;
+        renderer.toggle_invert_palette();
+
+        let reals = [0.0, 2.0];
+        let mut escapes = [EscapeResult::default(); 2];
+        let mut channels = [[0.0; 3]; 2];
+        renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+
+        // The origin is interior, 2.0 escapes straight away and becomes full red
+        assert_eq!(channels[0], [0.0, 0.0, 0.0]);
+        assert!(channels[1][0] > 250.0);
+    }
+
+    #[test]
+    fn test_invert_palette_applies_to_orbit_traps_and_distances() {
+        let reals = [0.0, 0.5, 1.0];
+        let colors = |renderer: &Renderer| {
+            let mut escapes = [EscapeResult::default(); 3];
+            let mut channels = [[0.0; 3]; 3];
+            renderer.channels_row(&reals, 0.2, &mut escapes, &mut channels);
+            channels
+        };
+        for scheme in [ColorScheme::OrbitTrap, ColorScheme::DistanceEstimate] {
+            let mut renderer = test_renderer(8, 8);
+            renderer.change_color_scheme(scheme);
+            let plain = colors(&renderer);
+            renderer.toggle_invert_palette();
+            let inverted = colors(&renderer);
+
+            // The red channel follows the glow, which flips end for end
+            assert_eq!(inverted[0], [0.0; 3], "{scheme:?}");
+            for (plain, inverted) in plain[1..].iter().zip(&inverted[1..]) {
+                assert!((plain[0] + inverted[0] - 255.0).abs() < 1e-9, "{scheme:?}");
+            }
+        }
+
+        // Newton points that converged quickly turn dark rather than bright
+        let mut renderer = test_renderer(8, 8);
+        renderer.set_fractal_kind(FractalKind::Newton);
+        let plain = colors(&renderer);
+        renderer.toggle_invert_palette();
+        let inverted = colors(&renderer);
+        for (plain, inverted) in plain.iter().zip(&inverted) {
+            assert_ne!(plain, inverted);
+        }
+    }
+
+    #[test]
+    fn test_interior_color_applies_to_every_scheme() {
+        // The origin is interior, 2.0 escapes straight away
+        let reals = [0.0, -0.1, 2.0];
+        for scheme in [
+            ColorScheme::Smooth,
+            ColorScheme::Red,
+            ColorScheme::OrbitTrap,
+            ColorScheme::DistanceEstimate,
+            ColorScheme::Angle,
+            ColorScheme::StripeAverage,
+        ] {
+            let mut renderer = test_renderer(8, 8);
+            renderer.change_color_scheme(scheme);
+            let mut escapes = [EscapeResult::default(); 3];
+            let mut black = [[0.0; 3]; 3];
+            renderer.channels_row(&reals, 0.0, &mut escapes, &mut black);
+            assert_eq!(black[0], [0.0; 3], "{scheme:?}");
+
+            renderer.set_interior_color([20, 0, 40]);
+            let mut channels = [[0.0; 3]; 3];
+            renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+            assert_eq!(channels[0], [20.0, 0.0, 40.0], "{scheme:?}");
+            assert_eq!(channels[1], [20.0, 0.0, 40.0], "{scheme:?}");
+            assert_eq!(channels[2], black[2], "{scheme:?}");
+
+            // Shading tells the two interior points apart by where their orbits end
+            renderer.set_interior_shading(true);
+            renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+            assert_eq!(channels[0], [20.0, 0.0, 40.0], "{scheme:?}");
+            assert!(channels[1][1] > 0.0, "{scheme:?}");
+        }
+
+        let mut renderer = test_renderer(8, 8);
+        renderer.change_color_scheme(ColorScheme::Histogram);
+        renderer.set_interior_color([20, 0, 40]);
+        let mut frame = vec![0; 8 * 8 * 4];
+        renderer.render(&mut frame);
+        // The view is centered on the set
+        let center = (4 * 8 + 4) * 4;
+        assert_eq!(frame[center..center + 4], [20, 0, 40, 255]);
+    }
+
+    #[test]
+    fn test_interior_shading_matches_across_render_paths() {
+        let mut renderer = test_renderer(96, 72);
+        renderer.set_interior_color([20, 0, 40]);
+        renderer.set_interior_shading(true);
+        let mut frame = vec![0; 96 * 72 * 4];
+        renderer.render(&mut frame);
+        // Traced here, iterated point by point there
+        assert!(renderer.cached_escapes().is_some());
+        assert!(frame == renderer.render_to_buffer(96, 72));
+    }
+
+    #[test]
+    fn test_interior_shading_is_smooth_inside_a_bulb() {
+        // A row across the period 3 bulb, where the period check stops each
+        // orbit at another point of the cycle
+        let reals: Vec<f64> = (0..40).map(|x| -0.13 + x as f64 * 4e-4).collect();
+        let mut shaded = Vec::new();
+        for scheme in [ColorScheme::Smooth, ColorScheme::OrbitTrap] {
+            let mut renderer = test_renderer(8, 8);
+            renderer.change_color_scheme(scheme);
+            renderer.set_interior_shading(true);
+            let mut escapes = vec![EscapeResult::default(); reals.len()];
+            let mut channels = vec![[0.0; 3]; reals.len()];
+            renderer.channels_row(&reals, 0.745, &mut escapes, &mut channels);
+            assert!(escapes.iter().all(|escape| !escape.escaped));
+            shaded.push(channels);
+        }
+
+        assert_eq!(shaded[0], shaded[1]);
+        for pair in shaded[0].windows(2) {
+            assert!((pair[0][0] - pair[1][0]).abs() < 2.0, "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn test_newton_basins_get_their_own_hues() {
+        let mut renderer = test_renderer(8, 8);
+        renderer.set_fractal_kind(FractalKind::Newton);
+        // Schemes don't apply to Newton basins
+        renderer.change_color_scheme(ColorScheme::Red);
+
+        let mut colors = Vec::new();
+        for root in crate::fractals::NEWTON_ROOTS {
+            let mut escapes = [EscapeResult::default(); 1];
+            let mut channels = [[0.0; 3]; 1];
+            renderer.channels_row(&[root.re * 1.5], root.im * 1.5, &mut escapes, &mut channels);
+            assert!(escapes[0].escaped);
+            colors.push(channels[0].map(|channel| channel as u8));
+        }
+        assert!(colors[0] != colors[1] && colors[1] != colors[2] && colors[0] != colors[2]);
+    }
+
+    #[test]
+    fn test_distance_estimate_lights_up_boundary() {
+        let mut renderer = test_renderer(8, 8);
+        renderer.change_color_scheme(ColorScheme::DistanceEstimate);
+
+        // Inside, right next to the cusp at 0.25, and far outside
+        let pixel_size = renderer.viewport.pixel_size();
+        let reals = [0.0, 0.25 + pixel_size / 4.0, 2.5];
+        let mut escapes = [EscapeResult::default(); 3];
+        let mut channels = [[0.0; 3]; 3];
+        renderer.channels_row(&reals, 0.0, &mut escapes, &mut channels);
+
+        assert_eq!(channels[0], [0.0, 0.0, 0.0]);
+        assert!(channels[1][0] > 200.0);
+        assert!(channels[2][0] < 10.0);
+    }
 }
@@ -0,0 +1,45 @@
+// One invocation per pixel, matching `mandelbrot` and the smooth color
+// scheme on the CPU, but in f32.
+
+struct Params {
+    center: vec2<f32>,
+    pixel_size: f32,
+    max_iterations: u32,
+    size: vec2<u32>,
+    // Pixels per row of the output, which is padded for the texture copy
+    row_stride: u32,
+    bailout: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> pixels: array<u32>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.size.x || id.y >= params.size.y {
+        return;
+    }
+
+    let c = params.center + (vec2<f32>(id.xy) - vec2<f32>(params.size) / 2.0) * params.pixel_size;
+    let bailout_sqr = params.bailout * params.bailout;
+    var z = vec2<f32>(0.0, 0.0);
+    var iterations = 0u;
+    while iterations < params.max_iterations && dot(z, z) <= bailout_sqr {
+        z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        iterations += 1u;
+    }
+
+    // Black for points inside the set
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+    if iterations < params.max_iterations {
+        let max_iterations = f32(params.max_iterations);
+        let smooth_iterations = f32(iterations) + 1.0 - log2(log(dot(z, z)) / 2.0);
+        // A limit of 1 would divide by log2(1) = 0, as in smooth_color
+        let log_max = max(log2(max_iterations), 1.0);
+        let nu = log2(clamp(smooth_iterations, 1.0, max_iterations)) / log_max;
+        color = mix(vec3<f32>(9.0, 0.0, 255.0), vec3<f32>(15.0, 7.0, 100.0), fract(nu));
+    }
+
+    let rgb = vec3<u32>(color);
+    pixels[id.y * params.row_stride + id.x] = rgb.r | (rgb.g << 8u) | (rgb.b << 16u) | (255u << 24u);
+}
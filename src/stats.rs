@@ -0,0 +1,183 @@
+use crate::args::ColorScheme;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a field is renamed or removed, so tools reading old
+/// sidecars can tell the layouts apart.
+pub const STATS_VERSION: u32 = 1;
+
+/// First byte of a location token, bumped whenever its layout changes.
+const LOCATION_VERSION: u8 = 1;
+/// Bytes in a location token: the version, the center and scale as `f64`s,
+/// the iteration limit as a `u32` and the color scheme's index.
+const LOCATION_BYTES: usize = 1 + 3 * 8 + 4 + 1;
+
+/// Where a render looked on the complex plane.
+#[derive(Clone, Debug, Serialize)]
+pub struct ViewStats {
+    pub center_re: f64,
+    pub center_im: f64,
+    pub scale: f64,
+    pub width: u32,
+    pub height: u32,
+    /// `[re, im]` of the top-left corner of the image
+    pub upper_left: [f64; 2],
+    /// `[re, im]` of the bottom-right corner of the image
+    pub lower_right: [f64; 2],
+}
+
+/// Everything needed to reproduce a render, plus a few measurements of it.
+#[derive(Clone, Debug, Serialize)]
+pub struct RenderStats {
+    pub version: u32,
+    pub view: ViewStats,
+    pub max_iterations: u32,
+    pub color_scheme: String,
+    /// Sum of the iteration counts of every pixel
+    pub total_iterations: u64,
+    pub render_time_ms: f64,
+    /// Share of pixels that never escaped, between 0.0 and 1.0
+    pub interior_fraction: f64,
+}
+
+/// A spot worth coming back to: enough to restore the view, without the
+/// window size.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ViewState {
+    pub center_x: f64,
+    pub center_y: f64,
+    pub scale: f64,
+    pub max_iterations: u32,
+    pub color_scheme: ColorScheme,
+}
+
+impl ViewState {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Packs the view into a short hex token to share, which
+    /// [`Self::from_location_string`] turns back into the exact same view.
+    pub fn to_location_string(&self) -> String {
+        let mut bytes = Vec::with_capacity(LOCATION_BYTES);
+        bytes.push(LOCATION_VERSION);
+        bytes.extend_from_slice(&self.center_x.to_le_bytes());
+        bytes.extend_from_slice(&self.center_y.to_le_bytes());
+        bytes.extend_from_slice(&self.scale.to_le_bytes());
+        bytes.extend_from_slice(&self.max_iterations.to_le_bytes());
+        let scheme = ColorScheme::ALL
+            .iter()
+            .position(|scheme| *scheme == self.color_scheme)
+            .expect("every scheme is in ColorScheme::ALL");
+        bytes.push(scheme as u8);
+
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Reads a token written by [`Self::to_location_string`], ignoring
+    /// surrounding whitespace and the case of the hex digits.
+    pub fn from_location_string(token: &str) -> Result<Self, LocationError> {
+        let token = token.trim();
+        if let Some(invalid) = token.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(LocationError::NotHex(invalid));
+        }
+        if token.len() != LOCATION_BYTES * 2 {
+            return Err(LocationError::Length(token.len()));
+        }
+        let bytes: Vec<u8> = (0..token.len())
+            .step_by(2)
+            .map(|start| u8::from_str_radix(&token[start..start + 2], 16).unwrap())
+            .collect();
+
+        if bytes[0] != LOCATION_VERSION {
+            return Err(LocationError::Version(bytes[0]));
+        }
+        let float = |at: usize| f64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let (center_x, center_y, scale) = (float(1), float(9), float(17));
+        let max_iterations = u32::from_le_bytes(bytes[25..29].try_into().unwrap());
+        let color_scheme = *ColorScheme::ALL
+            .get(bytes[29] as usize)
+            .ok_or(LocationError::Scheme(bytes[29]))?;
+
+        let state = Self {
+            center_x,
+            center_y,
+            scale,
+            max_iterations,
+            color_scheme,
+        };
+        state.validate()?;
+        Ok(state)
+    }
+
+    /// Checks the view can be shown: a finite center, a scale greater than
+    /// 0 and at least one iteration.
+    pub fn validate(&self) -> Result<(), LocationError> {
+        if !self.center_x.is_finite() || !self.center_y.is_finite() {
+            return Err(LocationError::Center);
+        }
+        if !(self.scale.is_finite() && self.scale > 0.0) {
+            return Err(LocationError::Scale(self.scale));
+        }
+        if self.max_iterations == 0 {
+            return Err(LocationError::Iterations);
+        }
+        Ok(())
+    }
+}
+
+/// Why a location token couldn't be read, see
+/// [`ViewState::from_location_string`], or a saved view couldn't be used,
+/// see [`ViewState::validate`].
+#[derive(Debug, PartialEq)]
+pub enum LocationError {
+    NotHex(char),
+    /// The number of hex digits, which has to be twice `LOCATION_BYTES`
+    Length(usize),
+    /// Written by a version that laid tokens out differently
+    Version(u8),
+    Scheme(u8),
+    Center,
+    Scale(f64),
+    Iterations,
+}
+
+impl fmt::Display for LocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocationError::NotHex(c) => write!(f, "{c:?} isn't a hex digit"),
+            LocationError::Length(len) => {
+                write!(f, "expected {} hex digits, got {len}", LOCATION_BYTES * 2)
+            }
+            LocationError::Version(version) => {
+                write!(f, "unknown location version {version}")
+            }
+            LocationError::Scheme(index) => write!(f, "unknown color scheme number {index}"),
+            LocationError::Center => write!(f, "the center isn't a finite number"),
+            LocationError::Scale(scale) => write!(f, "scale {scale} isn't greater than 0"),
+            LocationError::Iterations => write!(f, "the iteration limit is 0"),
+        }
+    }
+}
+
+impl Error for LocationError {}
+
+impl RenderStats {
+    /// The sidecar sits next to the image with a `.json` extension.
+    pub fn sidecar_path<P: AsRef<Path>>(image_path: P) -> PathBuf {
+        image_path.as_ref().with_extension("json")
+    }
+
+    pub fn write_sidecar<P: AsRef<Path>>(&self, image_path: P) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::sidecar_path(image_path), json)
+    }
+}
@@ -0,0 +1,151 @@
+use num_complex::Complex64;
+
+/// The part of the complex plane shown by a `width` by `height` pixel image,
+/// and the mapping between the two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub center_x: f64,
+    pub center_y: f64,
+    /// How many units the image spans across its smaller dimension
+    pub scale: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn new(center: Complex64, scale: f64, width: u32, height: u32) -> Self {
+        Self {
+            center_x: center.re,
+            center_y: center.im,
+            scale,
+            width,
+            height,
+        }
+    }
+
+    /// The view of a `width` by `height` image that shows the whole region
+    /// between two opposite corners, centered on it. When the region has
+    /// another aspect ratio than the image, the scale fits its longer side
+    /// and the other one gets equal margins on both sides.
+    pub fn from_corners(a: Complex64, b: Complex64, width: u32, height: u32) -> Self {
+        let span = b - a;
+        let (width_f, height_f) = (width.max(1) as f64, height.max(1) as f64);
+        let pixel_size = (span.re.abs() / width_f).max(span.im.abs() / height_f);
+        Self::new(
+            (a + b) / 2.0,
+            pixel_size * width_f.min(height_f),
+            width,
+            height,
+        )
+    }
+
+    pub fn center(&self) -> Complex64 {
+        Complex64::new(self.center_x, self.center_y)
+    }
+
+    /// The same view at another image size.
+    pub fn with_size(&self, width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            ..*self
+        }
+    }
+
+    /// The size of one pixel on the complex plane. Pixels are square, so
+    /// shapes keep their proportions whatever the image's aspect ratio.
+    pub fn pixel_size(&self) -> f64 {
+        self.scale / self.width.min(self.height).max(1) as f64
+    }
+
+    /// Maps a pixel position to its point on the complex plane.
+    pub fn pixel_to_complex(&self, x: f64, y: f64) -> Complex64 {
+        let pixel_size = self.pixel_size();
+        Complex64::new(
+            self.center_x + (x - self.width as f64 / 2.0) * pixel_size,
+            self.center_y + (y - self.height as f64 / 2.0) * pixel_size,
+        )
+    }
+
+    /// Maps a point on the complex plane to its pixel position, the inverse
+    /// of [`Self::pixel_to_complex`]. Points outside the view map outside
+    /// the image.
+    pub fn complex_to_pixel(&self, c: Complex64) -> (f64, f64) {
+        let pixel_size = self.pixel_size();
+        (
+            (c.re - self.center_x) / pixel_size + self.width as f64 / 2.0,
+            (c.im - self.center_y) / pixel_size + self.height as f64 / 2.0,
+        )
+    }
+}
+
+/// The length of a buffer with `channels` values per pixel for a `width`
+/// by `height` image, or `None` if it would overflow `usize`.
+pub fn buffer_len(width: u32, height: u32, channels: usize) -> Option<usize> {
+    let pixels = usize::try_from(width)
+        .ok()?
+        .checked_mul(usize::try_from(height).ok()?)?;
+    pixels.checked_mul(channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixel_mapping_round_trips() {
+        let view = Viewport::new(Complex64::new(-0.5, 0.25), 2.5, 800, 600);
+        assert_eq!(view.pixel_to_complex(400.0, 300.0), view.center());
+
+        for (x, y) in [(0.0, 0.0), (799.0, 599.0), (123.5, 456.25), (-10.0, 900.0)] {
+            let (back_x, back_y) = view.complex_to_pixel(view.pixel_to_complex(x, y));
+            assert!((back_x - x).abs() < 1e-9 && (back_y - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_large_frames_map_without_wrapping() {
+        // A print-sized export, past where u32 byte offsets would wrap
+        let (width, height) = (40_000, 30_000);
+        let view = Viewport::new(Complex64::new(-0.5, 0.0), 3.0, width, height);
+        // Which only fits in a 64 bit usize
+        assert_eq!(
+            buffer_len(width, height, 4),
+            usize::try_from(4_800_000_000_u64).ok()
+        );
+
+        let corner = view.pixel_to_complex(width as f64, height as f64);
+        assert!((corner - Complex64::new(1.5, 1.5)).norm() < 1e-12);
+        let (x, y) = view.complex_to_pixel(view.pixel_to_complex(39_999.0, 29_999.0));
+        assert!((x - 39_999.0).abs() < 1e-6 && (y - 29_999.0).abs() < 1e-6);
+
+        assert_eq!(buffer_len(u32::MAX, u32::MAX, 4), None);
+    }
+
+    #[test]
+    fn test_corners_fit_the_image() {
+        let (a, b) = (Complex64::new(-2.5, 1.5), Complex64::new(1.5, -1.5));
+        let view = Viewport::from_corners(a, b, 800, 600);
+        assert_eq!(view.center(), Complex64::new(-0.5, 0.0));
+        assert_eq!(view.scale, 3.0);
+        assert!((view.pixel_to_complex(0.0, 0.0) - Complex64::new(-2.5, -1.5)).norm() < 1e-12);
+
+        // A square region in a wide image is letterboxed at the sides
+        let view = Viewport::from_corners(a, Complex64::new(1.5, -2.5), 800, 400);
+        assert_eq!(view.center(), Complex64::new(-0.5, -0.5));
+        assert_eq!(view.scale, 4.0);
+        assert!((view.pixel_to_complex(400.0, 0.0).im - -2.5).abs() < 1e-12);
+        assert!(view.pixel_to_complex(0.0, 0.0).re < -2.5);
+    }
+
+    #[test]
+    fn test_pixels_are_square() {
+        for (width, height) in [(800, 600), (600, 800), (640, 640)] {
+            let view = Viewport::new(Complex64::new(-0.5, 0.25), 2.5, width, height);
+            let (x, y) = (width as f64 / 2.0, height as f64 / 2.0);
+            let delta = view.pixel_to_complex(x + 1.0, y + 1.0) - view.center();
+            assert!((delta.re - delta.im).abs() < 1e-15);
+            assert!((delta.re - 2.5 / width.min(height) as f64).abs() < 1e-15);
+        }
+    }
+}